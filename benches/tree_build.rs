@@ -0,0 +1,59 @@
+use {
+    broot::{
+        app::AppContext,
+        cli::Args,
+        conf::Conf,
+        task_sync::Dam,
+        tree::TreeOptions,
+        tree_build::TreeBuilder,
+        verb::VerbStore,
+    },
+    clap::Parser,
+    glassbench::*,
+    std::{fs, path::Path},
+};
+
+/// build a fixture of about 100k entries: 8420 directories
+/// on 3 levels, with 12 files in each leaf directory
+fn make_fixture(root: &Path) {
+    for a in 0..20 {
+        for b in 0..20 {
+            for c in 0..20 {
+                let dir = root.join(format!("a{a}/b{b}/c{c}"));
+                fs::create_dir_all(&dir).unwrap();
+                for f in 0..12 {
+                    fs::write(dir.join(format!("file_{f}.txt")), "").unwrap();
+                }
+            }
+        }
+    }
+}
+
+fn bench_tree_build(gb: &mut Bench) {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    make_fixture(&root);
+    let mut conf = Conf::default();
+    let verb_store = VerbStore::new(&mut conf).unwrap();
+    let args = Args::parse_from(["broot", root.to_str().unwrap()]);
+    let con = AppContext::from(args, verb_store, &conf).unwrap();
+    let build = || {
+        let tree = TreeBuilder::from(root.clone(), TreeOptions::default(), 50, &con)
+            .unwrap()
+            .build_tree(true, &Dam::unlimited())
+            .unwrap();
+        pretend_used(tree);
+    };
+    gb.task("total build - sequential", |b| {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+        b.iter(|| pool.install(build));
+    });
+    gb.task("total build - parallel", |b| {
+        b.iter(build);
+    });
+}
+
+glassbench!(
+    "Tree Building",
+    bench_tree_build,
+);
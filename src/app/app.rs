@@ -4,7 +4,7 @@ use {
         browser::BrowserState,
         cli::TriBool,
         command::{Command, Sequence},
-        conf::Conf,
+        conf::{self, Conf},
         display::{Areas, Screen, W},
         errors::ProgramError,
         file_sum,
@@ -25,7 +25,10 @@ use {
         Sender,
         unbounded,
     },
-    crokey::crossterm::event::Event,
+    crokey::crossterm::{
+        event::{DisableMouseCapture, EnableMouseCapture, Event},
+        QueueableCommand,
+    },
     std::{
         io::Write,
         path::PathBuf,
@@ -33,7 +36,7 @@ use {
         sync::{Arc, Mutex},
     },
     strict::NonEmptyVec,
-    termimad::EventSource,
+    termimad::{EventSource, TimedEvent},
 };
 
 
@@ -73,6 +76,9 @@ pub struct App {
 
     /// counter incremented at every draw
     drawing_count: usize,
+
+    /// set when the displayed directories are watched
+    dir_watcher: Option<DirWatcher>,
 }
 
 impl App {
@@ -113,6 +119,7 @@ impl App {
             tx_seqs,
             rx_seqs,
             drawing_count: 0,
+            dir_watcher: None,
         };
         if let Some(path) = con.initial_file.as_ref() {
             // open initial_file in preview
@@ -339,18 +346,20 @@ impl App {
                 if self.close_panel(close_idx) {
                     let screen = self.screen;
                     self.mut_state().refresh(screen, con);
+                    let app_cmd_context = AppCmdContext {
+                        panel_skin,
+                        preview_panel: self.preview_panel,
+                        stage_panel: self.stage_panel,
+                        screen,
+                        con,
+                    };
                     if let Some(new_arg) = new_arg {
                         self.mut_panel().set_input_arg(new_arg);
                         let new_input = self.panel().get_input_content();
                         let cmd = Command::from_raw(new_input, false);
-                        let app_cmd_context = AppCmdContext {
-                            panel_skin,
-                            preview_panel: self.preview_panel,
-                            stage_panel: self.stage_panel,
-                            screen,
-                            con,
-                        };
                         self.mut_panel().apply_command(w, &cmd, app_state, &app_cmd_context)?;
+                    } else {
+                        self.mut_panel().refresh_input_status(app_state, &app_cmd_context);
                     }
                 } else {
                     self.quitting = true;
@@ -453,6 +462,41 @@ impl App {
                             }
                         }
                     }
+                    Internal::toggle_mouse_capture => {
+                        if is_input_invocation {
+                            self.mut_panel().clear_input_invocation(con);
+                        }
+                        con.capture_mouse = !con.capture_mouse;
+                        if con.capture_mouse {
+                            w.queue(EnableMouseCapture)?;
+                            self.mut_panel().set_message("The mouse is captured by broot");
+                        } else {
+                            w.queue(DisableMouseCapture)?;
+                            self.mut_panel().set_message(
+                                "The mouse isn't captured anymore, you can select text with it",
+                            );
+                        }
+                    }
+                    Internal::toggle_watch => {
+                        if is_input_invocation {
+                            self.mut_panel().clear_input_invocation(con);
+                        }
+                        if self.dir_watcher.take().is_some() {
+                            self.mut_panel().set_message("Directories aren't watched anymore");
+                        } else {
+                            match DirWatcher::new() {
+                                Ok(dir_watcher) => {
+                                    self.dir_watcher = Some(dir_watcher);
+                                    self.mut_panel().set_message(
+                                        "Trees will be refreshed on changes of their directories",
+                                    );
+                                }
+                                Err(e) => {
+                                    self.mut_panel().set_error(format!("Can't watch directories: {e}"));
+                                }
+                            }
+                        }
+                    }
                     Internal::set_syntax_theme => {
                         let arg = cmd
                             .as_verb_invocation()
@@ -548,6 +592,8 @@ impl App {
                 for i in 0..self.panels.len().get() {
                     self.panels[i].mut_state().refresh(self.screen, con);
                 }
+                // the status may depend on the stage, which may have changed
+                self.mut_panel().refresh_input_status(app_state, &app_cmd_context);
             }
         }
         if let Some(text) = error {
@@ -646,6 +692,47 @@ impl App {
         Ok(())
     }
 
+    /// wait for the next event or command sequence.
+    ///
+    /// When watching, the displayed directories are watched. If one
+    /// of them changed, the panels are refreshed and None is returned,
+    /// so that they're redrawn.
+    fn next_event_or_sequence(
+        &mut self,
+        skin: &AppSkin,
+        dam: &mut Dam,
+        app_state: &mut AppState,
+        con: &AppContext,
+    ) -> Option<Either<Option<TimedEvent>, Option<Sequence>>> {
+        let Some(dir_watcher) = self.dir_watcher.as_mut() else {
+            return Some(dam.next(&self.rx_seqs));
+        };
+        dir_watcher.update(self.panels.iter().flat_map(|panel| panel.state().watched_dirs()));
+        if let Some(next) = dam.next_unless(&self.rx_seqs, dir_watcher.changes()) {
+            return Some(next);
+        }
+        // changes often come in bursts, we wait for the end of it,
+        // unless the user does something
+        if let Some(next) = dam.next_before(&self.rx_seqs, WATCH_DELAY) {
+            return Some(next);
+        }
+        dir_watcher.clear();
+        info!("watched directories changed, refreshing");
+        app_state.stage.refresh();
+        for panel in &mut self.panels {
+            panel.mut_state().refresh(self.screen, con);
+        }
+        let app_cmd_context = AppCmdContext {
+            panel_skin: &skin.focused,
+            preview_panel: self.preview_panel,
+            stage_panel: self.stage_panel,
+            screen: self.screen,
+            con,
+        };
+        self.mut_panel().refresh_input_status(app_state, &app_cmd_context);
+        None
+    }
+
     /// do the pending tasks, if any, and refresh the screen accordingly
     fn do_pending_tasks(
         &mut self,
@@ -687,21 +774,24 @@ impl App {
         dam: &mut Dam,
     ) -> Result<(), ProgramError> {
         let screen = self.screen;
-        // we start with the focused panel
-        if self.panel().has_pending_task() {
-            return self.mut_panel().do_pending_task(app_state, screen, con, dam);
-        }
-        // then the other ones
-        for idx in 0..self.panels.len().get() {
-            if idx != self.active_panel_idx {
-                let panel = &mut self.panels[idx];
-                if panel.has_pending_task() {
-                    return panel.do_pending_task(app_state, screen, con, dam);
+        // we start with the focused panel, then the other ones
+        let idx = std::iter::once(self.active_panel_idx)
+            .chain(0..self.panels.len().get())
+            .find(|&idx| self.panels[idx].has_pending_task());
+        let Some(idx) = idx else {
+            warn!("unexpected lack of pending task");
+            return Ok(());
+        };
+        let res = self.panels[idx].do_pending_task(app_state, screen, con, dam);
+        if std::mem::take(&mut app_state.refresh_other_panels) {
+            app_state.stage.refresh();
+            for other_idx in 0..self.panels.len().get() {
+                if other_idx != idx {
+                    self.panels[other_idx].mut_state().refresh(screen, con);
                 }
             }
         }
-        warn!("unexpected lack of pending task");
-        Ok(())
+        res
     }
 
     fn has_pending_task(&mut self) -> bool {
@@ -731,6 +821,10 @@ impl App {
             stage: Stage::default(),
             root: con.initial_root.clone(),
             other_panel_path: None,
+            marks: Marks::new(
+                con.persist_marks.then(|| conf::app_dirs().data_dir().join("marks.txt"))
+            ),
+            refresh_other_panels: false,
         };
 
         self.screen.clear_bottom_right_char(w, &skin.focused)?;
@@ -777,8 +871,11 @@ impl App {
                     self.do_pending_tasks(w, &skin, &mut dam, &mut app_state, con)?,
                 );
             }
+            let Some(next) = self.next_event_or_sequence(&skin, &mut dam, &mut app_state, con) else {
+                continue; // panels were refreshed because of a change
+            };
             #[allow(unused_mut)]
-            match dam.next(&self.rx_seqs) {
+            match next {
                 Either::First(Some(event)) => {
                     info!("event: {:?}", &event);
                     let mut handled = false;
@@ -805,7 +902,7 @@ impl App {
                             self.preview_panel.is_some(),
                         );
                         for panel in &mut self.panels {
-                            panel.mut_state().refresh(self.screen, con);
+                            panel.mut_state().on_resize(self.screen, con);
                         }
                         handled = true;
                     }
@@ -12,12 +12,14 @@ use {
         skin::ExtColorMap,
         syntactic::SyntaxTheme,
         tree::TreeOptions,
+        tree_build::DEFAULT_SEARCH_TIME_BUDGET_MS,
         verb::VerbStore,
     },
     std::{
         convert::{TryFrom, TryInto},
         io,
         path::{Path, PathBuf},
+        time::Duration,
     },
 };
 
@@ -47,6 +49,9 @@ pub struct AppContext {
     /// the paths for which there's a special behavior to follow (comes from conf)
     pub special_paths: Vec<path::SpecialPath>,
 
+    /// the globs of files which are never listed (from conf and launch args)
+    pub ignore_globs: Vec<path::IgnoreGlob>,
+
     /// the map between search prefixes and the search mode to apply
     pub search_modes: SearchModeMap,
 
@@ -59,6 +64,9 @@ pub struct AppContext {
     /// the syntect theme to use for text files previewing
     pub syntax_theme: Option<SyntaxTheme>,
 
+    /// whether text previews are colored according to their syntax
+    pub syntax_highlighting: bool,
+
     /// precomputed status to display in standard cases
     /// (ie when no verb is involved)
     pub standard_status: StandardStatus,
@@ -84,6 +92,14 @@ pub struct AppContext {
     /// and there's nothing to cancel
     pub quit_on_last_cancel: bool,
 
+    /// whether focusing a link to a directory makes its canonical
+    /// target the new root, instead of the link path
+    pub focus_link_targets: bool,
+
+    /// whether marks are saved in a file so that they
+    /// survive restarts
+    pub persist_marks: bool,
+
     /// number of threads used by file_sum (count, size, date)
     /// computation
     pub file_sum_threads_count: usize,
@@ -93,11 +109,22 @@ pub struct AppContext {
 
     /// max file size when searching file content
     pub content_search_max_file_size: usize,
+
+    /// how long a not total search may go on before it stops
+    /// with the matches it found
+    pub search_time_budget: Duration,
+
+    /// whether `:touch` creates the missing directories
+    /// of the new file
+    pub touch_creates_dirs: bool,
+
+    /// whether `:touch` opens the new file in the editor
+    pub edit_new_files: bool,
 }
 
 impl AppContext {
     pub fn from(
-        launch_args: Args,
+        mut launch_args: Args,
         verb_store: VerbStore,
         config: &Conf,
     ) -> Result<Self, ProgramError> {
@@ -108,21 +135,29 @@ impl AppContext {
         } else {
             are_true_colors_available()
         };
-        let icons = config.icon_theme.as_ref()
+        let icons = launch_args.icon_theme.as_ref()
+            .or(config.icon_theme.as_ref())
             .and_then(|itn| icon_plugin(itn));
         let mut special_paths = config.special_paths
             .iter()
             .map(|(k, v)| path::SpecialPath::new(k.clone(), *v))
             .collect();
         path::add_defaults(&mut special_paths);
+        let ignore_globs = config.ignore_globs
+            .iter()
+            .chain(launch_args.ignore.iter())
+            .map(|s| path::IgnoreGlob::new(s))
+            .collect::<Result<Vec<_>, _>>()?;
         let search_modes = config
             .search_modes
             .as_ref()
             .map(|map| map.try_into())
             .transpose()?
             .unwrap_or_default();
-        let ext_colors = ExtColorMap::try_from(&config.ext_colors)
-            .map_err(ConfError::from)?;
+        let ext_colors = ExtColorMap::new(
+            &config.ext_colors,
+            config.use_ls_colors.unwrap_or(true),
+        ).map_err(ConfError::from)?;
         let file_sum_threads_count = config.file_sum_threads_count
             .unwrap_or(file_sum::DEFAULT_THREAD_COUNT);
         if !(1..=50).contains(&file_sum_threads_count) {
@@ -147,6 +182,15 @@ impl AppContext {
         let mut initial_tree_options = TreeOptions::default();
         initial_tree_options.apply_config(config)?;
         initial_tree_options.apply_launch_args(&launch_args);
+        if let Some(raw) = &launch_args.branch_chars {
+            initial_tree_options.branch_chars = raw.parse()?;
+        }
+        if let Some(raw) = &launch_args.max_children {
+            initial_tree_options.max_children = raw.parse()?;
+        }
+        if launch_args.color == TriBool::Auto && no_color_env() {
+            launch_args.color = TriBool::No;
+        }
         if launch_args.color == TriBool::No {
             initial_tree_options.show_selection_mark = true;
         }
@@ -155,6 +199,10 @@ impl AppContext {
             .map(|u64value| usize::try_from(u64value).unwrap_or(usize::MAX))
             .unwrap_or(content_search::DEFAULT_MAX_FILE_SIZE);
 
+        // when picking a path, escaping out of broot means picking nothing
+        let quit_on_last_cancel = config.quit_on_last_cancel.unwrap_or(false)
+            || launch_args.select_only;
+
         Ok(Self {
             initial_root,
             initial_file,
@@ -163,20 +211,29 @@ impl AppContext {
             launch_args,
             verb_store,
             special_paths,
+            ignore_globs,
             search_modes,
             show_selection_mark: config.show_selection_mark.unwrap_or(false),
             ext_colors,
             syntax_theme: config.syntax_theme,
+            syntax_highlighting: config.syntax_highlighting.unwrap_or(true),
             standard_status,
             true_colors,
             icons,
             modal: config.modal.unwrap_or(false),
             capture_mouse,
             max_panels_count,
-            quit_on_last_cancel: config.quit_on_last_cancel.unwrap_or(false),
+            quit_on_last_cancel,
+            focus_link_targets: config.focus_link_targets.unwrap_or(false),
+            persist_marks: config.persist_marks.unwrap_or(false),
             file_sum_threads_count,
             max_staged_count,
             content_search_max_file_size,
+            search_time_budget: Duration::from_millis(
+                config.search_time_budget_ms.unwrap_or(DEFAULT_SEARCH_TIME_BUDGET_MS),
+            ),
+            touch_creates_dirs: config.touch_creates_dirs.unwrap_or(false),
+            edit_new_files: config.edit_new_files.unwrap_or(false),
         })
     }
 }
@@ -203,6 +260,12 @@ fn are_true_colors_available() -> bool {
     }
 }
 
+/// tell whether the user asked for no color with a non empty
+/// `NO_COLOR` env variable (see https://no-color.org)
+fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").map_or(false, |v| !v.is_empty())
+}
+
 /// Determine the initial root folder to show, and the optional
 /// initial file to open in preview
 fn initial_root_file(cli_args: &Args) -> Result<(PathBuf, Option<PathBuf>), ProgramError> {
@@ -1,4 +1,5 @@
 use {
+    super::Marks,
     crate::{
         stage::Stage,
     },
@@ -18,6 +19,13 @@ pub struct AppState {
     /// the selected path in another panel than the currently
     /// active one, if any
     pub other_panel_path: Option<PathBuf>,
+
+    /// the paths marked with `:mark`
+    pub marks: Marks,
+
+    /// set by a panel whose background task changed files (eg
+    /// a copy), so that the other panels are refreshed
+    pub refresh_other_panels: bool,
 }
 
 impl AppState {
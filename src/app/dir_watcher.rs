@@ -0,0 +1,107 @@
+use {
+    crossbeam::channel::{unbounded, Receiver},
+    fnv::FnvHashSet,
+    notify::{
+        event::{EventKind, ModifyKind},
+        RecommendedWatcher,
+        RecursiveMode,
+        Watcher,
+    },
+    std::{
+        path::{Path, PathBuf},
+        time::Duration,
+    },
+};
+
+/// How long the app waits, after a change, for other changes or
+/// an event, before refreshing the trees
+pub const WATCH_DELAY: Duration = Duration::from_millis(100);
+
+/// Detects the addition, removal or renaming of entries in
+/// directories, with the notifications of the system.
+///
+/// Only the directories given at each update are watched, so that
+/// the number of watches is bounded by the number of displayed lines.
+pub struct DirWatcher {
+    watcher: RecommendedWatcher,
+    watched: FnvHashSet<PathBuf>,
+    changes: Receiver<()>,
+}
+
+impl DirWatcher {
+    pub fn new() -> notify::Result<Self> {
+        let (tx, changes) = unbounded();
+        let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let changed = match res {
+                Ok(event) => matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_)),
+                ),
+                Err(e) => {
+                    warn!("watch error: {e}");
+                    false
+                }
+            };
+            if changed {
+                let _ = tx.send(());
+            }
+        })?;
+        Ok(Self {
+            watcher,
+            watched: FnvHashSet::default(),
+            changes,
+        })
+    }
+
+    /// watch the given directories, and only them
+    pub fn update<'p, I>(&mut self, dirs: I)
+    where
+        I: IntoIterator<Item = &'p Path>,
+    {
+        let dirs: FnvHashSet<PathBuf> = dirs
+            .into_iter()
+            .map(Path::to_path_buf)
+            .collect();
+        for dir in self.watched.difference(&dirs) {
+            // fails when the directory is gone, which is fine
+            let _ = self.watcher.unwatch(dir);
+        }
+        for dir in dirs.difference(&self.watched) {
+            if let Err(e) = self.watcher.watch(dir, RecursiveMode::NonRecursive) {
+                debug!("can't watch {dir:?}: {e}");
+            }
+        }
+        self.watched = dirs;
+    }
+
+    /// the receiver of a message for each change
+    pub fn changes(&self) -> &Receiver<()> {
+        &self.changes
+    }
+
+    /// drop the notifications of changes already received
+    pub fn clear(&self) {
+        while self.changes.try_recv().is_ok() {}
+    }
+}
+
+#[test]
+fn test_dir_watcher() {
+    use std::fs;
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    let sub = root.join("sub");
+    fs::create_dir(&sub).unwrap();
+    let mut watcher = DirWatcher::new().unwrap();
+    watcher.update([root.as_path()]);
+    fs::write(root.join("new_file"), "").unwrap();
+    assert!(watcher.changes().recv_timeout(Duration::from_secs(2)).is_ok());
+    watcher.clear();
+    // writing in a file isn't a change of the directory
+    fs::write(root.join("new_file"), "content").unwrap();
+    fs::write(sub.join("file"), "").unwrap();
+    assert!(watcher.changes().recv_timeout(Duration::from_millis(200)).is_err());
+    watcher.update([root.as_path(), sub.as_path()]);
+    fs::remove_file(sub.join("file")).unwrap();
+    assert!(watcher.changes().recv_timeout(Duration::from_secs(2)).is_ok());
+}
@@ -0,0 +1,91 @@
+use {
+    std::{
+        collections::BTreeMap,
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Paths marked with a char, like vim marks, so that they
+/// can be selected again later.
+///
+/// When there's a file, marks are read from it at launch and
+/// written to it on every change, so that they survive restarts.
+#[derive(Debug, Default)]
+pub struct Marks {
+    paths: BTreeMap<char, PathBuf>,
+    file: Option<PathBuf>,
+}
+
+impl Marks {
+    /// build the marks, reading them from the file if there's one.
+    /// A missing or unreadable file just means there's no mark yet.
+    pub fn new(file: Option<PathBuf>) -> Self {
+        let paths = file.as_ref()
+            .and_then(|file| fs::read_to_string(file).ok())
+            .map(|content| parse(&content))
+            .unwrap_or_default();
+        Self { paths, file }
+    }
+
+    pub fn get(&self, c: char) -> Option<&Path> {
+        self.paths.get(&c).map(PathBuf::as_path)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// iterate over the marks, sorted by char
+    pub fn iter(&self) -> impl Iterator<Item = (char, &Path)> {
+        self.paths.iter().map(|(c, path)| (*c, path.as_path()))
+    }
+
+    /// set the mark, replacing the previous path for this char if any
+    pub fn set(&mut self, c: char, path: PathBuf) {
+        self.paths.insert(c, path);
+        if let Err(e) = self.write() {
+            warn!("failed to write marks: {}", e);
+        }
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut content = String::new();
+        for (c, path) in &self.paths {
+            content.push(*c);
+            content.push(' ');
+            content.push_str(&path.to_string_lossy());
+            content.push('\n');
+        }
+        fs::write(file, content)
+    }
+}
+
+/// parse the content of a marks file, made of lines like `a /some/path`
+fn parse(content: &str) -> BTreeMap<char, PathBuf> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut chars = line.chars();
+            let c = chars.next()?;
+            let path = chars.as_str().strip_prefix(' ')?;
+            (!c.is_whitespace() && !path.is_empty()).then(|| (c, PathBuf::from(path)))
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_marks() {
+    let paths = parse("a /home/dys/dev\nb /tmp/with space\n\n/bad\né /é\n");
+    assert_eq!(paths.len(), 3);
+    assert_eq!(paths[&'a'], PathBuf::from("/home/dys/dev"));
+    assert_eq!(paths[&'b'], PathBuf::from("/tmp/with space"));
+    assert_eq!(paths[&'é'], PathBuf::from("/é"));
+}
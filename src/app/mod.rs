@@ -3,7 +3,9 @@ mod app_context;
 mod app_state;
 mod cmd_context;
 mod cmd_result;
+mod dir_watcher;
 mod display_context;
+mod marks;
 mod mode;
 mod panel;
 mod panel_id;
@@ -20,7 +22,9 @@ pub use {
     app_state::*,
     cmd_context::*,
     cmd_result::*,
+    dir_watcher::{DirWatcher, WATCH_DELAY},
     display_context::*,
+    marks::Marks,
     mode::*,
     panel::Panel,
     panel_id::PanelId,
@@ -3,6 +3,7 @@ use {
     crate::{
         command::*,
         display::{
+            selection_line,
             status_line,
             Areas,
             Screen,
@@ -199,6 +200,14 @@ impl Panel {
         disc: &DisplayContext,
     ) -> Result<(), ProgramError> {
         self.mut_state().display(w, disc)?;
+        selection_line::write(
+            w,
+            self.state().selected_path(),
+            self.state().tree_stats(),
+            &self.areas.selection,
+            disc.panel_skin,
+            disc.screen,
+        )?;
         if disc.active || !WIDE_STATUS {
             self.write_status(w, disc.panel_skin, disc.screen)?;
         }
@@ -1,11 +1,14 @@
 use {
     super::*,
     crate::{
+        archive,
+        clipboard,
         command::*,
         display::{Screen, W},
         errors::ProgramError,
         flag::Flag,
         help::HelpState,
+        launchable::Launchable,
         pattern::*,
         preview::{PreviewMode, PreviewState},
         print,
@@ -32,6 +35,11 @@ pub trait PanelState {
     /// called on start of on_command
     fn clear_pending(&mut self) {}
 
+    /// called on start of on_command when the command executes a verb,
+    /// so that a request for confirmation, or the notice of a change, is
+    /// only valid until the next one
+    fn age_requests(&mut self) {}
+
     fn on_click(
         &mut self,
         _x: u16,
@@ -56,6 +64,7 @@ pub trait PanelState {
         &mut self,
         _pat: InputPattern,
         _app_state: &AppState,
+        _screen: Screen,
         _con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
         Ok(CmdResult::Keep)
@@ -94,10 +103,10 @@ pub trait PanelState {
     /// behavior to execute
     fn on_internal_generic(
         &mut self,
-        _w: &mut W,
+        w: &mut W,
         internal_exec: &InternalExecution,
         input_invocation: Option<&VerbInvocation>,
-        _trigger_type: TriggerType,
+        trigger_type: TriggerType,
         app_state: &mut AppState,
         cc: &CmdContext,
     ) -> Result<CmdResult, ProgramError> {
@@ -108,22 +117,66 @@ pub trait PanelState {
             .unwrap_or(internal_exec.bang);
         Ok(match internal_exec.internal {
             Internal::back => CmdResult::PopState,
-            Internal::copy_line | Internal::copy_path => {
-                #[cfg(not(feature = "clipboard"))]
-                {
-                    CmdResult::error("Clipboard feature not enabled at compilation")
-                }
-                #[cfg(feature = "clipboard")]
-                {
-                    if let Some(path) = self.selected_path() {
-                        let path = path.to_string_lossy().to_string();
-                        match terminal_clipboard::set_string(path) {
-                            Ok(()) => CmdResult::Keep,
-                            Err(_) => CmdResult::error("Clipboard error while copying path"),
-                        }
+            Internal::scroll_down | Internal::scroll_up => {
+                // states without a selection independent from the view
+                // scroll with the line moves
+                let internal_exec = InternalExecution {
+                    internal: if internal_exec.internal == Internal::scroll_down {
+                        Internal::line_down
                     } else {
-                        CmdResult::error("Nothing to copy")
+                        Internal::line_up
+                    },
+                    bang: internal_exec.bang,
+                    arg: internal_exec.arg.clone(),
+                };
+                self.on_internal(
+                    w,
+                    &internal_exec,
+                    input_invocation,
+                    trigger_type,
+                    app_state,
+                    cc,
+                )?
+            }
+            Internal::copy_line
+            | Internal::copy_path
+            | Internal::copy_relative_path
+            | Internal::copy_file_name => {
+                let path_to_string = |path: &Path| match internal_exec.internal {
+                    Internal::copy_relative_path => {
+                        match pathdiff::diff_paths(path, &app_state.root) {
+                            Some(p) if p.components().next().is_some() => {
+                                p.to_string_lossy().to_string()
+                            }
+                            Some(_) => ".".to_string(),
+                            None => path.to_string_lossy().to_string(),
+                        }
                     }
+                    Internal::copy_file_name => path
+                        .file_name()
+                        .unwrap_or(path.as_os_str())
+                        .to_string_lossy()
+                        .to_string(),
+                    _ => path.to_string_lossy().to_string(),
+                };
+                let text = match self.sel_info(app_state) {
+                    SelInfo::None => None,
+                    SelInfo::One(sel) => Some(path_to_string(sel.path)),
+                    SelInfo::More(stage) => Some(
+                        stage.paths()
+                            .iter()
+                            .map(|path| path_to_string(path))
+                            .collect::<Vec<String>>()
+                            .join("\n")
+                    ),
+                };
+                if let Some(text) = text {
+                    match clipboard::copy(&text, w) {
+                        Ok(()) => CmdResult::Keep,
+                        Err(e) => CmdResult::error(e.to_string()),
+                    }
+                } else {
+                    CmdResult::error("Nothing to copy")
                 }
             }
             Internal::close_panel_ok => CmdResult::ClosePanel {
@@ -177,6 +230,20 @@ pub trait PanelState {
             }
             Internal::mode_input => self.on_mode_verb(Mode::Input, con),
             Internal::mode_command => self.on_mode_verb(Mode::Command, con),
+            Internal::edit => {
+                if let Some(selection) = self.selection() {
+                    let launchable = Launchable::editor(selection.path, selection.line, con);
+                    if let Err(e) = launchable.execute(Some(w)) {
+                        warn!("editor failed : {:?}", e);
+                        CmdResult::error(e.to_string())
+                    } else {
+                        // the editor may have written other files, eg backups
+                        CmdResult::RefreshState { clear_cache: true }
+                    }
+                } else {
+                    CmdResult::error("no selection to edit")
+                }
+            }
             Internal::open_leave => {
                 if let Some(selection) = self.selection() {
                     selection.to_opener(con)?
@@ -221,6 +288,22 @@ pub trait PanelState {
                 bang,
                 con,
             ),
+            Internal::sort_by_date_in_tree => self.with_new_options(
+                screen,
+                &|o| {
+                    if o.sort == Sort::DateTree {
+                        o.sort = Sort::None;
+                        o.show_dates = false;
+                        "*not sorting anymore*"
+                    } else {
+                        o.sort = Sort::DateTree;
+                        o.show_dates = true;
+                        "*now sorting the children of every directory by date*"
+                    }
+                },
+                bang,
+                con,
+            ),
             Internal::sort_by_size => self.with_new_options(
                 screen,
                 &|o| {
@@ -238,6 +321,22 @@ pub trait PanelState {
                 bang,
                 con,
             ),
+            Internal::sort_by_size_in_tree => self.with_new_options(
+                screen,
+                &|o| {
+                    if o.sort == Sort::SizeTree {
+                        o.sort = Sort::None;
+                        o.show_sizes = false;
+                        "*not sorting anymore*"
+                    } else {
+                        o.sort = Sort::SizeTree;
+                        o.show_sizes = true;
+                        "*now sorting the children of every directory by size*"
+                    }
+                },
+                bang,
+                con,
+            ),
             Internal::sort_by_type => self.with_new_options(
                 screen,
                 &|o| {
@@ -345,6 +444,68 @@ pub trait PanelState {
 					con,
 				)
             }
+            Internal::toggle_hard_links => {
+                self.with_new_options(
+					screen,
+					&|o| {
+						o.show_hard_links ^= true;
+                        if o.show_hard_links {
+                            "*displaying the number of hard links*"
+                        } else {
+                            "*hiding the number of hard links*"
+                        }
+					},
+					bang,
+					con,
+				)
+            }
+            Internal::toggle_one_file_system => {
+                self.with_new_options(
+					screen,
+					&|o| {
+						o.one_file_system ^= true;
+                        if o.one_file_system {
+                            "*not entering other filesystems*"
+                        } else {
+                            "*entering all filesystems*"
+                        }
+					},
+					bang,
+					con,
+				)
+            }
+            Internal::set_max_depth => {
+                let max_depth = get_arg(input_invocation, internal_exec, 0);
+                self.with_new_options(
+					screen,
+					&|o| {
+                        if max_depth > 0 {
+                            o.max_depth = Some(max_depth);
+                            "*not entering directories past the max depth*"
+                        } else {
+                            o.max_depth = None;
+                            "*entering directories at any depth*"
+                        }
+                    },
+					bang,
+					con,
+				)
+            }
+            Internal::toggle_dirs_first => {
+                self.with_new_options(
+					screen,
+					&|o| {
+                        o.dirs_first ^= true;
+                        if o.dirs_first {
+                            "*listing directories before files*"
+                        } else {
+                            "*listing directories and files together*"
+                        }
+                    },
+					bang,
+					con,
+				)
+            }
             Internal::toggle_files => {
                 self.with_new_options(
 					screen,
@@ -502,6 +663,12 @@ pub trait PanelState {
             Internal::toggle_second_tree => {
                 CmdResult::HandleInApp(Internal::toggle_second_tree)
             }
+            Internal::toggle_mouse_capture => {
+                CmdResult::HandleInApp(Internal::toggle_mouse_capture)
+            }
+            Internal::toggle_watch => {
+                CmdResult::HandleInApp(Internal::toggle_watch)
+            }
             Internal::clear_stage => {
                 app_state.stage.clear();
                 if let Some(panel_id) = cc.app.stage_panel {
@@ -555,7 +722,16 @@ pub trait PanelState {
             Internal::print_path => print::print_paths(self.sel_info(app_state), con)?,
             Internal::print_relative_path => print::print_relative_paths(self.sel_info(app_state), con)?,
             Internal::refresh => CmdResult::RefreshState { clear_cache: true },
+            Internal::soft_refresh => CmdResult::RefreshState { clear_cache: false },
             Internal::quit => CmdResult::Quit,
+            Internal::rm | Internal::rm_permanently => CmdResult::error("files can only be deleted from a tree or the staging area"),
+            Internal::cp
+            | Internal::mv
+            | Internal::copy_to_panel
+            | Internal::move_to_panel => CmdResult::error("files can only be copied or moved from a tree or the staging area"),
+            Internal::rename => CmdResult::error("only the selection of a tree can be renamed"),
+            Internal::mkdir | Internal::touch => CmdResult::error("files can only be created from a tree"),
+            Internal::chmod => CmdResult::error("modes can only be changed from a tree or the staging area"),
             _ => CmdResult::Keep,
         })
     }
@@ -619,6 +795,25 @@ pub trait PanelState {
         }
     }
 
+    /// return an error when the verb would act on the synthetic path of a
+    /// file in an archive, either the selection or the one of the other panel
+    fn refuse_virtual_paths(
+        &self,
+        uses_selection: bool,
+        uses_other_panel: bool,
+        app_state: &AppState,
+    ) -> Option<CmdResult> {
+        if uses_selection && self.selected_path().map_or(false, archive::is_virtual_path) {
+            return Some(CmdResult::error("This verb can't be applied to a file in an archive"));
+        }
+        if uses_other_panel
+            && app_state.other_panel_path.as_deref().map_or(false, archive::is_virtual_path)
+        {
+            return Some(CmdResult::error("This verb can't be applied to a file in an archive"));
+        }
+        None
+    }
+
     fn execute_verb(
         &mut self,
         w: &mut W, // needed because we may want to switch from alternate in some externals
@@ -634,6 +829,16 @@ pub trait PanelState {
         if verb.needs_another_panel && app_state.other_panel_path.is_none() {
             return Ok(CmdResult::error("This verb needs another panel"));
         }
+        let (uses_selection, uses_other_panel) = match &verb.execution {
+            VerbExecution::Internal(internal_exec) => {
+                let internal = internal_exec.internal;
+                (internal.needs_real_path(), internal.needs_other_panel_dir())
+            }
+            _ => (verb.needs_selection, verb.needs_another_panel),
+        };
+        if let Some(error) = self.refuse_virtual_paths(uses_selection, uses_other_panel, app_state) {
+            return Ok(error);
+        }
         let res = match &verb.execution {
             VerbExecution::Internal(internal_exec) => {
                 self.on_internal(
@@ -740,6 +945,12 @@ pub trait PanelState {
         cc: &CmdContext,
     ) -> Result<CmdResult, ProgramError> {
         self.clear_pending();
+        if matches!(
+            cc.cmd,
+            Command::VerbTrigger { .. } | Command::VerbInvocate(_) | Command::Internal { .. },
+        ) {
+            self.age_requests();
+        }
         let con = &cc.app.con;
         let screen = cc.app.screen;
         match &cc.cmd {
@@ -747,7 +958,7 @@ pub trait PanelState {
             Command::DoubleClick(x, y) => self.on_double_click(*x, *y, screen, con),
             Command::PatternEdit { raw, expr } => {
                 match InputPattern::new(raw.clone(), expr, con) {
-                    Ok(pattern) => self.on_pattern(pattern, app_state, con),
+                    Ok(pattern) => self.on_pattern(pattern, app_state, screen, con),
                     Err(e) => Ok(CmdResult::DisplayError(format!("{e}"))),
                 }
             }
@@ -765,15 +976,27 @@ pub trait PanelState {
             Command::Internal {
                 internal,
                 input_invocation,
-            } => self.on_internal(
-                w,
-                &InternalExecution::from_internal(*internal),
-                input_invocation.as_ref(),
-                TriggerType::Other,
-                app_state,
-                cc,
-            ),
+            } => {
+                let refusal = self.refuse_virtual_paths(
+                    internal.needs_real_path(),
+                    internal.needs_other_panel_dir(),
+                    app_state,
+                );
+                if let Some(error) = refusal {
+                    return Ok(error);
+                }
+                self.on_internal(
+                    w,
+                    &InternalExecution::from_internal(*internal),
+                    input_invocation.as_ref(),
+                    TriggerType::Other,
+                    app_state,
+                    cc,
+                )
+            }
             Command::VerbInvocate(invocation) => {
+                let line_selection = con.verb_store.line_selection(invocation);
+                let invocation = line_selection.as_ref().unwrap_or(invocation);
                 let sel_info = self.sel_info(app_state);
                 match con.verb_store.search_sel_info(
                     &invocation.name,
@@ -841,8 +1064,19 @@ pub trait PanelState {
         None
     }
 
+    /// the directories whose changes should trigger a refresh
+    /// when watching is on
+    fn watched_dirs(&self) -> Vec<&Path> {
+        Vec::new()
+    }
+
     fn selected_path(&self) -> Option<&Path>;
 
+    /// the stats of the displayed tree, if there's one
+    fn tree_stats(&self) -> Option<TreeStats> {
+        None
+    }
+
     fn selection(&self) -> Option<Selection<'_>>;
 
     fn sel_info<'c>(&'c self, _app_state: &'c AppState) -> SelInfo<'c> {
@@ -859,6 +1093,13 @@ pub trait PanelState {
 
     fn refresh(&mut self, screen: Screen, con: &AppContext) -> Command;
 
+    /// adapt the state to a new size of the screen.
+    ///
+    /// The default implementation does a refresh.
+    fn on_resize(&mut self, screen: Screen, con: &AppContext) {
+        self.refresh(screen, con);
+    }
+
     fn tree_options(&self) -> TreeOptions;
 
     /// Build a cmdResult in response to a command being a change of
@@ -930,8 +1171,23 @@ pub trait PanelState {
         width: usize,
     ) -> Status {
         info!("get_status cc.cmd={:?}", &cc.cmd);
+        let no_verb_status = || {
+            // in trees, the staged count is shown even when the stage panel is closed
+            let staged = (self.get_type() == PanelStateType::Tree && !app_state.stage.is_empty())
+                .then(|| format!("*{}* staged.", app_state.stage.len()));
+            let prefix_width = staged.as_ref().map_or(0, |md| md.len());
+            let mut status = self.no_verb_status(
+                has_previous_state,
+                cc.app.con,
+                width.saturating_sub(prefix_width),
+            );
+            if let Some(md) = staged.filter(|_| !status.error) {
+                status.message = format!("{md} {}", status.message);
+            }
+            status
+        };
         match &cc.cmd {
-            Command::PatternEdit { .. } => self.no_verb_status(has_previous_state, cc.app.con, width),
+            Command::PatternEdit { .. } => no_verb_status(),
             Command::VerbEdit(invocation) | Command::VerbTrigger { input_invocation: Some(invocation), .. } => {
                 if invocation.name.is_empty() {
                     Status::new(
@@ -939,6 +1195,8 @@ pub trait PanelState {
                         false,
                     )
                 } else {
+                    let line_selection = cc.app.con.verb_store.line_selection(invocation);
+                    let invocation = line_selection.as_ref().unwrap_or(invocation);
                     let sel_info = self.sel_info(app_state);
                     match cc.app.con.verb_store.search_sel_info(
                         &invocation.name,
@@ -964,7 +1222,7 @@ pub trait PanelState {
                     }
                 }
             }
-            _ => self.no_verb_status(has_previous_state, cc.app.con, width),
+            _ => no_verb_status(),
         }
     }
 
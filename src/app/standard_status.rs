@@ -18,6 +18,7 @@ pub struct StandardStatus {
     tree_file_open_leave: Option<String>,
     tree_unfiltered: String,
     tree_filtered: String,
+    tree_partial_search: Option<String>, // ctrl-s to search all files
     preview_unfiltered: String, // ctrl-left to close, or a pattern to filter
     preview_filtered: Option<String>,
     preview_restorable_filter: Option<String>,
@@ -47,6 +48,9 @@ impl StandardStatus {
         //let tree_file_enter = None; // TODO (for when enter is customized)
         let tree_unfiltered = "a few letters to search".to_string();
         let tree_filtered = "*esc* to clear the filter".to_string();
+        let tree_partial_search = verb_store
+            .key_desc_of_internal(Internal::total_search)
+            .map(|k| format!("*{k}* to search all files"));
         let preview_unfiltered = "a pattern to filter".to_string();
         let preview_filtered = verb_store
             .key_desc_of_internal(Internal::panel_right)
@@ -73,6 +77,7 @@ impl StandardStatus {
             //tree_file_enter,
             tree_unfiltered,
             tree_filtered,
+            tree_partial_search,
             preview_unfiltered,
             preview_filtered,
             preview_restorable_filter,
@@ -145,6 +150,7 @@ pub struct StandardStatusBuilder<'s> {
     selection: Selection<'s>,
     pub has_previous_state: bool,
     pub is_filtered: bool,
+    pub is_partial_search: bool, // the search stopped before looking at all files
     pub has_removed_pattern: bool,
     pub on_tree_root: bool, // should this be part of the Selection struct ?
     pub width: usize, // available width
@@ -162,6 +168,7 @@ impl<'s> StandardStatusBuilder<'s> {
             selection,
             has_previous_state: true,
             is_filtered: false,
+            is_partial_search: false,
             has_removed_pattern: false,
             on_tree_root: false,
             width,
@@ -175,6 +182,9 @@ impl<'s> StandardStatusBuilder<'s> {
         }
         match self.state_type {
             PanelStateType::Tree => {
+                if self.is_partial_search {
+                    parts.addo(&ss.tree_partial_search);
+                }
                 if self.on_tree_root {
                     if self.selection.path.file_name().is_some() { // it's not '/'
                         parts.add(&ss.tree_top_focus);
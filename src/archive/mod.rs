@@ -0,0 +1,506 @@
+//! Reading of zip, tar and tar.gz archives, whose content can
+//! be browsed as if it were made of directories.
+//!
+//! The entries of an archive get synthetic paths: the path of the
+//! archive followed by their path in the archive, for example
+//! `/home/dys/dl/fonts.zip/fonts/README.md`. Those "virtual" paths
+//! don't exist on disk, so verbs needing real files refuse them.
+
+mod tar;
+mod zip;
+
+use {
+    crate::{
+        errors::ArchiveError,
+        file_sum::FileSum,
+    },
+    ahash::AHashMap,
+    once_cell::sync::Lazy,
+    std::{
+        fs,
+        io::Write,
+        path::{Path, PathBuf},
+        sync::{Arc, Mutex},
+        time::SystemTime,
+    },
+    tempfile::TempPath,
+};
+
+/// number of archive listings kept in memory, so that the tree
+/// isn't read again at every keystroke of a search
+const CACHE_SIZE: usize = 5;
+
+/// an archive listing, with the modification date of the archive file
+type Cached = (SystemTime, Arc<Archive>);
+
+static CACHE: Lazy<Mutex<Vec<Cached>>> = Lazy::new(Default::default);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// where the content of an entry is, in the archive
+#[derive(Debug, Clone, Copy)]
+enum Location {
+    /// the entry wasn't in the archive but is the parent of some
+    None,
+    Zip {
+        header_offset: u64,
+        compressed_size: u64,
+        method: u16,
+    },
+    /// offset in the uncompressed tar stream
+    Tar { offset: u64 },
+}
+
+/// A file or directory in an archive
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub path: String, // path in the archive, without leading or trailing slash
+    pub is_dir: bool,
+    pub size: u64,     // uncompressed size, 0 for directories
+    pub modified: u32, // seconds since epoch, 0 if unknown
+    pub parent: Option<usize>,
+    pub sum: FileSum, // for directories, the sum of their content
+    location: Location,
+}
+
+impl ArchiveEntry {
+    fn new(path: String, is_dir: bool, size: u64, modified: u32, location: Location) -> Self {
+        Self {
+            path,
+            is_dir,
+            size,
+            modified,
+            parent: None,
+            sum: FileSum::zero(),
+            location,
+        }
+    }
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// The listing of an archive
+#[derive(Debug)]
+pub struct Archive {
+    pub path: PathBuf,
+    pub kind: ArchiveKind,
+    pub entries: Vec<ArchiveEntry>,
+    index: AHashMap<String, usize>,
+    children: Vec<Vec<usize>>,
+    root_children: Vec<usize>,
+    pub sum: FileSum,
+}
+
+impl Archive {
+    /// Read the listing of the archive, or take it from
+    /// the cache if the file didn't change
+    pub fn open(path: &Path) -> Result<Arc<Self>, ArchiveError> {
+        let kind = kind_of(path).ok_or_else(|| ArchiveError::Unsupported {
+            details: "unknown extension".to_string(),
+        })?;
+        let modified = fs::metadata(path)?.modified()?;
+        let mut cache = CACHE.lock().unwrap();
+        if let Some((_, archive)) = cache
+            .iter()
+            .find(|(date, archive)| *date == modified && archive.path == path)
+        {
+            return Ok(Arc::clone(archive));
+        }
+        let entries = match kind {
+            ArchiveKind::Zip => zip::read_entries(path)?,
+            ArchiveKind::Tar | ArchiveKind::TarGz => tar::read_entries(path, kind)?,
+        };
+        let archive = Arc::new(Self::new(path.to_path_buf(), kind, entries));
+        if cache.len() >= CACHE_SIZE {
+            cache.remove(0);
+        }
+        cache.push((modified, Arc::clone(&archive)));
+        Ok(archive)
+    }
+
+    /// Build the archive from the raw entries, normalizing their paths,
+    /// adding the missing directories and computing the sums
+    fn new(path: PathBuf, kind: ArchiveKind, raw_entries: Vec<ArchiveEntry>) -> Self {
+        let mut entries: Vec<ArchiveEntry> = Vec::new();
+        let mut index: AHashMap<String, usize> = AHashMap::default();
+        for mut entry in raw_entries {
+            let Some(clean_path) = clean_path(&entry.path) else {
+                debug!("ignoring archive entry {:?}", &entry.path);
+                continue;
+            };
+            entry.path = clean_path;
+            // the parent directories aren't always listed
+            let mut parent = None;
+            for (i, _) in entry.path.match_indices('/') {
+                let dir_path = &entry.path[..i];
+                let idx = match index.get(dir_path) {
+                    Some(&idx) => idx,
+                    None => {
+                        let mut dir = ArchiveEntry::new(
+                            dir_path.to_string(),
+                            true,
+                            0,
+                            0,
+                            Location::None,
+                        );
+                        dir.parent = parent;
+                        entries.push(dir);
+                        index.insert(dir_path.to_string(), entries.len() - 1);
+                        entries.len() - 1
+                    }
+                };
+                parent = Some(idx);
+            }
+            entry.parent = parent;
+            match index.get(&entry.path) {
+                Some(&idx) => {
+                    // a directory which was added as parent, or an entry
+                    // appended to the archive to replace a previous one
+                    entries[idx] = entry;
+                }
+                None => {
+                    index.insert(entry.path.clone(), entries.len());
+                    entries.push(entry);
+                }
+            }
+        }
+        let mut children = vec![Vec::new(); entries.len()];
+        let mut root_children = Vec::new();
+        let mut sum = FileSum::zero();
+        for idx in 0..entries.len() {
+            match entries[idx].parent {
+                Some(parent) => children[parent].push(idx),
+                None => root_children.push(idx),
+            }
+            if entries[idx].is_dir {
+                continue;
+            }
+            let file_sum = FileSum::new(entries[idx].size, false, 1, entries[idx].modified);
+            entries[idx].sum = file_sum;
+            sum += file_sum;
+            let mut parent = entries[idx].parent;
+            while let Some(parent_idx) = parent {
+                entries[parent_idx].sum += file_sum;
+                parent = entries[parent_idx].parent;
+            }
+        }
+        Self {
+            path,
+            kind,
+            entries,
+            index,
+            children,
+            root_children,
+            sum,
+        }
+    }
+
+    /// the indexes of the children of the given entry, or of
+    /// the top level entries when `parent` is None
+    pub fn children(&self, parent: Option<usize>) -> &[usize] {
+        match parent {
+            Some(idx) => &self.children[idx],
+            None => &self.root_children,
+        }
+    }
+
+    pub fn entry_idx(&self, path_in_archive: &str) -> Option<usize> {
+        self.index.get(path_in_archive).copied()
+    }
+
+    /// the synthetic path of the entry
+    pub fn entry_path(&self, idx: usize) -> PathBuf {
+        self.path.join(&self.entries[idx].path)
+    }
+
+    /// Uncompress the start of the content of a file entry,
+    /// up to `max_len` bytes
+    pub fn read_entry(&self, idx: usize, max_len: u64) -> Result<Vec<u8>, ArchiveError> {
+        let entry = &self.entries[idx];
+        let max_len = max_len.min(entry.size);
+        match entry.location {
+            Location::None => Ok(Vec::new()),
+            Location::Zip { header_offset, compressed_size, method } => {
+                zip::read_data(&self.path, header_offset, compressed_size, method, max_len)
+            }
+            Location::Tar { offset } => {
+                tar::read_data(&self.path, self.kind, offset, max_len)
+            }
+        }
+    }
+}
+
+/// Remove the leading `./` or `/` and the trailing `/`, return None
+/// when the path is empty or contains `..`
+fn clean_path(path: &str) -> Option<String> {
+    let parts: Vec<&str> = path
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect();
+    if parts.is_empty() || parts.contains(&"..") {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+/// Tell whether the path looks like the one of an archive
+/// which can be browsed (the file isn't checked)
+pub fn kind_of(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else {
+        None
+    }
+}
+
+/// Tell whether the path is the one of an archive file which can be browsed
+pub fn is_archive(path: &Path) -> bool {
+    kind_of(path).is_some() && path.is_file()
+}
+
+/// If the path is the synthetic path of an entry in an archive, return
+/// the path of the archive and the path of the entry in the archive
+pub fn split_virtual_path(path: &Path) -> Option<(&Path, String)> {
+    if path.exists() {
+        return None;
+    }
+    let archive_path = path.ancestors().skip(1).find(|p| is_archive(p))?;
+    let path_in_archive = path
+        .strip_prefix(archive_path)
+        .ok()?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some((archive_path, path_in_archive))
+}
+
+/// Tell whether the path is the synthetic path of an entry in an
+/// archive, which doesn't exist on disk
+pub fn is_virtual_path(path: &Path) -> bool {
+    split_virtual_path(path).is_some()
+}
+
+/// If the path is the one of a file in an archive, extract the start of
+/// its content (at most `max_len` bytes) into a temporary file, whose
+/// name ends with the name of the entry so that its type can be guessed.
+///
+/// The file is removed when the returned TempPath is dropped.
+pub fn extract_to_temp_file(
+    path: &Path,
+    max_len: u64,
+) -> Result<Option<TempPath>, ArchiveError> {
+    let Some((archive_path, path_in_archive)) = split_virtual_path(path) else {
+        return Ok(None);
+    };
+    let archive = Archive::open(archive_path)?;
+    let idx = archive
+        .entry_idx(&path_in_archive)
+        .ok_or(ArchiveError::EntryNotFound { path: path_in_archive })?;
+    let entry = &archive.entries[idx];
+    if entry.is_dir {
+        return Ok(None);
+    }
+    let data = archive.read_entry(idx, max_len)?;
+    let mut file = tempfile::Builder::new()
+        .prefix("broot-")
+        .suffix(&format!("-{}", entry.name()))
+        .tempfile()?;
+    file.write_all(&data)?;
+    Ok(Some(file.into_temp_path()))
+}
+
+/// Return the closest path which can be the root of a tree,
+/// archives and their directories being considered as directories
+pub fn closest_dir(path: &Path) -> PathBuf {
+    if is_archive(path) {
+        return path.to_path_buf();
+    }
+    if let Some((archive_path, path_in_archive)) = split_virtual_path(path) {
+        let is_dir = Archive::open(archive_path)
+            .ok()
+            .and_then(|archive| {
+                archive.entry_idx(&path_in_archive).map(|idx| archive.entries[idx].is_dir)
+            });
+        return match (is_dir, path.parent()) {
+            (Some(true), _) => path.to_path_buf(),
+            (_, Some(parent)) => closest_dir(parent),
+            _ => archive_path.to_path_buf(),
+        };
+    }
+    crate::path::closest_dir(path)
+}
+
+/// Convert a MS-DOS date and time (as found in zip files) into
+/// seconds since epoch, returning 0 when invalid
+fn dos_date_time_to_seconds(date: u16, time: u16) -> u32 {
+    chrono::NaiveDate::from_ymd_opt(
+        1980 + i32::from(date >> 9),
+        u32::from((date >> 5) & 0xF),
+        u32::from(date & 0x1F),
+    )
+    .and_then(|day| day.and_hms_opt(
+        u32::from(time >> 11),
+        u32::from((time >> 5) & 0x3F),
+        u32::from(time & 0x1F) * 2,
+    ))
+    .and_then(|date_time| u32::try_from(date_time.timestamp()).ok())
+    .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod archive_tests {
+    use super::*;
+
+    /// build a zip with stored and deflated entries
+    fn write_zip(path: &Path, files: &[(&str, &[u8], bool)]) {
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+        for &(name, content, deflate) in files {
+            let offset = out.len() as u32;
+            let (method, data) = if deflate {
+                let mut encoder = flate2::write::DeflateEncoder::new(
+                    Vec::new(),
+                    flate2::Compression::default(),
+                );
+                encoder.write_all(content).unwrap();
+                (8u16, encoder.finish().unwrap())
+            } else {
+                (0u16, content.to_vec())
+            };
+            let mut common = Vec::new();
+            common.extend(20u16.to_le_bytes()); // version needed
+            common.extend(0u16.to_le_bytes()); // flags
+            common.extend(method.to_le_bytes());
+            common.extend(0x6000u16.to_le_bytes()); // time: 12:00
+            common.extend(0x5921u16.to_le_bytes()); // date: 2024-09-01
+            common.extend(0u32.to_le_bytes()); // crc (not checked)
+            common.extend((data.len() as u32).to_le_bytes());
+            common.extend((content.len() as u32).to_le_bytes());
+            common.extend((name.len() as u16).to_le_bytes());
+            common.extend(0u16.to_le_bytes()); // extra len
+            out.extend(0x04034b50u32.to_le_bytes());
+            out.extend(&common);
+            out.extend(name.as_bytes());
+            out.extend(&data);
+            central.extend(0x02014b50u32.to_le_bytes());
+            central.extend(20u16.to_le_bytes()); // version made by
+            central.extend(&common);
+            central.extend([0; 6]); // comment len, disk, internal attributes
+            central.extend(0u32.to_le_bytes()); // external attributes
+            central.extend(offset.to_le_bytes());
+            central.extend(name.as_bytes());
+        }
+        let central_offset = out.len() as u32;
+        out.extend(&central);
+        out.extend(0x06054b50u32.to_le_bytes());
+        out.extend([0; 4]); // disk numbers
+        out.extend((files.len() as u16).to_le_bytes());
+        out.extend((files.len() as u16).to_le_bytes());
+        out.extend((central.len() as u32).to_le_bytes());
+        out.extend(central_offset.to_le_bytes());
+        out.extend(0u16.to_le_bytes()); // comment len
+        fs::write(path, out).unwrap();
+    }
+
+    /// build a tar, with the given entries (a name ending in '/' is a dir)
+    fn tar_bytes(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for &(name, content) in files {
+            let mut header = [0u8; 512];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[100..107].copy_from_slice(b"0000644");
+            header[124..135].copy_from_slice(format!("{:011o}", content.len()).as_bytes());
+            header[136..147].copy_from_slice(b"14671234567");
+            header[156] = if name.ends_with('/') { b'5' } else { b'0' };
+            header[257..263].copy_from_slice(b"ustar\0");
+            header[263..265].copy_from_slice(b"00");
+            header[148..156].copy_from_slice(b"        ");
+            let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+            header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+            out.extend(header);
+            out.extend(content);
+            out.resize((out.len() + 511) / 512 * 512, 0);
+        }
+        out.resize(out.len() + 1024, 0);
+        out
+    }
+
+    #[test]
+    fn test_zip_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.zip");
+        write_zip(&path, &[
+            ("src/", b"", false),
+            ("src/main.rs", b"fn main() {}\n", true),
+            ("doc/guide/intro.md", b"# Intro", false),
+            ("../evil", b"no", false),
+        ]);
+        let archive = Archive::open(&path).unwrap();
+        let names: Vec<&str> = archive.children(None)
+            .iter()
+            .map(|&idx| archive.entries[idx].name())
+            .collect();
+        assert_eq!(names, vec!["src", "doc"]);
+        let main = archive.entry_idx("src/main.rs").unwrap();
+        assert_eq!(archive.read_entry(main, 1000).unwrap(), b"fn main() {}\n");
+        assert_eq!(archive.read_entry(main, 2).unwrap(), b"fn");
+        assert_eq!(archive.entries[main].modified, 1725192000);
+        let guide = archive.entry_idx("doc/guide").unwrap();
+        assert!(archive.entries[guide].is_dir);
+        assert_eq!(archive.entries[guide].sum.to_size(), 7);
+        assert_eq!(archive.sum.to_count(), 2);
+        assert_eq!(
+            split_virtual_path(&path.join("doc/guide/intro.md")),
+            Some((path.as_path(), "doc/guide/intro.md".to_string())),
+        );
+        assert!(!is_virtual_path(&path));
+        // a corrupt archive is an error
+        fs::write(&path, b"PK\x03\x04 not really a zip").unwrap();
+        assert!(Archive::open(&path).is_err());
+    }
+
+    #[test]
+    fn test_tar_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let tar = tar_bytes(&[
+            ("./project/", b""),
+            ("./project/README", b"read me"),
+            ("./project/src/lib.rs", &[b'x'; 1000]),
+        ]);
+        let tar_path = dir.path().join("test.tar");
+        fs::write(&tar_path, &tar).unwrap();
+        let tgz_path = dir.path().join("test.tgz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            fs::File::create(&tgz_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        encoder.write_all(&tar).unwrap();
+        encoder.finish().unwrap();
+        for path in [tar_path, tgz_path] {
+            let archive = Archive::open(&path).unwrap();
+            let readme = archive.entry_idx("project/README").unwrap();
+            assert_eq!(archive.read_entry(readme, 100).unwrap(), b"read me");
+            let lib = archive.entry_idx("project/src/lib.rs").unwrap();
+            assert_eq!(archive.read_entry(lib, 10000).unwrap().len(), 1000);
+            assert_eq!(archive.entries[lib].modified, 0o14671234567);
+            assert_eq!(archive.entries[archive.entry_idx("project").unwrap()].sum.to_size(), 1007);
+        }
+        // a truncated archive is an error
+        let cut_path = dir.path().join("cut.tar");
+        fs::write(&cut_path, &tar[..700]).unwrap();
+        assert!(Archive::open(&cut_path).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Read the headers of tar files, which may be gzipped.
+//!
+//! Long names are supported in both the GNU (`L` entries) and
+//! the POSIX (`path` record of pax headers) ways.
+
+use {
+    super::*,
+    flate2::read::MultiGzDecoder,
+    std::{
+        fs::File,
+        io::{self, BufReader, Read},
+    },
+};
+
+const BLOCK_SIZE: u64 = 512;
+
+/// a pax or GNU header can't reasonably be bigger
+const MAX_META_SIZE: u64 = 1024 * 1024;
+
+fn corrupt(details: &str) -> ArchiveError {
+    ArchiveError::Corrupt { details: details.to_string() }
+}
+
+fn open(path: &Path, kind: ArchiveKind) -> io::Result<Box<dyn Read>> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(if kind == ArchiveKind::TarGz {
+        Box::new(MultiGzDecoder::new(reader))
+    } else {
+        Box::new(reader)
+    })
+}
+
+/// read a number, written either in octal ASCII or, for big
+/// ones, in base 256 (flagged by the high bit of the first byte)
+fn parse_number(field: &[u8]) -> Result<u64, ArchiveError> {
+    if field.first().map_or(false, |&b| b & 0x80 != 0) {
+        let mut n: u64 = u64::from(field[0] & 0x7F);
+        for &b in &field[1..] {
+            n = n.checked_mul(256)
+                .ok_or_else(|| corrupt("invalid number"))?
+                + u64::from(b);
+        }
+        return Ok(n);
+    }
+    let s = String::from_utf8_lossy(field);
+    let s = s.trim_matches(|c: char| c == '\0' || c == ' ');
+    if s.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(s, 8).map_err(|_| corrupt("invalid number"))
+}
+
+/// the string up to the first NUL byte
+fn parse_string(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).to_string()
+}
+
+/// skip bytes, failing if the stream ends before
+fn skip(reader: &mut dyn Read, len: u64) -> Result<(), ArchiveError> {
+    let skipped = io::copy(&mut reader.take(len), &mut io::sink())?;
+    if skipped < len {
+        return Err(corrupt("unexpected end of file"));
+    }
+    Ok(())
+}
+
+/// the size of the data of an entry, rounded up to whole blocks
+fn padded(size: u64) -> u64 {
+    (size + BLOCK_SIZE - 1) / BLOCK_SIZE * BLOCK_SIZE
+}
+
+/// read the next block, return false if the stream ended before
+/// (failing if it ended in the middle of the block)
+fn read_block(reader: &mut dyn Read, block: &mut [u8]) -> Result<bool, ArchiveError> {
+    let mut filled = 0;
+    while filled < block.len() {
+        match reader.read(&mut block[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    match filled {
+        0 => Ok(false),
+        n if n == block.len() => Ok(true),
+        _ => Err(corrupt("unexpected end of file")),
+    }
+}
+
+/// read the data of an entry holding metadata (long name, pax records)
+fn read_meta(reader: &mut dyn Read, size: u64) -> Result<Vec<u8>, ArchiveError> {
+    if size > MAX_META_SIZE {
+        return Err(corrupt("metadata entry too big"));
+    }
+    let mut data = vec![0; size as usize];
+    reader.read_exact(&mut data)
+        .map_err(|_| corrupt("unexpected end of file"))?;
+    skip(reader, padded(size) - size)?;
+    Ok(data)
+}
+
+/// find the path in the records of a pax header, each record
+/// being like "30 path=some/long/path/name\n"
+fn pax_path(data: &[u8]) -> Option<String> {
+    let data = String::from_utf8_lossy(data);
+    data.lines()
+        .filter_map(|record| record.split_once(' '))
+        .find_map(|(_, kv)| kv.strip_prefix("path="))
+        .map(|path| path.to_string())
+}
+
+pub(super) fn read_entries(
+    path: &Path,
+    kind: ArchiveKind,
+) -> Result<Vec<ArchiveEntry>, ArchiveError> {
+    let mut reader = open(path, kind)?;
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    let mut long_name: Option<String> = None;
+    let mut header = [0u8; BLOCK_SIZE as usize];
+    loop {
+        if !read_block(&mut reader, &mut header)? {
+            if offset == 0 {
+                return Err(corrupt("empty file"));
+            }
+            break; // some tools omit the final empty blocks
+        }
+        offset += BLOCK_SIZE;
+        if header.iter().all(|&b| b == 0) {
+            break; // end of archive
+        }
+        let checksum = parse_number(&header[148..156])?;
+        let computed: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| if (148..156).contains(&i) { 32 } else { u64::from(b) })
+            .sum();
+        if checksum != computed {
+            return Err(corrupt("invalid header checksum"));
+        }
+        let size = parse_number(&header[124..136])?;
+        let modified = parse_number(&header[136..148])?;
+        let type_flag = header[156];
+        match type_flag {
+            b'L' => {
+                // GNU: the data is the name of the next entry
+                long_name = Some(parse_string(&read_meta(&mut reader, size)?));
+                offset += padded(size);
+                continue;
+            }
+            b'x' => {
+                // pax header of the next entry
+                if let Some(path) = pax_path(&read_meta(&mut reader, size)?) {
+                    long_name = Some(path);
+                }
+                offset += padded(size);
+                continue;
+            }
+            b'g' | b'K' => {
+                // global pax header, GNU long link name
+                skip(&mut reader, padded(size))?;
+                offset += padded(size);
+                continue;
+            }
+            _ => {}
+        }
+        let name = long_name.take().unwrap_or_else(|| {
+            let name = parse_string(&header[0..100]);
+            let prefix = parse_string(&header[345..500]);
+            if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                format!("{prefix}/{name}")
+            } else {
+                name
+            }
+        });
+        let is_dir = type_flag == b'5' || name.ends_with('/');
+        // links and special files have no data
+        let has_data = matches!(type_flag, b'0' | b'\0' | b'7');
+        let data_size = if has_data { size } else { 0 };
+        entries.push(ArchiveEntry::new(
+            name,
+            is_dir,
+            data_size,
+            u32::try_from(modified).unwrap_or(0),
+            Location::Tar { offset },
+        ));
+        skip(&mut reader, padded(size))?;
+        offset += padded(size);
+    }
+    Ok(entries)
+}
+
+pub(super) fn read_data(
+    path: &Path,
+    kind: ArchiveKind,
+    offset: u64,
+    max_len: u64,
+) -> Result<Vec<u8>, ArchiveError> {
+    let mut reader = open(path, kind)?;
+    skip(&mut reader, offset)?;
+    let mut data = Vec::new();
+    reader.take(max_len).read_to_end(&mut data)?;
+    Ok(data)
+}
@@ -0,0 +1,185 @@
+//! Read the central directory of zip files and extract entries
+//! which are stored or deflated (the two methods found in
+//! nearly all zip files)
+
+use {
+    super::*,
+    flate2::read::DeflateDecoder,
+    std::{
+        fs::File,
+        io::{Read, Seek, SeekFrom},
+    },
+};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_SIGNATURE: u32 = 0x06054b50;
+const ZIP64_END_SIGNATURE: u32 = 0x06064b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x07064b50;
+
+/// the end of central directory record is 22 bytes, followed
+/// by a comment of at most 65535 bytes
+const MAX_END_SIZE: u64 = 22 + 65535;
+
+fn corrupt(details: &str) -> ArchiveError {
+    ArchiveError::Corrupt { details: details.to_string() }
+}
+
+fn u16_at(bytes: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes([bytes[pos], bytes[pos + 1]])
+}
+fn u32_at(bytes: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+}
+fn u64_at(bytes: &[u8], pos: usize) -> u64 {
+    u64::from_le_bytes(bytes[pos..pos + 8].try_into().unwrap())
+}
+
+fn read_at(file: &mut File, offset: u64, len: usize) -> Result<Vec<u8>, ArchiveError> {
+    let mut bytes = vec![0; len];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut bytes)
+        .map_err(|_| corrupt("unexpected end of file"))?;
+    Ok(bytes)
+}
+
+/// Return the number of entries, the size and the offset of the central directory
+fn read_end(file: &mut File) -> Result<(u64, u64, u64), ArchiveError> {
+    let file_len = file.metadata()?.len();
+    let tail_len = file_len.min(MAX_END_SIZE);
+    let tail = read_at(file, file_len - tail_len, tail_len as usize)?;
+    let end_pos = (0..tail.len().saturating_sub(21))
+        .rev()
+        .find(|&pos| u32_at(&tail, pos) == END_SIGNATURE)
+        .ok_or_else(|| corrupt("no end of central directory"))?;
+    let count = u64::from(u16_at(&tail, end_pos + 10));
+    let size = u64::from(u32_at(&tail, end_pos + 12));
+    let offset = u64::from(u32_at(&tail, end_pos + 16));
+    if count != 0xFFFF && size != 0xFFFF_FFFF && offset != 0xFFFF_FFFF {
+        return Ok((count, size, offset));
+    }
+    // zip64: the real values are in another record, found with the locator
+    let locator_pos = end_pos
+        .checked_sub(20)
+        .filter(|&pos| u32_at(&tail, pos) == ZIP64_LOCATOR_SIGNATURE)
+        .ok_or_else(|| corrupt("no zip64 locator"))?;
+    let zip64_end_offset = u64_at(&tail, locator_pos + 8);
+    let zip64_end = read_at(file, zip64_end_offset, 56)?;
+    if u32_at(&zip64_end, 0) != ZIP64_END_SIGNATURE {
+        return Err(corrupt("no zip64 end of central directory"));
+    }
+    Ok((u64_at(&zip64_end, 32), u64_at(&zip64_end, 40), u64_at(&zip64_end, 48)))
+}
+
+pub(super) fn read_entries(path: &Path) -> Result<Vec<ArchiveEntry>, ArchiveError> {
+    let mut file = File::open(path)?;
+    let (count, size, offset) = read_end(&mut file)?;
+    if size > file.metadata()?.len() {
+        return Err(corrupt("central directory bigger than file"));
+    }
+    let central = read_at(&mut file, offset, size as usize)?;
+    let mut entries = Vec::new();
+    let mut pos = 0;
+    for _ in 0..count {
+        if pos + 46 > central.len() || u32_at(&central, pos) != CENTRAL_HEADER_SIGNATURE {
+            return Err(corrupt("invalid central directory header"));
+        }
+        let flags = u16_at(&central, pos + 8);
+        let method = u16_at(&central, pos + 10);
+        let modified = dos_date_time_to_seconds(
+            u16_at(&central, pos + 14),
+            u16_at(&central, pos + 12),
+        );
+        let mut compressed_size = u64::from(u32_at(&central, pos + 20));
+        let mut size = u64::from(u32_at(&central, pos + 24));
+        let name_len = usize::from(u16_at(&central, pos + 28));
+        let extra_len = usize::from(u16_at(&central, pos + 30));
+        let comment_len = usize::from(u16_at(&central, pos + 32));
+        let mut header_offset = u64::from(u32_at(&central, pos + 42));
+        let name_start = pos + 46;
+        let extra_start = name_start + name_len;
+        let next_pos = extra_start + extra_len + comment_len;
+        if next_pos > central.len() {
+            return Err(corrupt("invalid central directory header"));
+        }
+        // the name is UTF-8 when bit 11 of flags is set, and most often
+        // ASCII otherwise (CP437 isn't decoded)
+        let name = String::from_utf8_lossy(&central[name_start..extra_start]).to_string();
+        // in zip64, values which didn't fit are in an extra field
+        let mut extra = &central[extra_start..extra_start + extra_len];
+        while extra.len() >= 4 {
+            let id = u16_at(extra, 0);
+            let len = usize::from(u16_at(extra, 2)).min(extra.len() - 4);
+            if id == 0x0001 {
+                let mut values = extra[4..4 + len].chunks_exact(8).map(|b| u64_at(b, 0));
+                for value in [&mut size, &mut compressed_size, &mut header_offset] {
+                    if *value == 0xFFFF_FFFF {
+                        if let Some(v) = values.next() {
+                            *value = v;
+                        }
+                    }
+                }
+            }
+            extra = &extra[4 + len..];
+        }
+        let is_dir = name.ends_with('/');
+        if flags & 1 != 0 && !is_dir {
+            debug!("{name:?} is encrypted");
+        }
+        entries.push(ArchiveEntry::new(
+            name,
+            is_dir,
+            if is_dir { 0 } else { size },
+            modified,
+            Location::Zip {
+                header_offset,
+                compressed_size,
+                // encrypted entries can't be read, whatever their method
+                method: if flags & 1 != 0 { u16::MAX } else { method },
+            },
+        ));
+        pos = next_pos;
+    }
+    Ok(entries)
+}
+
+pub(super) fn read_data(
+    path: &Path,
+    header_offset: u64,
+    compressed_size: u64,
+    method: u16,
+    max_len: u64,
+) -> Result<Vec<u8>, ArchiveError> {
+    let mut file = File::open(path)?;
+    let header = read_at(&mut file, header_offset, 30)?;
+    if u32_at(&header, 0) != LOCAL_HEADER_SIGNATURE {
+        return Err(corrupt("invalid local header"));
+    }
+    let data_offset = header_offset
+        + 30
+        + u64::from(u16_at(&header, 26))
+        + u64::from(u16_at(&header, 28));
+    file.seek(SeekFrom::Start(data_offset))?;
+    let compressed = file.take(compressed_size);
+    let mut data = Vec::new();
+    match method {
+        0 => {
+            compressed.take(max_len).read_to_end(&mut data)?;
+        }
+        8 => {
+            DeflateDecoder::new(compressed)
+                .take(max_len)
+                .read_to_end(&mut data)
+                .map_err(|e| corrupt(&e.to_string()))?;
+        }
+        u16::MAX => {
+            return Err(ArchiveError::Unsupported { details: "encrypted entry".to_string() });
+        }
+        _ => {
+            return Err(ArchiveError::Unsupported {
+                details: format!("compression method {method}"),
+            });
+        }
+    }
+    Ok(data)
+}
@@ -1,11 +1,14 @@
 use {
+    super::SelectionHistory,
     crate::{
         app::*,
+        archive,
         command::{Command, TriggerType},
-        display::{DisplayableTree, Screen, W},
+        display::{DisplayableTree, Screen, W, MINIMAL_PANEL_HEIGHT},
         errors::{ProgramError, TreeBuildError},
         flag::Flag,
         git,
+        launchable::Launchable,
         pattern::*,
         path::{self, PathAnchor},
         print,
@@ -28,6 +31,10 @@ pub struct BrowserState {
     // pub total_search_required: bool,   // whether the pending pattern should be in total search mode
     mode: Mode, // whether we're in 'input' or 'normal' mode
     pending_task: Option<BrowserTask>, // note: there are some other pending task, see
+    selection_history: SelectionHistory,
+    removal_request: Option<RemovalRequest>,
+    overwrite_request: Option<OverwriteRequest>,
+    transfer: Option<Transfer>, // a copy or move, not interrupted by commands
 }
 
 /// A task that can be computed in background
@@ -36,6 +43,7 @@ enum BrowserTask {
     Search {
         pattern: InputPattern,
         total: bool,
+        selection: Option<PathBuf>, // path to select instead of the best match
     },
     StageAll(InputPattern),
 }
@@ -53,7 +61,7 @@ impl BrowserState {
         let pending_task = options.pattern
             .take()
             .as_option()
-            .map(|pattern| BrowserTask::Search { pattern, total: false });
+            .map(|pattern| BrowserTask::Search { pattern, total: false, selection: None });
         let builder = TreeBuilder::from(
             path,
             options,
@@ -66,11 +74,97 @@ impl BrowserState {
             filtered_tree: None,
             mode: initial_mode(con),
             pending_task,
+            selection_history: SelectionHistory::default(),
+            removal_request: None,
+            overwrite_request: None,
+            transfer: None,
         })
     }
 
+    /// refresh the trees after a change on disk, then select
+    /// the given path if it's in the displayed tree
+    fn refresh_and_select(&mut self, path: &Path, page_height: usize, con: &AppContext) {
+        let trees = std::iter::once(&mut self.tree).chain(self.filtered_tree.as_mut());
+        for tree in trees {
+            if let Err(e) = tree.refresh(page_height, con) {
+                warn!("refreshing tree failed : {:?}", e);
+            }
+        }
+        let tree = self.displayed_tree_mut();
+        if tree.try_select_path(path) {
+            tree.make_selection_visible(page_height);
+        }
+    }
+
+    /// record the previous selection in the history, if the
+    /// selection changed
+    fn record_selection_change(&mut self, previous: PathBuf) {
+        if self.displayed_tree().selected_line().path != previous {
+            self.selection_history.record(previous);
+        }
+    }
+
+    /// expand the selected directory so that all its children are listed,
+    /// or collapse it when it's already open, then rebuild the trees
+    fn toggle_expansion(&mut self, page_height: usize, con: &AppContext) -> CmdResult {
+        let tree = self.displayed_tree();
+        let line = tree.selected_line();
+        if tree.selection == 0 || !line.is_dir() {
+            return CmdResult::error("only directories below the root can be expanded");
+        }
+        let children = || tree.lines.iter().filter(|l| l.parent_index == Some(tree.selection));
+        let is_open = children().next().is_some();
+        let is_partially_listed = children().any(|l| l.line_type == TreeLineType::Pruning);
+        let expand = !tree.options.expansions.is_expanded(&line.path)
+            && (!is_open || is_partially_listed);
+        let path = line.path.clone();
+        self.set_expansion(path, expand, page_height, con);
+        CmdResult::Keep
+    }
+
+    /// list all the children of the selected directory, or of the
+    /// parent of the selected file, keeping the selection
+    fn expand_dir(&mut self, page_height: usize, con: &AppContext) -> CmdResult {
+        let tree = self.displayed_tree();
+        let line = tree.selected_line();
+        let dir = if line.is_dir() {
+            Some(&line.path)
+        } else {
+            line.parent_index.map(|idx| &tree.lines[idx].path)
+        };
+        let Some(dir) = dir else {
+            return CmdResult::error("no directory to expand");
+        };
+        self.set_expansion(dir.clone(), true, page_height, con);
+        CmdResult::Keep
+    }
+
+    /// record that a directory must be expanded or collapsed, then
+    /// rebuild the trees, keeping the selection
+    fn set_expansion(
+        &mut self,
+        dir: PathBuf,
+        expand: bool,
+        page_height: usize,
+        con: &AppContext,
+    ) {
+        let selected_path = self.displayed_tree().selected_line().path.clone();
+        // the expansion is stored in the options of both trees, so
+        // that it's kept when the filter changes
+        let trees = std::iter::once(&mut self.tree).chain(self.filtered_tree.as_mut());
+        for tree in trees {
+            tree.options.expansions.set(dir.clone(), expand);
+        }
+        self.refresh_and_select(&selected_path, page_height, con);
+        // the lines which appeared aren't new files
+        self.tree.clear_changes();
+        if let Some(filtered_tree) = self.filtered_tree.as_mut() {
+            filtered_tree.clear_changes();
+        }
+    }
+
     fn search(&mut self, pattern: InputPattern, total: bool) {
-        self.pending_task = Some(BrowserTask::Search { pattern, total });
+        self.pending_task = Some(BrowserTask::Search { pattern, total, selection: None });
     }
 
     /// build a cmdResult asking for the addition of a new state
@@ -86,11 +180,22 @@ impl BrowserState {
         con: &AppContext,
     ) -> CmdResult {
         let tree = self.displayed_tree();
+        // a selected root is selected again only when it's not the root
+        // anymore (i.e. when going up)
+        let keep_selection = tree.selection != 0 || &root != tree.root();
         let mut new_state = BrowserState::new(root, options, screen, con, &Dam::unlimited());
         if let Ok(bs) = &mut new_state {
-            if tree.selection != 0 {
-                bs.displayed_tree_mut().try_select_path(&tree.selected_line().path);
+            if keep_selection {
+                let path = tree.selected_line().path.clone();
+                if bs.tree.try_select_path_or_ancestor(&path) {
+                    bs.tree.make_selection_visible(BrowserState::page_height(screen));
+                }
+                // if there's a pattern, the filtered tree isn't built yet
+                if let Some(BrowserTask::Search { selection, .. }) = &mut bs.pending_task {
+                    *selection = Some(path);
+                }
             }
+            bs.selection_history = self.selection_history.clone();
         }
         CmdResult::from_optional_state(
             new_state,
@@ -104,7 +209,23 @@ impl BrowserState {
     }
 
     pub fn page_height(screen: Screen) -> usize {
-        screen.height as usize - 2 // br shouldn't be displayed when the screen is smaller
+        // the panel areas are never smaller than the minimal height, so
+        // that there's always room for the root and the selected line
+        (screen.height.max(MINIMAL_PANEL_HEIGHT) - 3) as usize
+    }
+
+    /// go back to the unfiltered tree, which is kept while a pattern
+    /// is active, so that there's no need to read the disk again.
+    /// The selection of the filtered tree is kept when it's there.
+    fn remove_filter(&mut self, page_height: usize) {
+        if matches!(self.pending_task, Some(BrowserTask::Search { .. })) {
+            self.pending_task = None;
+        }
+        if let Some(filtered_tree) = self.filtered_tree.take() {
+            if self.tree.try_select_path(&filtered_tree.selected_line().path) {
+                self.tree.make_selection_visible(page_height);
+            }
+        }
     }
 
     /// return a reference to the currently displayed tree, which
@@ -128,8 +249,21 @@ impl BrowserState {
     ) -> Result<CmdResult, ProgramError> {
         let tree = self.displayed_tree();
         let line = tree.selected_line();
-        let mut target = line.target().to_path_buf();
-        if line.is_dir() {
+        // a link to a directory is focused as a directory under the
+        // link path, unless the user prefers going to the target
+        let mut target = if line.is_dir() && !con.focus_link_targets {
+            line.path.clone()
+        } else {
+            line.target().to_path_buf()
+        };
+        if con.launch_args.select_only && (!line.is_dir() || con.launch_args.dirs_ok) {
+            // the session ends with the picked path printed on stdout
+            return Ok(CmdResult::from(Launchable::printer(
+                line.path.to_string_lossy().to_string(),
+            )));
+        }
+        // archives are entered as if they were directories
+        if line.is_dir() || archive::is_archive(&target) {
             if tree.selection == 0 {
                 // opening the root would be going to where we already are.
                 // We go up one level instead
@@ -153,17 +287,41 @@ impl BrowserState {
                 None,
                 in_new_panel,
             ))
+        } else if line.in_archive {
+            Ok(CmdResult::error("A file in an archive can't be opened"))
         } else {
             match opener::open(&target) {
                 Ok(exit_status) => {
                     info!("open returned with exit_status {:?}", exit_status);
                     Ok(CmdResult::Keep)
                 }
-                Err(e) => Ok(CmdResult::error(format!("{e:?}"))),
+                Err(e) => Ok(CmdResult::error(format!(
+                    "Failed to open `{}`: {e}",
+                    target.to_string_lossy(),
+                ))),
             }
         }
     }
 
+    /// focus the parent of the root, keeping the options and the pattern,
+    /// with the previous root selected (or the previous selection).
+    ///
+    /// Nothing is done when the root has no parent.
+    fn up_tree(&self, screen: Screen, con: &AppContext, in_new_panel: bool) -> CmdResult {
+        let tree = self.displayed_tree();
+        match tree.root().parent() {
+            Some(parent) => self.modified(
+                screen,
+                parent.to_path_buf(),
+                tree.options.clone(),
+                None,
+                in_new_panel,
+                con,
+            ),
+            None => CmdResult::Keep,
+        }
+    }
+
     pub fn go_to_parent(
         &mut self,
         screen: Screen,
@@ -194,6 +352,14 @@ impl PanelState for BrowserState {
         Some(self.root())
     }
 
+    fn watched_dirs(&self) -> Vec<&Path> {
+        self.displayed_tree().lines
+            .iter()
+            .filter(|line| line.is_dir() && !line.in_archive)
+            .map(|line| line.path.as_path())
+            .collect()
+    }
+
     fn get_type(&self) -> PanelStateType {
         PanelStateType::Tree
     }
@@ -207,7 +373,9 @@ impl PanelState for BrowserState {
     }
 
     fn get_pending_task(&self) -> Option<&'static str> {
-        if self.displayed_tree().has_dir_missing_sum() {
+        if let Some(transfer) = &self.transfer {
+            Some(transfer.kind.progress_name())
+        } else if self.displayed_tree().has_dir_missing_sum() {
             Some("computing stats")
         } else if self.displayed_tree().is_missing_git_status_computation() {
             Some("computing git status")
@@ -224,6 +392,10 @@ impl PanelState for BrowserState {
         Some(&self.displayed_tree().selected_line().path)
     }
 
+    fn tree_stats(&self) -> Option<TreeStats> {
+        Some(self.displayed_tree().stats)
+    }
+
     fn selection(&self) -> Option<Selection<'_>> {
         let tree = self.displayed_tree();
         let mut selection = tree.selected_line().as_selection();
@@ -265,13 +437,39 @@ impl PanelState for BrowserState {
         self.pending_task = None;
     }
 
+    fn age_requests(&mut self) {
+        OverwriteRequest::age(&mut self.overwrite_request);
+        RemovalRequest::age(&mut self.removal_request);
+        // the changes of the last refresh are shown until the next verb
+        self.tree.clear_changes();
+        if let Some(filtered_tree) = self.filtered_tree.as_mut() {
+            filtered_tree.clear_changes();
+        }
+    }
+
     fn on_click(
         &mut self,
         _x: u16,
         y: u16,
-        _screen: Screen,
-        _con: &AppContext,
+        screen: Screen,
+        con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
+        let tree = self.displayed_tree();
+        let idx = tree.line_index_at_y(y as usize);
+        if idx == tree.selection && tree.selected_line().is_dir() {
+            // a click on the already selected directory focuses it
+            return self.open_selection_stay_in_broot(screen, con, false, false);
+        }
+        if let Some(line) = tree.lines.get(idx) {
+            if line.line_type == TreeLineType::Pruning {
+                // a click on "N unlisted" lists the rest of the directory
+                if let Some(parent_idx) = line.parent_index {
+                    let dir = tree.lines[parent_idx].path.clone();
+                    self.set_expansion(dir, true, BrowserState::page_height(screen), con);
+                }
+                return Ok(CmdResult::Keep);
+            }
+        }
         self.displayed_tree_mut().try_select_y(y as usize);
         Ok(CmdResult::Keep)
     }
@@ -283,7 +481,8 @@ impl PanelState for BrowserState {
         screen: Screen,
         con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
-        if self.displayed_tree().selection == y as usize {
+        let tree = self.displayed_tree();
+        if tree.line_index_at_y(y as usize) == tree.selection {
             self.open_selection_stay_in_broot(screen, con, false, false)
         } else {
             // A double click always come after a simple click at
@@ -297,10 +496,12 @@ impl PanelState for BrowserState {
         &mut self,
         pat: InputPattern,
         _app_state: &AppState,
+        screen: Screen,
         _con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
         if pat.is_none() {
-            self.filtered_tree = None;
+            self.remove_filter(BrowserState::page_height(screen));
+            return Ok(CmdResult::Keep);
         }
         if let Some(filtered_tree) = &self.filtered_tree {
             if pat != filtered_tree.options.pattern {
@@ -327,14 +528,11 @@ impl PanelState for BrowserState {
         let bang = input_invocation
             .map(|inv| inv.bang)
             .unwrap_or(internal_exec.bang);
-        Ok(match internal_exec.internal {
+        let previous_selection = self.displayed_tree().selected_line().path.clone();
+        let res = match internal_exec.internal {
             Internal::back => {
-                if let Some(filtered_tree) = &self.filtered_tree {
-                    let filtered_selection = &filtered_tree.selected_line().path;
-                    if self.tree.try_select_path(filtered_selection) {
-                        self.tree.make_selection_visible(page_height);
-                    }
-                    self.filtered_tree = None;
+                if self.filtered_tree.is_some() {
+                    self.remove_filter(page_height);
                     CmdResult::Keep
                 } else if self.tree.selection > 0 {
                     self.tree.selection = 0;
@@ -352,6 +550,24 @@ impl PanelState for BrowserState {
                 app_state,
                 cc,
             ),
+            Internal::mark => internal_mark::on_mark(
+                internal_exec,
+                input_invocation,
+                self.displayed_tree(),
+                app_state,
+            ),
+            Internal::go_to_mark => {
+                // the options of the unfiltered tree, in case of re-rooting
+                let options = self.tree.options.clone();
+                internal_mark::on_go_to_mark(
+                    internal_exec,
+                    input_invocation,
+                    self.displayed_tree_mut(),
+                    options,
+                    app_state,
+                    cc,
+                )
+            }
             Internal::select => internal_select::on_internal(
                 internal_exec,
                 input_invocation,
@@ -360,16 +576,195 @@ impl PanelState for BrowserState {
                 app_state,
                 cc,
             ),
-            Internal::up_tree => match self.displayed_tree().root().parent() {
-                Some(path) => internal_focus::on_path(
-                    path.to_path_buf(),
-                    screen,
-                    self.displayed_tree().options.clone(),
-                    bang,
-                    con,
-                ),
-                None => CmdResult::error("no parent found"),
-            },
+            Internal::up_tree => self.up_tree(screen, con, bang),
+            Internal::rm | Internal::rm_permanently => {
+                let permanently = internal_exec.internal == Internal::rm_permanently;
+                let tree = self.displayed_tree();
+                let path = tree.selected_line().path.clone();
+                let root = tree.root().clone();
+                match internal_rm::remove(
+                    vec![path],
+                    &root,
+                    &mut self.removal_request,
+                    permanently,
+                ) {
+                    Removal::Done => {
+                        let tree = self.displayed_tree_mut();
+                        if !tree.select_next_sibling() {
+                            tree.select_parent();
+                        }
+                        CmdResult::RefreshState { clear_cache: true }
+                    }
+                    Removal::Confirm(message) => CmdResult::DisplayError(message),
+                    Removal::Failed(message) => {
+                        // some of the content may have been deleted
+                        if let Err(e) = self.displayed_tree_mut().refresh(page_height, con) {
+                            warn!("refreshing tree failed : {:?}", e);
+                        }
+                        CmdResult::error(message)
+                    }
+                }
+            }
+            Internal::cp | Internal::mv => {
+                let kind = if internal_exec.internal == Internal::cp {
+                    TransferKind::Copy
+                } else {
+                    TransferKind::Move
+                };
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                if self.transfer.is_some() {
+                    CmdResult::error("A copy or move is already in progress")
+                } else if let Some(arg) = arg {
+                    let tree = self.displayed_tree();
+                    let src = tree.selected_line().path.clone();
+                    let dst = path::path_from(&src, PathAnchor::Parent, arg);
+                    let root = tree.root().clone();
+                    let paths = vec![(src, dst)];
+                    match internal_cp::start(kind, paths, &root, kind.internal_name(), &mut self.overwrite_request) {
+                        TransferStart::Started(mut transfers) => {
+                            // a rename is immediate, a copy goes on as a pending task
+                            self.transfer = transfers.pop();
+                            CmdResult::Keep
+                        }
+                        TransferStart::Confirm(message) => CmdResult::DisplayError(message),
+                        TransferStart::Failed(message) => CmdResult::error(message),
+                    }
+                } else {
+                    CmdResult::error(format!("Usage: :{} <newpath>", kind.internal_name()))
+                }
+            }
+            Internal::copy_to_panel | Internal::move_to_panel => {
+                let kind = if internal_exec.internal == Internal::copy_to_panel {
+                    TransferKind::Copy
+                } else {
+                    TransferKind::Move
+                };
+                if self.transfer.is_some() {
+                    CmdResult::error("A copy or move is already in progress")
+                } else if let Some(other_path) = &app_state.other_panel_path {
+                    let tree = self.displayed_tree();
+                    let src = tree.selected_line().path.clone();
+                    let paths = vec![(src, path::closest_dir(other_path))];
+                    let root = tree.root().clone();
+                    let verb_name = internal_exec.internal.name();
+                    match internal_cp::start(kind, paths, &root, verb_name, &mut self.overwrite_request) {
+                        TransferStart::Started(mut transfers) => {
+                            self.transfer = transfers.pop();
+                            CmdResult::Keep
+                        }
+                        TransferStart::Confirm(message) => CmdResult::DisplayError(message),
+                        TransferStart::Failed(message) => CmdResult::error(message),
+                    }
+                } else {
+                    CmdResult::error("This verb needs exactly two panels")
+                }
+            }
+            Internal::mkdir => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                if let Some(arg) = arg {
+                    let selected = &self.displayed_tree().selected_line().path;
+                    let path = path::path_from(selected, PathAnchor::Directory, arg);
+                    match internal_mkdir::create_dir(&path) {
+                        Ok(()) => {
+                            // refresh_and_select rebuilds the trees, no need for RefreshState
+                            self.refresh_and_select(&path, page_height, con);
+                            CmdResult::Keep
+                        }
+                        Err(message) => CmdResult::error(message),
+                    }
+                } else {
+                    CmdResult::error("Usage: :mkdir <subpath>")
+                }
+            }
+            Internal::touch => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                if let Some(arg) = arg {
+                    let selected = &self.displayed_tree().selected_line().path;
+                    let path = path::path_from(selected, PathAnchor::Directory, arg);
+                    match internal_mkdir::create_file(&path, con.touch_creates_dirs) {
+                        Ok(()) => {
+                            // refresh_and_select rebuilds the trees, no need for RefreshState
+                            self.refresh_and_select(&path, page_height, con);
+                            if con.edit_new_files {
+                                let launchable = Launchable::editor(&path, 0, con);
+                                if let Err(e) = launchable.execute(Some(w)) {
+                                    warn!("editor failed : {:?}", e);
+                                    CmdResult::error(e.to_string())
+                                } else {
+                                    // the editor may have written other files, eg backups
+                                    CmdResult::RefreshState { clear_cache: true }
+                                }
+                            } else {
+                                CmdResult::Keep
+                            }
+                        }
+                        Err(message) => CmdResult::error(message),
+                    }
+                } else {
+                    CmdResult::error("Usage: :touch <subpath>")
+                }
+            }
+            #[cfg(not(any(target_family = "windows", target_os = "android")))]
+            Internal::chmod => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                if let Some(arg) = arg {
+                    let path = self.displayed_tree().selected_line().path.clone();
+                    let res = crate::permissions::parse_chmod_args(arg).and_then(|(change, recursive)| {
+                        let res = crate::permissions::change_mode(&path, &change, recursive);
+                        // even after a failure, some modes may have changed
+                        let trees = std::iter::once(&mut self.tree).chain(self.filtered_tree.as_mut());
+                        for tree in trees {
+                            tree.reload_metadata(&path, recursive);
+                        }
+                        res
+                    });
+                    match res {
+                        Ok(()) => CmdResult::Keep,
+                        Err(message) => CmdResult::error(message),
+                    }
+                } else {
+                    CmdResult::error("Usage: :chmod [-R] <mode>")
+                }
+            }
+            Internal::rename => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                if let Some(new_name) = arg {
+                    let tree = self.displayed_tree();
+                    let src = tree.selected_line().path.clone();
+                    let root = tree.root().clone();
+                    match internal_cp::rename(src.clone(), new_name, &root, &mut self.overwrite_request) {
+                        TransferStart::Started(transfers) => {
+                            let mut res = CmdResult::RefreshState { clear_cache: true };
+                            for mut transfer in transfers {
+                                if let Err(e) = transfer.finish() {
+                                    res = CmdResult::error(format!("Failed to rename: {e}"));
+                                } else {
+                                    // updating the lines lets the refresh keep the selection
+                                    self.tree.rename_path(&src, &transfer.dst);
+                                    if let Some(filtered_tree) = self.filtered_tree.as_mut() {
+                                        filtered_tree.rename_path(&src, &transfer.dst);
+                                    }
+                                }
+                            }
+                            res
+                        }
+                        TransferStart::Confirm(message) => CmdResult::DisplayError(message),
+                        TransferStart::Failed(message) => CmdResult::error(message),
+                    }
+                } else {
+                    CmdResult::error("Usage: :rename <new_filename>")
+                }
+            }
             Internal::open_stay => self.open_selection_stay_in_broot(screen, con, bang, false)?,
             Internal::open_stay_filter => self.open_selection_stay_in_broot(screen, con, bang, true)?,
             Internal::line_down => {
@@ -392,6 +787,17 @@ impl PanelState for BrowserState {
                 self.displayed_tree_mut().move_selection(-count, page_height, false);
                 CmdResult::Keep
             }
+            Internal::scroll_down => {
+                // half a page by default, like in most pagers
+                let count = get_arg(input_invocation, internal_exec, page_height as i32 / 2);
+                self.displayed_tree_mut().try_scroll(count, page_height);
+                CmdResult::Keep
+            }
+            Internal::scroll_up => {
+                let count = get_arg(input_invocation, internal_exec, page_height as i32 / 2);
+                self.displayed_tree_mut().try_scroll(-count, page_height);
+                CmdResult::Keep
+            }
             Internal::previous_dir => {
                 self.displayed_tree_mut().try_select_previous_filtered(
                     |line| line.is_dir(),
@@ -420,6 +826,20 @@ impl PanelState for BrowserState {
                 );
                 CmdResult::Keep
             }
+            Internal::previous_sibling => {
+                let tree = self.displayed_tree_mut();
+                if tree.select_previous_sibling() {
+                    tree.make_selection_visible(page_height);
+                }
+                CmdResult::Keep
+            }
+            Internal::next_sibling => {
+                let tree = self.displayed_tree_mut();
+                if tree.select_next_sibling() {
+                    tree.make_selection_visible(page_height);
+                }
+                CmdResult::Keep
+            }
             Internal::previous_same_depth => {
                 self.displayed_tree_mut().try_select_previous_same_depth(page_height);
                 CmdResult::Keep
@@ -430,14 +850,18 @@ impl PanelState for BrowserState {
             }
             Internal::page_down => {
                 let tree = self.displayed_tree_mut();
-                if !tree.try_scroll(page_height as i32, page_height) {
+                if tree.try_scroll(page_height as i32, page_height) {
+                    tree.select_visible_line(page_height);
+                } else {
                     tree.try_select_last(page_height);
                 }
                 CmdResult::Keep
             }
             Internal::page_up => {
                 let tree = self.displayed_tree_mut();
-                if !tree.try_scroll(-(page_height as i32), page_height) {
+                if tree.try_scroll(-(page_height as i32), page_height) {
+                    tree.select_visible_line(page_height);
+                } else {
                     tree.try_select_first();
                 }
                 CmdResult::Keep
@@ -462,9 +886,12 @@ impl PanelState for BrowserState {
             Internal::panel_left_no_open => CmdResult::HandleInApp(Internal::panel_left_no_open),
             Internal::panel_right => {
                 let areas = &cc.panel.areas;
-                let selected_path = &self.displayed_tree().selected_line().path;
+                let selected_line = self.displayed_tree().selected_line();
+                let selected_path = &selected_line.path;
+                // files in archives don't exist on disk but can be previewed
+                let is_file = selected_path.is_file() || selected_line.is_file();
                 if areas.is_last() && areas.nb_pos < con.max_panels_count {
-                    let purpose = if selected_path.is_file() && cc.app.preview_panel.is_none() {
+                    let purpose = if is_file && cc.app.preview_panel.is_none() {
                         PanelPurpose::Preview
                     } else {
                         PanelPurpose::None
@@ -539,6 +966,50 @@ impl PanelState for BrowserState {
                     CmdResult::Keep
                 }
             }
+            Internal::selection_back => {
+                // not displayed_tree_mut, to keep the history borrowable
+                let tree = self.filtered_tree.as_mut().unwrap_or(&mut self.tree);
+                let current = tree.selected_line().path.clone();
+                if self.selection_history.go_back(&current, |path| tree.try_select_path(path)) {
+                    tree.make_selection_visible(page_height);
+                }
+                CmdResult::Keep
+            }
+            Internal::selection_forward => {
+                let tree = self.filtered_tree.as_mut().unwrap_or(&mut self.tree);
+                let current = tree.selected_line().path.clone();
+                if self.selection_history.go_forward(&current, |path| tree.try_select_path(path)) {
+                    tree.make_selection_visible(page_height);
+                }
+                CmdResult::Keep
+            }
+            Internal::toggle_flat => {
+                // only the display changes, there's no need to rebuild
+                self.tree.options.flat ^= true;
+                let flat = self.tree.options.flat;
+                if let Some(filtered_tree) = self.filtered_tree.as_mut() {
+                    filtered_tree.options.flat = flat;
+                }
+                CmdResult::Keep
+            }
+            Internal::select_line => {
+                let line = get_arg(input_invocation, internal_exec, 0);
+                let page_height = BrowserState::page_height(screen);
+                let tree = self.displayed_tree_mut();
+                if tree.try_select_index(line) {
+                    tree.make_selection_visible(page_height);
+                }
+                CmdResult::Keep
+            }
+            Internal::toggle_line_numbers => {
+                // only the display changes, there's no need to rebuild
+                self.tree.options.show_line_numbers ^= true;
+                let show_line_numbers = self.tree.options.show_line_numbers;
+                if let Some(filtered_tree) = self.filtered_tree.as_mut() {
+                    filtered_tree.options.show_line_numbers = show_line_numbers;
+                }
+                CmdResult::Keep
+            }
             Internal::select_first => {
                 self.displayed_tree_mut().try_select_first();
                 CmdResult::Keep
@@ -548,6 +1019,19 @@ impl PanelState for BrowserState {
                 self.displayed_tree_mut().try_select_last(page_height);
                 CmdResult::Keep
             }
+            Internal::select_parent => {
+                let page_height = BrowserState::page_height(screen);
+                let tree = self.displayed_tree_mut();
+                tree.select_parent();
+                tree.make_selection_visible(page_height);
+                CmdResult::Keep
+            }
+            Internal::expand_dir => {
+                self.expand_dir(BrowserState::page_height(screen), con)
+            }
+            Internal::toggle_expansion => {
+                self.toggle_expansion(BrowserState::page_height(screen), con)
+            }
             Internal::start_end_panel => {
                 if cc.panel.purpose.is_arg_edition() {
                     debug!("start_end understood as end");
@@ -607,7 +1091,15 @@ impl PanelState for BrowserState {
                 app_state,
                 cc,
             )?,
-        })
+        };
+        // only jumps are recorded, not moves to a neighbouring line
+        if matches!(
+            internal_exec.internal,
+            Internal::focus | Internal::go_to_mark | Internal::select | Internal::select_line,
+        ) {
+            self.record_selection_change(previous_selection);
+        }
+        Ok(res)
     }
 
     fn no_verb_status(
@@ -616,6 +1108,13 @@ impl PanelState for BrowserState {
         con: &AppContext,
         width: usize,
     ) -> Status {
+        if let Some(transfer) = &self.transfer {
+            return Status::from_message(format!(
+                "Transfer to {}: *{}* files and directories done",
+                transfer.dst.to_string_lossy(),
+                transfer.done_count,
+            ));
+        }
         let tree = self.displayed_tree();
         if tree.is_empty() && tree.build_report.hidden_count > 0 {
             let mut parts = Vec::new();
@@ -629,16 +1128,42 @@ impl PanelState for BrowserState {
                 return Status::from_error(parts.join(". "));
             }
         }
+        if tree.is_empty() && tree.build_report.ignored_count > 0 {
+            return Status::from_error("All files are excluded by ignore globs".to_string());
+        }
+        let line = tree.selected_line();
+        if let Some(error) = line.error {
+            return Status::from_error(format!("`{}`: {}", line.name, error.description()));
+        }
+        let matches = match &self.filtered_tree {
+            Some(filtered_tree) => match matches_markdown(filtered_tree) {
+                Some(md) => Some(md),
+                None => return Status::from_error("No match"),
+            },
+            None => None,
+        };
+        let prefix: Vec<String> = [capped_markdown(tree), changes_markdown(tree), matches]
+            .into_iter()
+            .flatten()
+            .collect();
+        let prefix = if prefix.is_empty() { None } else { Some(prefix.join(" ")) };
+        let prefix_width = prefix.as_ref().map_or(0, |md| md.len());
         let mut ssb = con.standard_status.builder(
             PanelStateType::Tree,
             tree.selected_line().as_selection(),
-            width,
+            width.saturating_sub(prefix_width),
         );
         ssb.has_previous_state = has_previous_state;
         ssb.is_filtered = self.filtered_tree.is_some();
+        ssb.is_partial_search = self.filtered_tree.as_ref()
+            .map_or(false, |filtered_tree| !filtered_tree.total_search);
         ssb.has_removed_pattern = false;
         ssb.on_tree_root = tree.selection == 0;
-        ssb.status()
+        let mut status = ssb.status();
+        if let Some(md) = prefix {
+            status.message = format!("{md} {}", status.message);
+        }
+        status
     }
 
     /// do some work, totally or partially, if there's some to do.
@@ -650,15 +1175,42 @@ impl PanelState for BrowserState {
         con: &AppContext,
         dam: &mut Dam,
     ) -> Result<(), ProgramError> {
-        if let Some(pending_task) = self.pending_task.take() {
+        if let Some(mut transfer) = self.transfer.take() {
+            let page_height = BrowserState::page_height(screen);
+            match transfer.step(dam) {
+                Ok(false) => {
+                    self.transfer = Some(transfer);
+                }
+                Ok(true) => {
+                    self.refresh_and_select(&transfer.dst, page_height, con);
+                    app_state.refresh_other_panels = true;
+                }
+                Err(e) => {
+                    self.refresh_and_select(&transfer.src, page_height, con);
+                    app_state.refresh_other_panels = true;
+                    return Err(ProgramError::FileTransfer {
+                        details: format!("Failed to {}: {e}", transfer.kind.verb()),
+                    });
+                }
+            }
+        } else if let Some(pending_task) = self.pending_task.take() {
             match pending_task {
-                BrowserTask::Search { pattern, total } => {
+                BrowserTask::Search { pattern, total, selection } => {
                     let pattern_str = pattern.raw.clone();
                     let mut options = self.tree.options.clone();
                     options.pattern = pattern;
                     let root = self.tree.root().clone();
                     let page_height = BrowserState::page_height(screen);
-                    let builder = TreeBuilder::from(root, options, page_height, con)?;
+                    let mut builder = TreeBuilder::from(root, options, page_height, con)?;
+                    if total {
+                        // a search stopped before its end goes on from where it was
+                        let continuation = self.filtered_tree.as_ref()
+                            .filter(|ft| ft.options.pattern.raw == pattern_str)
+                            .and_then(|ft| ft.search_continuation.as_deref());
+                        if let Some(continuation) = continuation {
+                            builder.resume(continuation.clone());
+                        }
+                    }
                     let filtered_tree = time!(
                         Info,
                         "tree filtering",
@@ -666,9 +1218,17 @@ impl PanelState for BrowserState {
                         builder.build_tree(total, dam),
                     );
                     if let Ok(mut ft) = filtered_tree {
-                        ft.try_select_best_match();
+                        let previous = self.displayed_tree().selected_line().path.clone();
+                        let restored = selection
+                            .map_or(false, |path| ft.try_select_path_or_ancestor(&path));
+                        if !restored {
+                            ft.try_select_best_match();
+                        }
                         ft.make_selection_visible(BrowserState::page_height(screen));
                         self.filtered_tree = Some(ft);
+                        if !restored {
+                            self.record_selection_change(previous);
+                        }
                     }
                 }
                 BrowserTask::StageAll(pattern) => {
@@ -684,7 +1244,10 @@ impl PanelState for BrowserState {
                             time!(builder.build_paths(
                                 total_search,
                                 dam,
-                                |line| line.file_type.is_file() || line.file_type.is_symlink(),
+                                |line| {
+                                    (line.file_type.is_file() || line.file_type.is_symlink())
+                                        && line.archive.is_none()
+                                },
                             ))
                         })?;
                     for path in paths.drain(..) {
@@ -736,6 +1299,22 @@ impl PanelState for BrowserState {
         })
     }
 
+    /// rebuild the trees only when there's more room than lines and
+    /// some may be missing. Width changes and shrinks need no disk access,
+    /// the tree is just scrolled to keep the selection visible.
+    fn on_resize(&mut self, screen: Screen, con: &AppContext) {
+        let page_height = BrowserState::page_height(screen);
+        let trees = std::iter::once(&mut self.tree).chain(self.filtered_tree.as_mut());
+        for tree in trees {
+            if tree.lines.len() < page_height && tree.has_unlisted() {
+                if let Err(e) = tree.refresh(page_height, con) {
+                    warn!("refreshing tree on resize failed : {:?}", e);
+                }
+            }
+            tree.make_selection_visible(page_height);
+        }
+    }
+
     fn get_flags(&self) -> Vec<Flag> {
         let options = &self.displayed_tree().options;
         vec![
@@ -759,3 +1338,83 @@ impl PanelState for BrowserState {
     }
 }
 
+/// build the markdown telling how many lines match the pattern and
+/// which one is selected, eg "*3/14* matches.", or None when
+/// there's no match
+fn matches_markdown(tree: &Tree) -> Option<String> {
+    let (count, rank) = tree.count_matches();
+    if count == 0 {
+        return None;
+    }
+    let plus = if tree.may_miss_matches() { "+" } else { "" };
+    let s = if count > 1 || !plus.is_empty() { "es" } else { "" };
+    Some(match rank {
+        Some(rank) => format!("*{rank}/{count}{plus}* match{s}."),
+        None => format!("*{count}{plus}* match{s}."),
+    })
+}
+
+/// return the markdown warning that some directories had more
+/// than HARD_MAX_CHILDREN entries, so weren't totally read
+fn capped_markdown(tree: &Tree) -> Option<String> {
+    match tree.build_report.capped_dir_count {
+        0 => None,
+        1 => Some("*1* huge dir partially read.".to_string()),
+        n => Some(format!("*{n}* huge dirs partially read.")),
+    }
+}
+
+/// return the markdown telling what changed in the last refresh, if anything
+fn changes_markdown(tree: &Tree) -> Option<String> {
+    let new_count = tree.new_count();
+    let mut parts = Vec::new();
+    if new_count > 0 {
+        parts.push(format!("*{new_count}* new"));
+    }
+    if tree.gone_count > 0 {
+        parts.push(format!("*{}* gone", tree.gone_count));
+    }
+    if parts.is_empty() {
+        None
+    } else {
+        Some(format!("{}.", parts.join(", ")))
+    }
+}
+
+#[test]
+fn test_up_tree_keeps_the_pattern() {
+    use {
+        crate::command::CommandParts,
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("sub");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("file.txt"), "").unwrap();
+    fs::write(dir.path().join("other.md"), "").unwrap();
+    let con = test_util::app_context(&root);
+    let screen = Screen { width: 80, height: 30 };
+    let mut app_state = AppState {
+        stage: Stage::default(),
+        root: root.clone(),
+        other_panel_path: None,
+        marks: Marks::default(),
+        refresh_other_panels: false,
+    };
+    let parts = CommandParts::from("txt");
+    let options = TreeOptions {
+        pattern: InputPattern::new("txt".to_string(), &parts.pattern, &con).unwrap(),
+        ..TreeOptions::default()
+    };
+    let mut state = BrowserState::new(root.clone(), options, screen, &con, &Dam::unlimited()).unwrap();
+    state.do_pending_task(&mut app_state, screen, &con, &mut Dam::unlimited()).unwrap();
+    let CmdResult::NewState { state: mut parent_state, .. } = state.up_tree(screen, &con, false) else {
+        panic!("no new state");
+    };
+    assert_eq!(parent_state.get_starting_input(), "txt");
+    parent_state.do_pending_task(&mut app_state, screen, &con, &mut Dam::unlimited()).unwrap();
+    assert_eq!(parent_state.tree_root(), Some(dir.path()));
+    // the best match was selected, and still is
+    assert_eq!(parent_state.selected_path(), Some(root.join("file.txt").as_path()));
+    assert_eq!(parent_state.tree_options().pattern.raw, "txt");
+}
@@ -1,3 +1,7 @@
 mod browser_state;
+mod selection_history;
 
-pub use browser_state::BrowserState;
+pub use {
+    browser_state::BrowserState,
+    selection_history::SelectionHistory,
+};
@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+
+/// max number of paths kept in each direction
+const MAX_LEN: usize = 100;
+
+/// The history of the paths which were selected in a browser,
+/// navigable back and forth like the history of a web browser.
+///
+/// Paths are stored rather than line indices so that the history
+/// survives tree rebuilds.
+#[derive(Debug, Clone, Default)]
+pub struct SelectionHistory {
+    back: Vec<PathBuf>,
+    forward: Vec<PathBuf>,
+}
+
+impl SelectionHistory {
+    /// record the path which was selected before a selection change
+    /// which didn't come from the history. This clears the forward stack.
+    pub fn record(&mut self, previous: PathBuf) {
+        self.forward.clear();
+        if self.back.last() != Some(&previous) {
+            push_bounded(&mut self.back, previous);
+        }
+    }
+
+    /// go back to the most recent path which can still be selected,
+    /// return whether the selection changed.
+    ///
+    /// `select` must try to select the given path and tell whether it worked.
+    pub fn go_back(
+        &mut self,
+        current: &Path,
+        select: impl FnMut(&Path) -> bool,
+    ) -> bool {
+        navigate(&mut self.back, &mut self.forward, current, select)
+    }
+
+    /// go forward to the most recent path which can still be selected,
+    /// return whether the selection changed
+    pub fn go_forward(
+        &mut self,
+        current: &Path,
+        select: impl FnMut(&Path) -> bool,
+    ) -> bool {
+        navigate(&mut self.forward, &mut self.back, current, select)
+    }
+}
+
+fn push_bounded(stack: &mut Vec<PathBuf>, path: PathBuf) {
+    if stack.len() >= MAX_LEN {
+        stack.remove(0);
+    }
+    stack.push(path);
+}
+
+/// pop paths from `from` until one can be selected, pushing the
+/// current one to `to` on success. Paths which can't be selected
+/// (eg because they're not in the tree anymore) are dropped.
+fn navigate(
+    from: &mut Vec<PathBuf>,
+    to: &mut Vec<PathBuf>,
+    current: &Path,
+    mut select: impl FnMut(&Path) -> bool,
+) -> bool {
+    while let Some(path) = from.pop() {
+        if path != current && select(&path) {
+            push_bounded(to, current.to_path_buf());
+            return true;
+        }
+    }
+    false
+}
+
+#[test]
+fn test_selection_history() {
+    let p = |s: &str| PathBuf::from(s);
+    let mut history = SelectionHistory::default();
+    history.record(p("a"));
+    history.record(p("b"));
+    history.record(p("b"));
+    history.record(p("c"));
+    // current is "d", and "b" isn't in the tree anymore
+    let mut selected = None;
+    assert!(history.go_back(&p("d"), |path| {
+        selected = Some(path.to_path_buf());
+        true
+    }));
+    assert_eq!(selected, Some(p("c")));
+    assert!(history.go_back(&p("c"), |path| path != Path::new("b")));
+    assert_eq!(history.back, Vec::<PathBuf>::new());
+    assert!(!history.go_back(&p("a"), |_| true));
+    assert!(history.go_forward(&p("a"), |path| path == Path::new("c")));
+    assert!(history.go_forward(&p("c"), |path| path == Path::new("d")));
+    assert!(!history.go_forward(&p("d"), |_| true));
+    // a new selection clears the forward stack
+    assert!(history.go_back(&p("d"), |_| true));
+    history.record(p("c"));
+    assert!(!history.go_forward(&p("e"), |_| true));
+}
@@ -40,6 +40,39 @@ pub struct Args {
     #[arg(short='F', long)]
     pub no_only_folders: bool,
 
+    /// List the content of symlinked directories
+    #[arg(long)]
+    pub follow_links: bool,
+
+    /// Don't list the content of symlinked directories
+    #[arg(long)]
+    pub no_follow_links: bool,
+
+    /// Don't enter directories on other filesystems than the root's one
+    #[arg(long)]
+    pub one_file_system: bool,
+
+    /// Enter directories whatever their filesystem
+    #[arg(long)]
+    pub no_one_file_system: bool,
+
+    /// Don't list the content of directories deeper than this (0 for no limit)
+    #[arg(long, value_name = "depth")]
+    pub max_depth: Option<u16>,
+
+    /// Max number of listed children per directory: a number, `auto`
+    /// (depending on the terminal height) or `unlimited`
+    #[arg(long, value_name = "count")]
+    pub max_children: Option<String>,
+
+    /// List directories before files, at every level
+    #[arg(long)]
+    pub dirs_first: bool,
+
+    /// Don't group directories before files
+    #[arg(long)]
+    pub no_dirs_first: bool,
+
     /// Show filesystem info on top
     #[arg(long)]
     pub show_root_fs: bool,
@@ -96,10 +129,18 @@ pub struct Args {
     /// Sort by date (only show one level of the tree)
     pub sort_by_date: bool,
 
+    #[arg(long)]
+    /// Sort the children of every directory by date, keeping the tree
+    pub sort_by_date_in_tree: bool,
+
     #[arg(long)]
     /// Sort by size (only show one level of the tree)
     pub sort_by_size: bool,
 
+    #[arg(long)]
+    /// Sort the children of every directory by size, keeping the tree
+    pub sort_by_size_in_tree: bool,
+
     #[arg(long)]
     /// Same as sort-by-type-dirs-first
     pub sort_by_type: bool,
@@ -112,6 +153,11 @@ pub struct Args {
     /// Sort by type, directories last (only show one level of the tree)
     pub sort_by_type_dirs_last: bool,
 
+    /// Never list the files and directories matching this glob
+    /// (can be repeated)
+    #[arg(long, value_name = "GLOB")]
+    pub ignore: Vec<String>,
+
     /// Don't sort
     #[arg(long)]
     pub no_sort: bool,
@@ -140,6 +186,38 @@ pub struct Args {
     #[arg(long, default_value="auto", value_name = "color")]
     pub color: TriBool,
 
+    /// Chars drawing the tree branches: `unicode`, `ascii`, or the
+    /// vertical, tee, corner, horizontal and staged chars (eg `|+`-*`)
+    #[arg(long, value_name = "chars")]
+    pub branch_chars: Option<String>,
+
+    /// Icon theme, `vscode` or `nerdfont` (needs a font with the icons)
+    #[arg(long, value_name = "theme")]
+    pub icon_theme: Option<String>,
+
+    /// Print the tree without style and quit, without interaction
+    /// (done too when broot can't render on a terminal)
+    #[arg(long)]
+    pub no_style: bool,
+
+    /// Print the whole tree, without style, then quit
+    /// (a pattern may be given with --cmd)
+    #[arg(long)]
+    pub print: bool,
+
+    /// Interactively pick a file, print its path and quit
+    /// (exit with status 1 when nothing is picked)
+    #[arg(long)]
+    pub select_only: bool,
+
+    /// Allow picking directories with --select-only
+    #[arg(long, requires = "select_only")]
+    pub dirs_ok: bool,
+
+    /// Format of the printed tree
+    #[arg(long, default_value="text", value_name = "format")]
+    pub out_format: OutFormat,
+
     /// Height (if you don't want to fill the screen or for file export)
     #[arg(long, value_name = "height")]
     pub height: Option<u16>,
@@ -201,6 +279,14 @@ impl TriBool {
     }
 }
 
+/// How the tree is printed when not interactive
+#[derive(ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutFormat {
+    Text,
+    Json,
+}
+
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
 pub enum CliShellInstallState {
     Undefined, // before any install, this is the initial state
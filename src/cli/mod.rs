@@ -16,6 +16,7 @@ use {
         display,
         errors::ProgramError,
         launchable::Launchable,
+        print,
         shell_install::{ShellInstall, ShellInstallState},
         verb::VerbStore,
     },
@@ -25,6 +26,7 @@ use {
         cursor,
         event::{DisableMouseCapture, EnableMouseCapture},
         terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+        tty::IsTty,
         QueueableCommand,
     },
     std::{
@@ -95,9 +97,15 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
         .as_ref()
         .map(|s| s.split(';').map(PathBuf::from).collect());
 
+    // broot renders on stderr. When it's not a terminal, there can't
+    // be any interaction, so we'll just print the tree (a redirected
+    // stdout is fine, it's where paths are printed with :pp)
+    let plain = args.print || args.no_style || !io::stderr().is_tty();
+
     // if we don't run on a specific config file, we check the
-    // configuration
-    if specific_conf.is_none() && install_args.install != Some(false) {
+    // configuration (which may involve asking the user, which we
+    // can't do when stdout is captured for a picked path)
+    if specific_conf.is_none() && install_args.install != Some(false) && !plain && !args.select_only {
         // TODO clean the next few lines when inspect_err is stable
         let res = shell_install.check();
         if let Err(e) = &res {
@@ -150,6 +158,10 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
         return Ok(None);
     }
 
+    if plain {
+        return Ok(Some(print::plain_tree_printer(&context)?));
+    }
+
     let mut w = display::writer();
     let app = App::new(&context)?;
     w.queue(EnterAlternateScreen)?;
@@ -164,7 +176,10 @@ pub fn run() -> Result<Option<Launchable>, ProgramError> {
     w.queue(cursor::Show)?;
     w.queue(LeaveAlternateScreen)?;
     w.flush()?;
-    r
+    match r {
+        Ok(None) if context.launch_args.select_only => Err(ProgramError::NothingSelected),
+        r => r,
+    }
 }
 
 /// wait for user input, return `true` if they didn't answer 'n'
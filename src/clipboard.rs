@@ -0,0 +1,152 @@
+//! Write text to the system clipboard.
+//!
+//! The clipboard tool of the platform is used: wl-copy, xclip or xsel
+//! on Linux and BSD (the first one found for the current display
+//! server), pbcopy on macOS, and clip on Windows.
+//! When broot is compiled with the "clipboard" feature, the
+//! terminal-clipboard crate is tried first.
+//!
+//! In SSH sessions where no tool is found, the text is sent to the
+//! terminal in an OSC 52 escape sequence, which most terminals
+//! put into the clipboard of the local machine.
+
+use {
+    crate::errors::ClipboardError,
+    std::{
+        env,
+        io::Write,
+        process::{Command, Stdio},
+    },
+};
+
+/// A program reading the text to copy on its standard input
+struct Tool {
+    name: &'static str,
+    args: &'static [&'static str],
+    utf16: bool, // whether the text must be given in UTF-16 instead of UTF-8
+}
+
+impl Tool {
+    fn copy(&self, text: &str) -> Result<(), ClipboardError> {
+        // wl-copy, xclip and xsel fork a process serving the clipboard, which
+        // mustn't keep our pipes open
+        let mut child = Command::new(self.name)
+            .args(self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            if self.utf16 {
+                stdin.write_all(&utf16_bytes(text))?;
+            } else {
+                stdin.write_all(text.as_bytes())?;
+            }
+        }
+        let status = child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(ClipboardError::ToolFailed { tool: self.name, status })
+        }
+    }
+}
+
+/// encode the text in UTF-16LE, with a BOM, which is the only way to
+/// have clip read it as unicode instead of in the console's code page
+fn utf16_bytes(text: &str) -> Vec<u8> {
+    std::iter::once(0xFEFF)
+        .chain(text.encode_utf16())
+        .flat_map(u16::to_le_bytes)
+        .collect()
+}
+
+/// the clipboard tools usable in the current environment, in order of preference
+fn available_tools() -> Vec<Tool> {
+    let mut candidates = Vec::new();
+    if cfg!(target_os = "macos") {
+        candidates.push(Tool { name: "pbcopy", args: &[], utf16: false });
+    } else if cfg!(windows) {
+        candidates.push(Tool { name: "clip", args: &[], utf16: true });
+    } else {
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            candidates.push(Tool { name: "wl-copy", args: &[], utf16: false });
+        }
+        if env::var_os("DISPLAY").is_some() {
+            candidates.push(Tool { name: "xclip", args: &["-selection", "clipboard"], utf16: false });
+            candidates.push(Tool { name: "xsel", args: &["--clipboard", "--input"], utf16: false });
+        }
+    }
+    candidates.retain(|tool| which::which(tool.name).is_ok());
+    candidates
+}
+
+fn is_ssh_session() -> bool {
+    env::var_os("SSH_TTY").is_some() || env::var_os("SSH_CONNECTION").is_some()
+}
+
+/// build the OSC 52 sequence setting the clipboard to the text.
+///
+/// In tmux, the sequence is wrapped so that it's passed to the terminal.
+fn osc52_sequence(text: &str, in_tmux: bool) -> String {
+    let osc = format!("\u{1b}]52;c;{}\u{7}", base64::encode(text));
+    if in_tmux {
+        format!("\u{1b}Ptmux;\u{1b}{osc}\u{1b}\\")
+    } else {
+        osc
+    }
+}
+
+/// Put the text in the clipboard.
+///
+/// The writer is the one of the terminal, it's only used for the
+/// OSC 52 fallback.
+pub fn copy<W: Write>(text: &str, w: &mut W) -> Result<(), ClipboardError> {
+    #[cfg(feature = "clipboard")]
+    {
+        match terminal_clipboard::set_string(text) {
+            Ok(()) => {
+                return Ok(());
+            }
+            Err(e) => {
+                warn!("terminal-clipboard failed: {:?}", e);
+            }
+        }
+    }
+    let mut error = None;
+    for tool in available_tools() {
+        match tool.copy(text) {
+            Ok(()) => {
+                return Ok(());
+            }
+            Err(e) => {
+                warn!("clipboard tool {} failed: {}", tool.name, e);
+                error = Some(e);
+            }
+        }
+    }
+    if is_ssh_session() {
+        let in_tmux = env::var_os("TMUX").is_some();
+        w.write_all(osc52_sequence(text, in_tmux).as_bytes())?;
+        w.flush()?;
+        return Ok(());
+    }
+    Err(error.unwrap_or(ClipboardError::NoTool))
+}
+
+#[test]
+fn test_utf16_bytes() {
+    assert_eq!(utf16_bytes("é/a"), vec![0xFF, 0xFE, 0xE9, 0, b'/', 0, b'a', 0]);
+}
+
+#[test]
+fn test_osc52_sequence() {
+    assert_eq!(
+        osc52_sequence("/home/dys/été", false),
+        "\u{1b}]52;c;L2hvbWUvZHlzL8OpdMOp\u{7}",
+    );
+    assert_eq!(
+        osc52_sequence("a", true),
+        "\u{1b}Ptmux;\u{1b}\u{1b}]52;c;YQ==\u{7}\u{1b}\\",
+    );
+}
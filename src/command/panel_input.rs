@@ -274,6 +274,41 @@ impl PanelInput {
         }
     }
 
+    /// When a verb needing arguments is invoked without any, fill the
+    /// input with its invocation and the default values of the arguments
+    /// (eg the parent directory for a `path-from-parent` argument), so
+    /// that the user only has to complete it
+    fn prompt_missing_args(
+        &mut self,
+        parts: &CommandParts,
+        con: &AppContext,
+        sel_info: SelInfo<'_>,
+        app_state: &AppState,
+    ) -> Option<Command> {
+        let invocation = parts.verb_invocation.as_ref()?;
+        if invocation.args.as_ref().map_or(false, |args| !args.trim().is_empty()) {
+            return None;
+        }
+        let verb = con.verb_store.search_sel_info_unique(&invocation.name, sel_info)?;
+        let invocation_parser = verb.invocation_parser.as_ref()?;
+        let no_args = VerbInvocation { args: None, ..invocation.clone() };
+        invocation_parser.check_args(&no_args, &None)?;
+        let exec_builder = ExecutionStringBuilder::without_invocation(
+            sel_info,
+            app_state,
+        );
+        let prompt = VerbInvocation {
+            args: exec_builder
+                .invocation_with_default(&invocation_parser.invocation_pattern)
+                .args,
+            ..no_args
+        };
+        let mut parts = parts.clone();
+        parts.verb_invocation = Some(prompt.clone());
+        self.set_content(&parts.to_string());
+        Some(Command::VerbEdit(prompt))
+    }
+
     fn find_key_verb<'c>(
         &mut self,
         key: KeyEvent,
@@ -324,16 +359,17 @@ impl PanelInput {
                         Command::Click(column, row)
                     }
                 }
+                // the wheel scrolls one line at a time
                 MouseEventKind::ScrollDown => {
                     Command::Internal {
-                        internal: Internal::line_down,
-                        input_invocation: None,
+                        internal: Internal::scroll_down,
+                        input_invocation: Some(VerbInvocation::new("scroll_down", Some("1"), false)),
                     }
                 }
                 MouseEventKind::ScrollUp => {
                     Command::Internal {
-                        internal: Internal::line_up,
-                        input_invocation: None,
+                        internal: Internal::scroll_up,
+                        input_invocation: Some(VerbInvocation::new("scroll_up", Some("1"), false)),
                     }
                 }
                 _ => Command::None,
@@ -357,7 +393,11 @@ impl PanelInput {
         let raw = self.input_field.get_content();
         let parts = CommandParts::from(raw.clone());
 
-        let verb = if keys::is_key_allowed_for_verb(key, mode, raw.is_empty()) {
+        let root_is_selected = matches!(
+            sel_info,
+            SelInfo::One(sel) if sel.path == app_state.root,
+        );
+        let verb = if keys::is_key_allowed_for_verb(key, mode, raw.is_empty(), root_is_selected) {
             self.find_key_verb(
                 key,
                 con,
@@ -397,6 +437,9 @@ impl PanelInput {
         // 'enter': trigger the verb if any on the input. If none, then may be
         // used as trigger of another verb
         if key == key!(enter) && parts.has_not_empty_verb_invocation() {
+            if let Some(command) = self.prompt_missing_args(&parts, con, sel_info, app_state) {
+                return command;
+            }
             return Command::from_parts(parts, true);
         }
 
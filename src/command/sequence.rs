@@ -89,7 +89,7 @@ fn add_commands(
             // we check that the verb exists to avoid running a sequence
             // of actions with some missing
             match con.verb_store.search_prefix(&invocation.name) {
-                PrefixSearchResult::NoMatch => {
+                PrefixSearchResult::NoMatch if invocation.as_line_selection().is_none() => {
                     return Err(ProgramError::UnknownVerb {
                         name: invocation.name.to_string(),
                     });
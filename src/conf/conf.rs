@@ -14,6 +14,7 @@ use {
         },
         skin::SkinEntry,
         syntactic::SyntaxTheme,
+        tree::MaxChildren,
     },
     ahash::AHashMap,
     crokey::crossterm::style::Attribute,
@@ -78,15 +79,30 @@ pub struct Conf {
     #[serde(default, alias="ext-colors")]
     pub ext_colors: AHashMap<String, String>,
 
+    #[serde(alias="use-ls-colors")]
+    pub use_ls_colors: Option<bool>,
+
     #[serde(alias="syntax-theme")]
     pub syntax_theme: Option<SyntaxTheme>,
 
+    #[serde(alias="syntax-highlighting")]
+    pub syntax_highlighting: Option<bool>,
+
     #[serde(alias="true-colors")]
     pub true_colors: Option<bool>,
 
     #[serde(alias="icon-theme")]
     pub icon_theme: Option<String>,
 
+    #[serde(alias="icon-width")]
+    pub icon_width: Option<u8>,
+
+    #[serde(alias="branch-chars")]
+    pub branch_chars: Option<String>,
+
+    #[serde(alias="max-children")]
+    pub max_children: Option<MaxChildren>,
+
     pub modal: Option<bool>,
 
     pub max_panels_count: Option<usize>,
@@ -94,6 +110,12 @@ pub struct Conf {
     #[serde(alias="quit-on-last-cancel")]
     pub quit_on_last_cancel: Option<bool>,
 
+    #[serde(alias="persist-marks")]
+    pub persist_marks: Option<bool>,
+
+    #[serde(alias="focus-link-targets")]
+    pub focus_link_targets: Option<bool>,
+
     pub file_sum_threads_count: Option<usize>,
 
     #[serde(alias="max_staged_count")]
@@ -105,8 +127,23 @@ pub struct Conf {
     #[serde(alias="show-matching-characters-on-path-searches")]
     pub show_matching_characters_on_path_searches: Option<bool>,
 
+    #[serde(alias="natural-sort")]
+    pub natural_sort: Option<bool>,
+
+    #[serde(alias="ignore-globs", default)]
+    pub ignore_globs: Vec<String>,
+
     #[serde(alias="content-search-max-file-size", deserialize_with="file_size::deserialize", default)]
     pub content_search_max_file_size: Option<u64>,
+
+    #[serde(alias="search-time-budget-ms")]
+    pub search_time_budget_ms: Option<u64>,
+
+    #[serde(alias="touch-creates-dirs")]
+    pub touch_creates_dirs: Option<bool>,
+
+    #[serde(alias="edit-new-files")]
+    pub edit_new_files: Option<bool>,
 }
 
 impl Conf {
@@ -172,9 +209,14 @@ impl Conf {
         overwrite!(self, default_flags, conf);
         overwrite!(self, date_time_format, conf);
         overwrite!(self, icon_theme, conf);
+        overwrite!(self, icon_width, conf);
+        overwrite!(self, branch_chars, conf);
+        overwrite!(self, max_children, conf);
         overwrite!(self, syntax_theme, conf);
+        overwrite!(self, syntax_highlighting, conf);
         overwrite!(self, disable_mouse_capture, conf);
         overwrite!(self, capture_mouse, conf);
+        overwrite!(self, use_ls_colors, conf);
         overwrite!(self, true_colors, conf);
         overwrite!(self, show_selection_mark, conf);
         overwrite!(self, cols_order, conf);
@@ -183,11 +225,18 @@ impl Conf {
         overwrite!(self, max_panels_count, conf);
         overwrite!(self, modal, conf);
         overwrite!(self, quit_on_last_cancel, conf);
+        overwrite!(self, persist_marks, conf);
+        overwrite!(self, focus_link_targets, conf);
         overwrite!(self, file_sum_threads_count, conf);
         overwrite!(self, max_staged_count, conf);
         overwrite!(self, show_matching_characters_on_path_searches, conf);
+        overwrite!(self, natural_sort, conf);
         overwrite!(self, content_search_max_file_size, conf);
+        overwrite!(self, search_time_budget_ms, conf);
+        overwrite!(self, touch_creates_dirs, conf);
+        overwrite!(self, edit_new_files, conf);
         self.verbs.append(&mut conf.verbs);
+        self.ignore_globs.append(&mut conf.ignore_globs);
         // the following maps are "additive": we can add entries from several
         // config files and they still make sense
         overwrite_map!(self, special_paths, conf);
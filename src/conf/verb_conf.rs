@@ -50,6 +50,8 @@ pub struct VerbConf {
 
     pub switch_terminal: Option<bool>,
 
+    pub refresh_after: Option<bool>,
+
     #[serde(default)]
     pub panels: Vec<PanelStateType>,
 }
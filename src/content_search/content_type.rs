@@ -0,0 +1,159 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+/// size of the start of the file which is looked at to
+/// guess the type of its content
+pub const SNIFF_SIZE: usize = 4 * 1024;
+
+/// if more than one byte out of this number is a control char
+/// (other than the usual whitespaces), the content is binary
+const MAX_CONTROL_RATIO: usize = 10;
+
+/// The kind of content of a file, guessed from its first bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentType {
+    /// ASCII, UTF-8 or some other 8 bits encoding
+    Text,
+    /// UTF-16, with or without BOM
+    Utf16 { big_endian: bool },
+    Binary,
+}
+
+impl ContentType {
+    /// guess the type of a content from its first bytes
+    /// (at most SNIFF_SIZE are looked at)
+    pub fn of_bytes(bytes: &[u8]) -> Self {
+        let bytes = &bytes[..bytes.len().min(SNIFF_SIZE)];
+        if let Some(big_endian) = utf16_endianness(bytes) {
+            return Self::Utf16 { big_endian };
+        }
+        if bytes.contains(&0) {
+            return Self::Binary;
+        }
+        let control_count = bytes
+            .iter()
+            .filter(|&&b| (b < 32 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B)) || b == 127)
+            .count();
+        if control_count * MAX_CONTROL_RATIO > bytes.len() {
+            Self::Binary
+        } else {
+            Self::Text
+        }
+    }
+    /// guess the type of the content of a file, reading only
+    /// its first bytes
+    pub fn of_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut bytes = Vec::with_capacity(SNIFF_SIZE);
+        File::open(path)?
+            .take(SNIFF_SIZE as u64)
+            .read_to_end(&mut bytes)?;
+        Ok(Self::of_bytes(&bytes))
+    }
+    pub fn is_text(self) -> bool {
+        !matches!(self, Self::Binary)
+    }
+}
+
+/// When the bytes look like UTF-16 text, return whether it's big endian.
+///
+/// Without BOM, the text is assumed to be UTF-16 when it's mostly made
+/// of ASCII chars, i.e. when nearly all the bytes at either even or odd
+/// positions are NUL while the other ones aren't.
+fn utf16_endianness(bytes: &[u8]) -> Option<bool> {
+    let big_endian = match bytes {
+        [0xFF, 0xFE, ..] => false,
+        [0xFE, 0xFF, ..] => true,
+        _ => {
+            let pairs = bytes.len() / 2;
+            if pairs < 2 {
+                return None;
+            }
+            let count_nul = |offset: usize| {
+                bytes.chunks_exact(2).filter(|pair| pair[offset] == 0).count()
+            };
+            let (even_nuls, odd_nuls) = (count_nul(0), count_nul(1));
+            let mostly = |count: usize| count * 10 >= pairs * 9;
+            if mostly(odd_nuls) && even_nuls == 0 {
+                false
+            } else if mostly(even_nuls) && odd_nuls == 0 {
+                true
+            } else {
+                return None;
+            }
+        }
+    };
+    // the bytes must be decodable (the last unit may be cut)
+    let mut decoded = char::decode_utf16(utf16_units(bytes, big_endian)).peekable();
+    while let Some(c) = decoded.next() {
+        match c {
+            Ok(c) if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {
+                return None;
+            }
+            Err(_) if decoded.peek().is_some() => {
+                return None;
+            }
+            _ => {}
+        }
+    }
+    Some(big_endian)
+}
+
+fn utf16_units(bytes: &[u8], big_endian: bool) -> impl Iterator<Item = u16> + '_ {
+    bytes.chunks_exact(2)
+        .map(move |pair| {
+            if big_endian {
+                u16::from_be_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_le_bytes([pair[0], pair[1]])
+            }
+        })
+}
+
+/// Decode UTF-16 bytes, skipping the BOM and replacing
+/// invalid sequences with the replacement char
+pub fn decode_utf16(bytes: &[u8], big_endian: bool) -> String {
+    char::decode_utf16(utf16_units(bytes, big_endian))
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .skip_while(|&c| c == '\u{FEFF}')
+        .collect()
+}
+
+#[test]
+fn test_content_type() {
+    use ContentType::*;
+    assert_eq!(ContentType::of_bytes(b"some text\n\twith tabs\r\n"), Text);
+    assert_eq!(ContentType::of_bytes("été à Noël\n".as_bytes()), Text);
+    assert_eq!(ContentType::of_bytes(b"caf\xe9 latin1\n"), Text);
+    assert_eq!(ContentType::of_bytes(b"\x1b[31mred\x1b[0m log\n"), Text);
+    assert_eq!(ContentType::of_bytes(b"abc\0def"), Binary);
+    assert_eq!(ContentType::of_bytes(b"\x01\x02\x03\x04 some\x05"), Binary);
+    let utf16 = |s: &str, big_endian: bool, bom: bool| {
+        let mut bytes = Vec::new();
+        if bom {
+            bytes.extend(if big_endian { [0xFE, 0xFF] } else { [0xFF, 0xFE] });
+        }
+        for unit in s.encode_utf16() {
+            if big_endian {
+                bytes.extend(unit.to_be_bytes());
+            } else {
+                bytes.extend(unit.to_le_bytes());
+            }
+        }
+        bytes
+    };
+    for big_endian in [false, true] {
+        for bom in [false, true] {
+            let bytes = utf16("Some text,\r\nen été\r\n", big_endian, bom);
+            assert_eq!(ContentType::of_bytes(&bytes), Utf16 { big_endian });
+            assert_eq!(decode_utf16(&bytes, big_endian), "Some text,\r\nen été\r\n");
+        }
+    }
+    // a cut content is still recognized
+    let bytes = utf16("a long enough text", false, true);
+    assert_eq!(ContentType::of_bytes(&bytes[..bytes.len() - 1]), Utf16 { big_endian: false });
+    // numbers stored on 16 bits aren't text
+    assert_eq!(ContentType::of_bytes(&[1, 0, 2, 0, 3, 0, 4, 0]), Binary);
+}
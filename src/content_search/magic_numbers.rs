@@ -6,6 +6,9 @@ use {
 
 pub const MIN_FILE_SIZE: usize = 100;
 
+/// size of the block checked for NUL bytes
+const FIRST_BLOCK_SIZE: usize = 1024;
+
 // those ones are now removed because of the extension filtering
 // static SIGNATURES_2: [[u8;2];2] = [
 //     [ 0x4D, 0x5A ], // exe, dll
@@ -74,6 +77,11 @@ static SIGNATURES_4: Set<[u8; 4]> = phf_set! {
 /// If you feel this list should maybe be changed, contact
 /// me on miaou or raise an issue.
 pub fn is_known_binary(hay: &Mmap) -> bool {
+    // text files don't contain NUL bytes (UTF-16 ones are excluded
+    // too, but they can't be searched with an UTF-8 needle anyway)
+    if hay[..hay.len().min(FIRST_BLOCK_SIZE)].contains(&0) {
+        return true;
+    }
     if hay.len() < MIN_FILE_SIZE {
         return false;
     }
@@ -1,5 +1,6 @@
 
 mod content_match;
+mod content_type;
 mod content_search_result;
 mod magic_numbers;
 mod extensions;
@@ -7,6 +8,7 @@ mod needle;
 
 pub use {
     content_match::ContentMatch,
+    content_type::{decode_utf16, ContentType, SNIFF_SIZE},
     content_search_result::ContentSearchResult,
     needle::Needle,
     std::io::{ BufRead, BufReader},
@@ -68,3 +70,15 @@ pub fn line_count_at_pos<P: AsRef<Path>>(path: P, pos: usize) -> io::Result<usiz
     }
     Err(io::Error::new(io::ErrorKind::UnexpectedEof, "too short".to_string()))
 }
+
+#[test]
+fn test_binary_files_are_not_suitable() {
+    let dir = tempfile::tempdir().unwrap();
+    let text = dir.path().join("notes");
+    std::fs::write(&text, "some text\n".repeat(20)).unwrap();
+    assert!(is_path_suitable(&text, DEFAULT_MAX_FILE_SIZE));
+    assert!(!is_path_suitable(&text, 10)); // too big
+    let binary = dir.path().join("data");
+    std::fs::write(&binary, b"abc\0def").unwrap();
+    assert!(!is_path_suitable(&binary, DEFAULT_MAX_FILE_SIZE));
+}
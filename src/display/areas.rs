@@ -15,6 +15,7 @@ use {
 #[derive(Debug, Clone)]
 pub struct Areas {
     pub state: Area,
+    pub selection: Area,
     pub status: Area,
     pub input: Area,
     pub purpose: Option<Area>,
@@ -22,7 +23,7 @@ pub struct Areas {
     pub nb_pos: usize,  // number of displayed panels
 }
 
-const MINIMAL_PANEL_HEIGHT: u16 = 4;
+pub const MINIMAL_PANEL_HEIGHT: u16 = 5;
 const MINIMAL_PANEL_WIDTH: u16 = 4;
 const MINIMAL_SCREEN_WIDTH: u16 = 8;
 
@@ -45,6 +46,7 @@ impl Areas {
         }
         let mut areas = Areas {
             state: Area::uninitialized(),
+            selection: Area::uninitialized(),
             status: Area::uninitialized(),
             input: Area::uninitialized(),
             purpose: None,
@@ -103,8 +105,10 @@ impl Areas {
                 Slot::Panel(panel_idx) => &mut panels[*panel_idx].areas,
                 Slot::New(areas) => areas,
             };
-            let y = screen_height - 2;
+            let y = screen_height - 3;
             areas.state = Area::new(x, 0, panel_width, y);
+            areas.selection = Area::new(x, y, panel_width, 1);
+            let y = y + 1;
             areas.status = if WIDE_STATUS {
                 Area::new(0, y, screen_width, 1)
             } else {
@@ -0,0 +1,83 @@
+use {
+    crate::errors::ConfError,
+    std::str::FromStr,
+    unicode_width::UnicodeWidthChar,
+};
+
+/// The characters used to draw the branches of the tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BranchChars {
+    pub vertical: char, // on the left of the children of a directory
+    pub tee: char, // before a line having a next sibling
+    pub corner: char, // before the last child of a directory
+    pub horizontal: char, // from the tee or the corner to the name
+    pub staged: char, // replaces a horizontal char when the path is staged
+}
+
+impl BranchChars {
+    pub const UNICODE: Self = Self {
+        vertical: '│',
+        tee: '├',
+        corner: '└',
+        horizontal: '─',
+        staged: '◍',
+    };
+    pub const ASCII: Self = Self {
+        vertical: '|',
+        tee: '+',
+        corner: '`',
+        horizontal: '-',
+        staged: '*',
+    };
+}
+
+impl Default for BranchChars {
+    fn default() -> Self {
+        Self::UNICODE
+    }
+}
+
+impl FromStr for BranchChars {
+    type Err = ConfError;
+    /// parse either the name of a preset (`unicode` or `ascii`) or
+    /// the chars themselves, in order vertical, tee, corner, horizontal
+    /// and, optionally, staged (eg `|+`-*`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unicode" => return Ok(Self::UNICODE),
+            "ascii" => return Ok(Self::ASCII),
+            _ => {}
+        }
+        let chars: Vec<char> = s.chars().collect();
+        if !(4..=5).contains(&chars.len()) {
+            return Err(ConfError::InvalidBranchChars {
+                details: format!("expected 4 or 5 chars in {s:?}"),
+            });
+        }
+        if let Some(c) = chars.iter().find(|c| c.width() != Some(1)) {
+            return Err(ConfError::InvalidBranchChars {
+                details: format!("{c:?} isn't one cell wide"),
+            });
+        }
+        Ok(Self {
+            vertical: chars[0],
+            tee: chars[1],
+            corner: chars[2],
+            horizontal: chars[3],
+            staged: chars.get(4).copied().unwrap_or(chars[3]),
+        })
+    }
+}
+
+#[test]
+fn test_parse_branch_chars() {
+    assert_eq!("ascii".parse::<BranchChars>().unwrap(), BranchChars::ASCII);
+    assert_eq!("│├└─◍".parse::<BranchChars>().unwrap(), BranchChars::UNICODE);
+    assert_eq!(
+        "|+`-".parse::<BranchChars>().unwrap(),
+        BranchChars { staged: '-', ..BranchChars::ASCII },
+    );
+    assert!("|+".parse::<BranchChars>().is_err());
+    assert!("|+`\t".parse::<BranchChars>().is_err());
+    assert!("|+`日".parse::<BranchChars>().is_err());
+}
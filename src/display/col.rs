@@ -12,7 +12,7 @@ use {
 };
 
 // number of columns in enum
-const COLS_COUNT: usize = 10;
+const COLS_COUNT: usize = 11;
 
 /// One of the "columns" of the tree view
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -32,6 +32,9 @@ pub enum Col {
     /// file mode and ownership
     Permission,
 
+    /// number of hard links of files (unix only)
+    HardLinks,
+
     /// last modified date
     Date,
 
@@ -67,6 +70,7 @@ pub static DEFAULT_COLS: Cols = [
     Col::Size,
     Col::Date,
     Col::Permission,
+    Col::HardLinks,
     Col::Count,
     Col::Branch,
     Col::Staged,
@@ -83,6 +87,7 @@ impl FromStr for Col {
             "dev" | "device" | "device-id" => Ok(Self::DeviceId),
             "b" | "branch" => Ok(Self::Branch),
             "p" | "permission" => Ok(Self::Permission),
+            "l" | "links" | "hard-links" => Ok(Self::HardLinks),
             "d" | "date" => Ok(Self::Date),
             "s" | "size" => Ok(Self::Size),
             "c" | "count" => Ok(Self::Count),
@@ -114,6 +119,7 @@ impl Col {
             Col::Size => true,
             Col::Date => true,
             Col::Permission => true,
+            Col::HardLinks => true,
             Col::Count => false,
             Col::Branch => false,
             Col::Staged => false,
@@ -133,8 +139,9 @@ impl Col {
             Col::Size => tree_options.show_sizes,
             Col::Date => tree_options.show_dates,
             Col::Permission => tree_options.show_permissions,
+            Col::HardLinks => tree_options.show_hard_links,
             Col::Count => tree_options.show_counts,
-            Col::Branch => true,
+            Col::Branch => !tree_options.flat,
             //Col::Staged => app_state.map_or(false, |a| !a.stage.is_empty()),
             Col::Staged => false,
             Col::Name => true,
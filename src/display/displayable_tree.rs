@@ -6,7 +6,7 @@ use {
         GitStatusDisplay,
         MatchedString,
         num_format::format_count,
-        SPACE_FILLING, BRANCH_FILLING,
+        SPACE_FILLING,
     },
     crate::{
         app::AppState,
@@ -16,16 +16,19 @@ use {
         pattern::PatternObject,
         skin::{ExtColorMap, StyleMap},
         task_sync::ComputationResult,
-        tree::{Tree, TreeLine, TreeLineType},
+        tree::{COMPACT_DATE_TIME_FORMAT, LineError, Tree, TreeLine, TreeLineType},
     },
-    chrono::{DateTime, Local, LocalResult, TimeZone},
+    chrono::{DateTime, Datelike, Local, LocalResult, TimeZone},
     crokey::crossterm::{
         cursor,
         QueueableCommand,
     },
     file_size,
     git2::Status,
-    std::io::Write,
+    std::{
+        borrow::Cow,
+        io::Write,
+    },
     termimad::{CompoundStyle, ProgressBar},
 };
 
@@ -85,11 +88,31 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
             }
             TreeLineType::BrokenSymLink(_) | TreeLineType::SymLink { .. } => &self.skin.link,
             TreeLineType::Pruning => &self.skin.pruning,
+            TreeLineType::Fifo
+            | TreeLineType::Socket
+            | TreeLineType::BlockDevice
+            | TreeLineType::CharDevice => &self.skin.special_file,
         };
         let mut style = style.clone();
         if let Some(ext_color) = line.extension().and_then(|ext| self.ext_colors.get(ext)) {
             style.set_fg(ext_color);
         }
+        if line.git_ignored {
+            // ignored files are only displayed when the user asked for them,
+            // we make them stand out less than the other ones
+            if let Some(c) = self.skin.git_status_ignored.get_fg() {
+                style.set_fg(c);
+            }
+        }
+        if line.is_new {
+            // lines which appeared in the last refresh
+            if let Some(c) = self.skin.new_entry.get_fg() {
+                style.set_fg(c);
+            }
+            if let Some(c) = self.skin.new_entry.get_bg() {
+                style.set_bg(c);
+            }
+        }
         if selected {
             if let Some(c) = self.skin.selected_line.get_bg() {
                 style.set_bg(c);
@@ -132,50 +155,56 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
         Ok(0)
     }
 
-    fn write_line_selection_mark<W: Write>(
+    /// write the number of hard links of a file, highlighted
+    /// when the file has several ones
+    #[cfg(unix)]
+    fn write_line_hard_links<W: Write>(
         &self,
         cw: &mut CropWriter<W>,
-        style: &CompoundStyle,
+        line: &TreeLine,
         selected: bool,
     ) -> Result<usize, termimad::Error> {
-        Ok(if selected {
-            cw.queue_char(style, '▶')?;
-            0
+        if !line.is_file() {
+            return Ok(3);
+        }
+        let nlink = line.nlink();
+        if nlink > 1 {
+            cond_bg!(style, self, selected, self.skin.hard_links_multiple);
+            cw.queue_g_string(style, format!("{nlink:>3}"))?;
         } else {
-            1
-        })
+            cond_bg!(style, self, selected, self.skin.hard_links);
+            cw.queue_g_string(style, format!("{nlink:>3}"))?;
+        }
+        Ok(0)
     }
 
-    fn write_line_size<W: Write>(
+    fn write_line_selection_mark<W: Write>(
         &self,
         cw: &mut CropWriter<W>,
-        line: &TreeLine,
         style: &CompoundStyle,
-        _selected: bool,
+        selected: bool,
     ) -> Result<usize, termimad::Error> {
-        Ok(if let Some(s) = line.sum {
-            cw.queue_g_string(
-                style,
-                format!("{:>4}", file_size::fit_4(s.to_size())),
-            )?;
-            1
+        Ok(if selected {
+            cw.queue_char(style, '▶')?;
+            0
         } else {
-            5
+            1
         })
     }
 
-    /// only makes sense when there's only one level
-    /// (so in sort mode)
+    /// write the size and a bar showing its part of the
+    /// reference size (the total in sort mode, the greatest of
+    /// the siblings otherwise)
     fn write_line_size_with_bar<W: Write>(
         &self,
         cw: &mut CropWriter<W>,
         line: &TreeLine,
         label_style: &CompoundStyle,
-        total_size: FileSum,
+        reference: FileSum,
         selected: bool,
     ) -> Result<usize, termimad::Error> {
         Ok(if let Some(s) = line.sum {
-            let pb = ProgressBar::new(s.part_of_size(total_size), 10);
+            let pb = ProgressBar::new(s.part_of_size(reference), 10);
             cond_bg!(sparse_style, self, selected, self.skin.sparse);
             cw.queue_g_string(
                 label_style,
@@ -183,7 +212,13 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
             )?;
             cw.queue_char(
                 sparse_style,
-                if s.is_sparse() && line.is_file() { 's' } else { ' ' },
+                if s.is_partial() {
+                    '+'
+                } else if s.is_sparse() && line.is_file() {
+                    's'
+                } else {
+                    ' '
+                },
             )?;
             cw.queue_g_string(label_style, format!("{pb:<10}"))?;
             1
@@ -201,11 +236,16 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
         let (style, char) = if !line.is_selectable() {
             (&self.skin.tree, ' ')
         } else {
+            // changes in the work tree are shown before the staged ones
             match line.git_status.map(|s| s.status) {
                 Some(Status::CURRENT) => (&self.skin.git_status_current, ' '),
-                Some(Status::WT_NEW) => (&self.skin.git_status_new, 'N'),
-                Some(Status::CONFLICTED) => (&self.skin.git_status_conflicted, 'C'),
-                Some(Status::WT_MODIFIED) => (&self.skin.git_status_modified, 'M'),
+                Some(s) if s.contains(Status::CONFLICTED) => (&self.skin.git_status_conflicted, 'C'),
+                Some(s) if s.contains(Status::WT_NEW) => (&self.skin.git_status_new, 'N'),
+                Some(s) if s.contains(Status::WT_MODIFIED) => (&self.skin.git_status_modified, 'M'),
+                Some(s) if s.intersects(
+                    Status::INDEX_NEW | Status::INDEX_MODIFIED
+                    | Status::INDEX_RENAMED | Status::INDEX_TYPECHANGE
+                ) => (&self.skin.git_status_staged, 'S'),
                 Some(Status::IGNORED) => (&self.skin.git_status_ignored, 'I'),
                 None => (&self.skin.tree, ' '),
                 _ => (&self.skin.git_status_other, '?'),
@@ -226,14 +266,32 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
             cond_bg!(date_style, self, selected, self.skin.dates);
             cw.queue_g_string(
                 date_style,
-                date_time
-                    .format(self.tree.options.date_time_format)
-                    .to_string(),
+                self.format_date(date_time),
             )?;
         }
         Ok(1)
     }
 
+    /// format the date according to the date_time_format option.
+    ///
+    /// With the compact format, the displayed precision depends on
+    /// the age of the date, but the width is always the same.
+    fn format_date(&self, date_time: DateTime<Local>) -> String {
+        let format = self.tree.options.date_time_format;
+        if format != COMPACT_DATE_TIME_FORMAT {
+            return date_time.format(format).to_string();
+        }
+        let now = Local::now();
+        let format = if date_time.date_naive() == now.date_naive() {
+            "%R"
+        } else if date_time.year() == now.year() {
+            "%b %d"
+        } else {
+            "%Y-%m-%d"
+        };
+        format!("{:>10}", date_time.format(format))
+    }
+
     fn write_branch<W: Write>(
         &self,
         cw: &mut CropWriter<W>,
@@ -243,30 +301,21 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
         staged: bool,
     ) -> Result<usize, ProgramError> {
         cond_bg!(branch_style, self, selected, self.skin.tree);
+        let chars = &self.tree.options.branch_chars;
+        let mid = if staged { chars.staged } else { chars.horizontal };
         let mut branch = String::new();
         for depth in 0..line.depth {
-            branch.push_str(
-                if line.left_branches[depth as usize] {
-                    if self.tree.has_branch(line_index + 1, depth as usize) {
-                        // TODO: If a theme is on, remove the horizontal lines
-                        if depth == line.depth - 1 {
-                            if staged {
-                                "├◍─"
-                            } else {
-                                "├──"
-                            }
-                        } else {
-                            "│  "
-                        }
-                    } else if staged {
-                        "└◍─"
-                    } else {
-                        "└──"
-                    }
-                } else {
-                    "   "
-                },
-            );
+            let cells = if !line.left_branches[depth as usize] {
+                [' ', ' ', ' ']
+            } else if !self.tree.has_branch(line_index + 1, depth as usize) {
+                [chars.corner, mid, chars.horizontal]
+            } else if depth == line.depth - 1 {
+                // TODO: If a theme is on, remove the horizontal lines
+                [chars.tee, mid, chars.horizontal]
+            } else {
+                [chars.vertical, ' ', ' ']
+            };
+            branch.extend(cells);
         }
         if !branch.is_empty() {
             cw.queue_g_string(branch_style, branch)?;
@@ -290,6 +339,7 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
     }
 
     /// write the name or subpath, depending on the pattern_object
+    /// and on whether the tree is displayed flat
     fn write_line_label<W: Write>(
         &self,
         cw: &mut CropWriter<W>,
@@ -300,41 +350,67 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
     ) -> Result<usize, ProgramError> {
         cond_bg!(char_match_style, self, selected, self.skin.char_match);
         if let Some(icon) = line.icon {
+            // icons take 3 cells with their spaces, whether the terminal
+            // displays them on one or two cells
             cw.queue_char(style, icon)?;
-            cw.queue_char(style, ' ')?;
-            cw.queue_char(style, ' ')?;
+            let icon_width = self.tree.options.icon_width;
+            cw.allowed = cw.allowed.saturating_sub(icon_width - 1);
+            for _ in icon_width..3 {
+                cw.queue_char(style, ' ')?;
+            }
         }
-        if pattern_object.subpath {
-            if self.tree.options.show_matching_characters_on_path_searches && line.unlisted == 0 {
-                let name_match = self.tree.options.pattern.pattern
-                    .search_string(&line.subpath);
-                let mut path_ms = MatchedString::new(
-                    name_match,
-                    &line.subpath,
-                    style,
-                    char_match_style,
-                );
-                let name_ms = path_ms.split_on_last('/');
-                cond_bg!(parent_style, self, selected, self.skin.parent);
-                if let Some(name_ms) = name_ms {
-                    path_ms.base_style = parent_style;
-                    let allowed = cw.allowed - 2.min(cw.allowed);
-                    let tail_len = name_ms.width();
-                    if tail_len < allowed {
-                        let path_width = path_ms.width();
-                        if path_width > 1 && path_width + tail_len > allowed + 1 {
-                            cw.queue_char(style, '…')?;
-                            path_ms.cut_left_to_fit(allowed - tail_len - 1);
-                        }
-                        path_ms.queue_on(cw)?;
+        let flat = self.tree.options.flat;
+        let show_path = flat || (
+            pattern_object.subpath
+            && self.tree.options.show_matching_characters_on_path_searches
+            && line.unlisted == 0
+        );
+        if show_path {
+            let label = if flat {
+                Cow::Owned(line.flat_label())
+            } else {
+                Cow::Borrowed(&line.subpath)
+            };
+            let pattern = &self.tree.options.pattern.pattern;
+            let name_match = if pattern_object.subpath {
+                pattern.search_string(&label)
+            } else {
+                // the match is on the name, which ends the label
+                pattern.search_string(&line.name).map(|mut name_match| {
+                    let offset = label.chars().count() - line.name.chars().count();
+                    for pos in name_match.pos.iter_mut() {
+                        *pos += offset;
+                    }
+                    name_match
+                })
+            };
+            let mut path_ms = MatchedString::new(
+                name_match,
+                &label,
+                style,
+                char_match_style,
+            );
+            let name_ms = path_ms.split_on_last('/');
+            cond_bg!(parent_style, self, selected, self.skin.parent);
+            if let Some(name_ms) = name_ms {
+                path_ms.base_style = parent_style;
+                let allowed = cw.allowed - 2.min(cw.allowed);
+                let tail_len = name_ms.width();
+                if tail_len < allowed {
+                    let path_width = path_ms.width();
+                    if path_width > 1 && path_width + tail_len > allowed + 1 {
+                        cw.queue_char(style, '…')?;
+                        path_ms.cut_left_to_fit(allowed - tail_len - 1);
                     }
-                    name_ms.queue_on(cw)?;
-                } else {
                     path_ms.queue_on(cw)?;
                 }
+                name_ms.queue_elided_on(cw, style)?;
             } else {
-                cw.queue_str(style, &line.name)?;
+                path_ms.queue_elided_on(cw, style)?;
             }
+        } else if pattern_object.subpath {
+            MatchedString::new(None, &line.name, style, style)
+                .queue_elided_on(cw, style)?;
         } else {
             let name_match = self.tree.options.pattern.pattern
                 .search_string(&line.name);
@@ -344,13 +420,23 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
                 style,
                 char_match_style,
             );
-            matched_string.queue_on(cw)?;
+            matched_string.queue_elided_on(cw, style)?;
         }
         match &line.line_type {
             TreeLineType::Dir => {
                 if line.unlisted > 0 {
                     cw.queue_str(style, " …")?;
                 }
+                if line.hidden > 0 && self.tree.options.pattern.is_none() {
+                    cond_bg!(hidden_style, self, selected, self.skin.pruning);
+                    cw.queue_g_string(hidden_style, format!(" ({} hidden)", line.hidden))?;
+                }
+                #[cfg(unix)]
+                if self.tree.options.one_file_system
+                    && line.device_id() != self.tree.lines[0].device_id()
+                {
+                    cw.queue_str(style, " (mount point)")?;
+                }
             }
             TreeLineType::BrokenSymLink(direct_path) => {
                 cw.queue_str(style, " -> ")?;
@@ -370,8 +456,16 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
                 };
                 cond_bg!(target_style, self, selected, target_style);
                 cw.queue_str(target_style, direct_target)?;
+                if line.error == Some(LineError::LinkLoop) {
+                    cond_bg!(error_style, self, selected, self.skin.file_error);
+                    cw.queue_str(error_style, " (loop)")?;
+                }
+            }
+            line_type => {
+                if let Some(description) = line_type.special_description() {
+                    cw.queue_g_string(style, format!(" ({description})"))?;
+                }
             }
-            _ => {}
         }
         Ok(1)
     }
@@ -464,6 +558,11 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
 
         let tree = self.tree;
         let total_size = tree.total_sum();
+        let max_sums_by_parent = if tree.options.needs_sizes() {
+            tree.max_sums_by_parent()
+        } else {
+            Default::default()
+        };
         let scrollbar = if self.in_app {
             termimad::compute_scrollbar(
                 tree.scroll,
@@ -505,11 +604,18 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
         // we compute the length of the dates, depending on the format
         let date_len = if tree.options.show_dates {
             let date_time: DateTime<Local> = Local::now();
-            date_time.format(tree.options.date_time_format).to_string().len()
+            self.format_date(date_time).len()
         } else {
             0 // we don't care
         };
 
+        // the width of the line numbers, if shown
+        let line_number_len = if tree.options.show_line_numbers {
+            (tree.lines.len() - 1).to_string().len()
+        } else {
+            0
+        };
+
         for y in 1..self.area.height {
             if self.in_app {
                 f.queue(cursor::MoveTo(self.area.left, y + self.area.top))?;
@@ -533,6 +639,10 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
                 } else {
                     &self.skin.default
                 };
+                if line_number_len > 0 {
+                    cond_bg!(number_style, self, selected, self.skin.tree);
+                    cw.queue_g_string(number_style, format!("{line_index:>line_number_len$} "))?;
+                }
                 if visible_cols[0].needs_left_margin() {
                     cw.queue_char(space_style, ' ')?;
                 }
@@ -562,6 +672,14 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
                             self.write_line_device_id(cw, line, selected)?
                         }
 
+                        Col::HardLinks => {
+                            #[cfg(not(unix))]
+                            { 0 }
+
+                            #[cfg(unix)]
+                            self.write_line_hard_links(cw, line, selected)?
+                        }
+
                         Col::Permission => {
                             #[cfg(any(target_family = "windows", target_os = "android"))]
                             { 0 }
@@ -579,12 +697,16 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
                         }
 
                         Col::Size => {
-                            if tree.options.sort.prevent_deep_display() {
-                                // as soon as there's only one level displayed we can show the size bars
-                                self.write_line_size_with_bar(cw, line, &label_style, total_size, selected)?
+                            let reference = if tree.options.sort.prevent_deep_display() {
+                                // there's only one level displayed, we compare with the total
+                                total_size
                             } else {
-                                self.write_line_size(cw, line, &label_style, selected)?
-                            }
+                                // we compare with the biggest of the siblings
+                                line.parent_bid
+                                    .and_then(|bid| max_sums_by_parent.get(&bid).copied())
+                                    .unwrap_or_else(FileSum::zero)
+                            };
+                            self.write_line_size_with_bar(cw, line, &label_style, reference, selected)?
                         }
 
                         Col::Count => {
@@ -604,7 +726,9 @@ impl<'a, 's, 't> DisplayableTree<'a, 's, 't> {
                     // void: intercol & replacing missing cells
                     if in_branch && void_len > 2 {
                         cond_bg!(void_style, self, selected, self.skin.tree);
-                        cw.repeat(void_style, &BRANCH_FILLING, void_len)?;
+                        let horizontal = tree.options.branch_chars.horizontal;
+                        let filling: String = std::iter::repeat(horizontal).take(void_len).collect();
+                        cw.queue_str(void_style, &filling)?;
                     } else {
                         cond_bg!(void_style, self, selected, self.skin.default);
                         cw.repeat(void_style, &SPACE_FILLING, void_len)?;
@@ -14,9 +14,22 @@ pub struct GitStatusDisplay<'a, 's> {
     show_branch: bool,
     show_wide: bool,
     show_stats: bool,
+    show_counts: bool,
     pub width: usize,
 }
 
+/// width of the counts of changed and untracked files, eg " 3M 1N"
+fn counts_width(status: &TreeGitStatus) -> usize {
+    let mut width = 0;
+    if status.modified_count > 0 {
+        width += status.modified_count.to_string().len() + 2;
+    }
+    if status.untracked_count > 0 {
+        width += status.untracked_count.to_string().len() + 2;
+    }
+    width
+}
+
 impl<'a, 's> GitStatusDisplay<'a, 's> {
     pub fn from(status: &'a TreeGitStatus, skin: &'s StyleMap, available_width: usize) -> Self {
         let mut show_branch = false;
@@ -35,6 +48,12 @@ impl<'a, 's> GitStatusDisplay<'a, 's> {
             width += stats_width;
             show_stats = true;
         }
+        let mut show_counts = false;
+        let counts_width = counts_width(status);
+        if counts_width > 0 && width + counts_width < available_width {
+            width += counts_width;
+            show_counts = true;
+        }
         let show_wide = width + 3 < available_width;
         if show_wide {
             width += 3; // difference between compact and wide format widths
@@ -45,6 +64,7 @@ impl<'a, 's> GitStatusDisplay<'a, 's> {
             show_branch,
             show_wide,
             show_stats,
+            show_counts,
             width,
         }
     }
@@ -75,6 +95,16 @@ impl<'a, 's> GitStatusDisplay<'a, 's> {
             cond_bg!(deletions_style, self, selected, self.skin.git_deletions);
             cw.queue_g_string(deletions_style, format!("-{}", self.status.deletions))?;
         }
+        if self.show_counts {
+            if self.status.modified_count > 0 {
+                cond_bg!(modified_style, self, selected, self.skin.git_status_modified);
+                cw.queue_g_string(modified_style, format!(" {}M", self.status.modified_count))?;
+            }
+            if self.status.untracked_count > 0 {
+                cond_bg!(new_style, self, selected, self.skin.git_status_new);
+                cw.queue_g_string(new_style, format!(" {}N", self.status.untracked_count))?;
+            }
+        }
         Ok(())
     }
 }
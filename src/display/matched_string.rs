@@ -94,6 +94,64 @@ impl<'a, 'w> MatchedString<'a> {
         }
         removed_char_count
     }
+    /// Cut the middle of the string so that, with an ellipsis between
+    /// the head and the tail, it fits the given width. The tail is
+    /// the extension when it's short enough (eg `very-long-na….tar.gz`).
+    ///
+    /// Return the tail, or None when there's no need or no room to cut.
+    /// Matched chars falling in the removed part are dropped.
+    pub fn elide_middle(&mut self, max_width: usize) -> Option<Self> {
+        if self.display_width.is_some() || max_width < 2 || self.width() <= max_width {
+            return None;
+        }
+        let tail_start = self.string
+            .char_indices()
+            .skip(1) // a leading dot doesn't start an extension
+            .filter(|&(_, c)| c == '.')
+            .map(|(idx, _)| idx)
+            .find(|&idx| self.string[idx..].width() <= max_width / 2)
+            .unwrap_or(self.string.len());
+        let tail = &self.string[tail_start..];
+        let (head_end, _) = StrFit::count_fitting(
+            &self.string[..tail_start],
+            max_width - 1 - tail.width(),
+        );
+        let head_chars_count = self.string[..head_end].chars().count();
+        let tail_name_match = self.name_match.as_mut().map(|nm| {
+            let tail_match = nm.cut_after(self.string[..tail_start].chars().count());
+            nm.cut_after(head_chars_count);
+            tail_match
+        });
+        self.string = &self.string[..head_end];
+        Some(MatchedString {
+            name_match: tail_name_match,
+            string: tail,
+            base_style: self.base_style,
+            match_style: self.match_style,
+            display_width: None,
+            align: self.align,
+        })
+    }
+    /// Write the string, with an ellipsis in the middle if it
+    /// doesn't fit the remaining width of the writer
+    pub fn queue_elided_on<W>(
+        mut self,
+        cw: &mut CropWriter<'w, W>,
+        ellipsis_style: &CompoundStyle,
+    ) -> Result<(), termimad::Error>
+    where
+        W: std::io::Write,
+    {
+        if self.width() > cw.allowed {
+            // single chars are only written when they leave a cell free
+            if let Some(tail) = self.elide_middle(cw.allowed.saturating_sub(1)) {
+                self.queue_on(cw)?;
+                cw.queue_char(ellipsis_style, '…')?;
+                return tail.queue_on(cw);
+            }
+        }
+        self.queue_on(cw)
+    }
     pub fn queue_on<W>(&self, cw: &mut CropWriter<'w, W>) -> Result<(), termimad::Error>
     where
         W: std::io::Write,
@@ -156,3 +214,32 @@ impl<'a, 'w> MatchedString<'a> {
         Ok(())
     }
 }
+
+#[test]
+fn test_elide_middle() {
+    use smallvec::smallvec;
+    let style = CompoundStyle::default();
+    let elide = |s, pos: Vec<usize>, max_width| {
+        let name_match = NameMatch { score: 1, pos: pos.into_iter().collect() };
+        let mut ms = MatchedString::new(Some(name_match), s, &style, &style);
+        let tail = ms.elide_middle(max_width);
+        (ms.string, ms.name_match.unwrap().pos, tail.map(|t| (t.string, t.name_match.unwrap().pos)))
+    };
+    // the string fits
+    assert_eq!(elide("short.rs", vec![0], 8), ("short.rs", smallvec![0], None));
+    // the extension is kept, matches in the cut part are dropped
+    assert_eq!(
+        elide("very-long-name.tar.gz", vec![0, 12, 15, 19], 20),
+        ("very-long-na", smallvec![0], Some((".tar.gz", smallvec![1, 5]))),
+    );
+    // no extension short enough to be kept
+    assert_eq!(
+        elide("a-very-long-name-without-extension", vec![2], 10),
+        ("a-very-lo", smallvec![2], Some(("", smallvec![]))),
+    );
+    // widths are counted in cells, not bytes or chars
+    assert_eq!(
+        elide("日本語のファイル名.txt", vec![], 12),
+        ("日本語", smallvec![], Some((".txt", smallvec![]))),
+    );
+}
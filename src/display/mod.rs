@@ -22,6 +22,7 @@ macro_rules! cond_bg {
 }
 
 mod areas;
+mod branch_chars;
 mod cell_size;
 mod col;
 mod displayable_tree;
@@ -31,13 +32,15 @@ mod luma;
 mod matched_string;
 mod num_format;
 mod screen;
+pub mod selection_line;
 pub mod status_line;
 
 #[cfg(not(any(target_family="windows",target_os="android")))]
 mod permissions;
 
 pub use {
-    areas::Areas,
+    areas::{Areas, MINIMAL_PANEL_HEIGHT},
+    branch_chars::BranchChars,
     col::*,
     cond_bg,
     displayable_tree::DisplayableTree,
@@ -39,6 +39,8 @@ impl<'s> PermWriter<'s> {
         Self::new(skin, max_user_len, max_group_len)
     }
 
+    /// write the mode in the rwx form, with s/S for setuid and setgid,
+    /// and t/T for the sticky bit (uppercase when not executable)
     fn write_mode<W: Write>(
         &self,
         cw: &mut CropWriter<W>,
@@ -60,10 +62,11 @@ impl<'s> PermWriter<'s> {
         } else {
             cw.queue_char(n_style, '_')?;
         }
-        if mode.has(USER_EXEC) {
-            cw.queue_char(x_style, 'x')?;
-        } else {
-            cw.queue_char(n_style, '_')?;
+        match (mode.has(USER_EXEC), mode.has_extra(SETUID)) {
+            (true, false) => cw.queue_char(x_style, 'x')?,
+            (true, true) => cw.queue_char(x_style, 's')?,
+            (false, true) => cw.queue_char(n_style, 'S')?,
+            (false, false) => cw.queue_char(n_style, '_')?,
         }
 
         if mode.has(GROUP_READ) {
@@ -76,10 +79,11 @@ impl<'s> PermWriter<'s> {
         } else {
             cw.queue_char(n_style, '_')?;
         }
-        if mode.has(GROUP_EXEC) {
-            cw.queue_char(x_style, 'x')?;
-        } else {
-            cw.queue_char(n_style, '_')?;
+        match (mode.has(GROUP_EXEC), mode.has_extra(SETGID)) {
+            (true, false) => cw.queue_char(x_style, 'x')?,
+            (true, true) => cw.queue_char(x_style, 's')?,
+            (false, true) => cw.queue_char(n_style, 'S')?,
+            (false, false) => cw.queue_char(n_style, '_')?,
         }
 
         if mode.has(OTHERS_READ) {
@@ -92,10 +96,11 @@ impl<'s> PermWriter<'s> {
         } else {
             cw.queue_char(n_style, '_')?;
         }
-        if mode.has(OTHERS_EXEC) {
-            cw.queue_char(x_style, 'x')?;
-        } else {
-            cw.queue_char(n_style, '_')?;
+        match (mode.has(OTHERS_EXEC), mode.has_extra(STICKY)) {
+            (true, false) => cw.queue_char(x_style, 'x')?,
+            (true, true) => cw.queue_char(x_style, 't')?,
+            (false, true) => cw.queue_char(n_style, 'T')?,
+            (false, false) => cw.queue_char(n_style, '_')?,
         }
 
         Ok(())
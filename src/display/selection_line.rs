@@ -0,0 +1,88 @@
+use {
+    super::{Screen, W},
+    crate::{
+        errors::ProgramError,
+        skin::PanelSkin,
+        tree::TreeStats,
+    },
+    std::path::Path,
+    termimad::{Area, StrFit},
+    unicode_width::UnicodeWidthStr,
+};
+
+/// write the line showing the selected path and, when the panel
+/// displays a tree, the stats of this tree
+pub fn write(
+    w: &mut W,
+    path: Option<&Path>,
+    stats: Option<TreeStats>,
+    area: &Area,
+    panel_skin: &PanelSkin,
+    screen: Screen,
+) -> Result<(), ProgramError> {
+    let width = area.width as usize;
+    let stats = stats.map(|stats| format!(" {} ", stats_label(stats)));
+    let stats = stats.filter(|s| s.width() * 2 < width); // the path comes first
+    let stats_width = stats.as_ref().map_or(0, |s| s.width());
+    let path_width = width - stats_width;
+    let path = path
+        .map(|path| shorten_path(&path.to_string_lossy(), path_width.saturating_sub(1)))
+        .unwrap_or_default();
+    screen.goto(w, area.left, area.top)?;
+    let style = &panel_skin.styles.default;
+    style.queue_str(w, " ")?;
+    style.queue_str(w, &path)?;
+    let padding = path_width.saturating_sub(1 + path.width());
+    style.queue_str(w, " ".repeat(padding))?;
+    if let Some(stats) = stats {
+        let style = &panel_skin.styles.count;
+        style.queue_str(w, &stats)?;
+    }
+    Ok(())
+}
+
+fn stats_label(stats: TreeStats) -> String {
+    let mut label = format!("{} lines", stats.lines_count);
+    if stats.truncated_dirs_count > 0 {
+        label.push_str(&format!(", {} truncated", stats.truncated_dirs_count));
+    }
+    if stats.errors_count > 0 {
+        label.push_str(&format!(", {} errors", stats.errors_count));
+    }
+    label
+}
+
+/// shorten the path to the given width, if needed, by replacing
+/// a part of its middle with an ellipsis, keeping the file name
+pub fn shorten_path(path: &str, max_width: usize) -> String {
+    if path.width() <= max_width {
+        return path.to_string();
+    }
+    let name_start = path.rfind('/').unwrap_or(0);
+    let name = &path[name_start..];
+    if name.width() + 1 > max_width {
+        // there's not even room for the name, we keep its end
+        let name_chars: Vec<char> = name.chars().collect();
+        let mut tail = String::new();
+        let mut tail_width = 0;
+        for &c in name_chars.iter().rev() {
+            let cw = c.to_string().width();
+            if tail_width + cw + 1 > max_width {
+                break;
+            }
+            tail.insert(0, c);
+            tail_width += cw;
+        }
+        return format!("…{tail}");
+    }
+    let (head_end, _) = StrFit::count_fitting(&path[..name_start], max_width - 1 - name.width());
+    format!("{}…{}", &path[..head_end], name)
+}
+
+#[test]
+fn test_shorten_path() {
+    assert_eq!(shorten_path("/home/dys/dev/broot", 30), "/home/dys/dev/broot");
+    assert_eq!(shorten_path("/home/dys/dev/broot", 15), "/home/dy…/broot");
+    assert_eq!(shorten_path("/home/dys/dev/broot", 7), "…/broot");
+    assert_eq!(shorten_path("/home/dys/dev/broot", 4), "…oot");
+}
@@ -12,6 +12,7 @@ custom_error! {pub ProgramError
     ArgParse {bad: String, valid: String} = "{bad:?} can't be parsed (valid values: {valid:?})",
     Conf {source: ConfError} = "Bad configuration: {source}",
     ConfFile {path:String, details: ConfError} = "Bad configuration file {path:?} : {details}",
+    FileTransfer {details: String} = "{details}",
     ImageError {source: ImageError } = "{source}",
     InternalError {details: String} = "Internal error: {details}", // should not happen
     InvalidGlobError {pattern: String} = "Invalid glob: {pattern}",
@@ -19,7 +20,10 @@ custom_error! {pub ProgramError
     LaunchError {program: String, source: io::Error} = "Unable to launch {program}: {source}",
     Lfs {details: String} = "Failed to fetch mounts: {details}",
     NetError {source: NetError} = "{source}",
+    NothingSelected = "Nothing selected",
     OpenError { source: opener::OpenError } = "Open error: {source}",
+    Pattern {source: PatternError} = "{source}",
+    ProgramFailed {program: String, status: String} = "{program} failed ({status})",
     ShelInstall { source: ShellInstallError } = "{source}",
     Svg {source: SvgError} = "SVG error: {source}",
     SyntectCrashed { details: String } = "Syntect crashed on {details:?}",
@@ -83,10 +87,12 @@ custom_error! {pub ConfError
     ReservedKey {key: String}                       = "reserved key: {key}",
     UnexpectedInternalArg {invocation: String}      = "unexpected argument for internal: {invocation}",
     InvalidCols {details: String}                   = "invalid cols definition: {details}",
+    InvalidBranchChars {details: String}            = "invalid branch chars: {details}",
     InvalidSkin {source: InvalidSkinError}          = "invalid skin: {source}",
     InvalidThreadsCount { count: usize }            = "invalid threads count: {count}",
     InvalidDefaultFlags { flags: String }           = "invalid default flags: {flags:?}",
     InvalidSyntaxTheme { name: String }             = "invalid syntax theme: {name:?}",
+    InvalidMaxChildren { raw: String }              = "invalid max children: {raw:?} (expected a number, \"auto\" or \"unlimited\")",
 }
 
 // error which can be raised when parsing a pattern the user typed
@@ -96,6 +102,7 @@ custom_error! {pub PatternError
         format!("Invalid Regular Expression: {}", source.to_string().lines().last().unwrap_or(""))
     },
     UnknownRegexFlag {bad: char} = "Unknown regular expression flag: {bad:?}",
+    UnknownFuzzyFlag {bad: char} = "Unknown fuzzy pattern flag: {bad:?}",
 }
 
 custom_error! {pub InvalidSkinError
@@ -106,6 +113,19 @@ custom_error! {pub InvalidSkinError
     InvalidStyleToken {source: termimad::ParseStyleTokenError} = "{source}",
 }
 
+custom_error! {pub ArchiveError
+    Io {source: io::Error} = "{source}",
+    Corrupt {details: String} = "corrupt archive: {details}",
+    Unsupported {details: String} = "unsupported archive: {details}",
+    EntryNotFound {path: String} = "no {path} in the archive",
+}
+
+custom_error! {pub ClipboardError
+    Io {source: io::Error} = "Clipboard error: {source}",
+    ToolFailed {tool: &'static str, status: std::process::ExitStatus} = "{tool} failed ({status})",
+    NoTool = "No clipboard tool found: install wl-clipboard (Wayland), xclip or xsel (X11)",
+}
+
 custom_error! {pub NetError
     SocketNotAvailable { path : String } = "Can't open socket: {path} already exists - consider removing it",
     Io {source: io::Error}               = "error on the socket: {source}",
@@ -12,6 +12,7 @@ use {
     ahash::AHashMap,
     once_cell::sync::Lazy,
     std::{
+        fs,
         ops::AddAssign,
         path::{Path, PathBuf},
         sync::Mutex,
@@ -20,7 +21,12 @@ use {
 
 pub const DEFAULT_THREAD_COUNT: usize = 5;
 
-static SUM_CACHE: Lazy<Mutex<AHashMap<PathBuf, FileSum>>> = Lazy::new(|| {
+/// The path of a summed directory and the device the sum
+/// was restricted to, if any
+type SumCacheKey = (PathBuf, Option<u64>);
+
+/// Sums are cached by path and by the device they were restricted to, if any
+static SUM_CACHE: Lazy<Mutex<AHashMap<SumCacheKey, FileSum>>> = Lazy::new(|| {
     Mutex::new(AHashMap::default())
 });
 
@@ -35,6 +41,8 @@ pub struct FileSum {
     count: usize,   // number of files
     modified: u32,  // seconds from Epoch to last modification, or 0 if there was an error
     sparse: bool,   // only for non directories: tells whether the file is sparse
+    partial: bool,  // only for directories: tells whether some descendants couldn't be read
+    hard_linked: bool, // whether some counted files have several hard links
 }
 
 impl FileSum {
@@ -44,7 +52,7 @@ impl FileSum {
         count: usize,
         modified: u32,
     ) -> Self {
-        Self { real_size, count, modified, sparse }
+        Self { real_size, count, modified, sparse, partial: false, hard_linked: false }
     }
 
     pub fn zero() -> Self {
@@ -55,27 +63,56 @@ impl FileSum {
         self.count += 1;
     }
 
+    /// note that some content couldn't be read, so that the real
+    /// numbers are probably greater
+    pub fn mark_partial(&mut self) {
+        self.partial = true;
+    }
+
+    /// note that a file with several hard links was counted, so that
+    /// this sum can't be simply added to the one of another directory
+    /// without risking to count it twice
+    pub fn mark_hard_linked(&mut self) {
+        self.hard_linked = true;
+    }
+
     /// return the sum of the given file, which is assumed
     /// to be a normal file (ie not a directory)
     pub fn from_file(path: &Path) -> Self {
         sum_computation::compute_file_sum(path)
     }
 
+    /// return the sum of a file, which is assumed to be a normal
+    /// file, from its already read metadata
+    pub fn from_metadata(md: &fs::Metadata) -> Self {
+        sum_computation::compute_metadata_sum(md)
+    }
+
     /// Return the sum of the directory, either by computing it of by
     ///  fetching it from cache.
     /// If the lifetime expires before complete computation, None is returned.
-    pub fn from_dir(path: &Path, dam: &Dam, con: &AppContext) -> Option<Self> {
+    /// When a device is given, directories on other devices aren't counted.
+    pub fn from_dir(
+        path: &Path,
+        dev: Option<u64>,
+        dam: &Dam,
+        con: &AppContext,
+    ) -> Option<Self> {
         let mut sum_cache = SUM_CACHE.lock().unwrap();
-        match sum_cache.get(path) {
-            Some(sum) => Some(*sum),
+        let key = (PathBuf::from(path), dev);
+        match sum_cache.get(&key) {
+            Some(sum) => {
+                debug!("sum of {:?} taken from cache", path);
+                Some(*sum)
+            }
             None => {
                 let sum = time!(
                     "sum computation",
                     path,
-                    sum_computation::compute_dir_sum(path, &mut sum_cache, dam, con),
+                    sum_computation::compute_dir_sum(path, dev, &mut sum_cache, dam, con),
                 );
                 if let Some(sum) = sum {
-                    sum_cache.insert(PathBuf::from(path), sum);
+                    sum_cache.insert(key, sum);
                 }
                 sum
             }
@@ -115,17 +152,100 @@ impl FileSum {
     pub fn is_sparse(self) -> bool {
         self.sparse
     }
+    /// tell whether some directories couldn't be read during computation
+    /// (the real size and count are probably greater)
+    pub fn is_partial(self) -> bool {
+        self.partial
+    }
+    pub fn is_hard_linked(self) -> bool {
+        self.hard_linked
+    }
 }
 
 impl AddAssign for FileSum {
     #[allow(clippy::suspicious_op_assign_impl)]
     fn add_assign(&mut self, other: Self) {
-        *self = Self::new(
-            self.real_size + other.real_size,
-            self.sparse | other.sparse,
-            self.count + other.count,
-            self.modified.max(other.modified),
-        );
+        *self = Self {
+            real_size: self.real_size + other.real_size,
+            sparse: self.sparse | other.sparse,
+            count: self.count + other.count,
+            modified: self.modified.max(other.modified),
+            partial: self.partial | other.partial,
+            hard_linked: self.hard_linked | other.hard_linked,
+        };
     }
 }
 
+
+#[cfg(unix)]
+#[test]
+fn test_hard_links_counted_once() {
+    use crate::tree::test_util::app_context;
+    let dir = tempfile::tempdir().unwrap();
+    let sub = dir.path().join("sub");
+    fs::create_dir(&sub).unwrap();
+    fs::write(dir.path().join("a"), vec![1u8; 100_000]).unwrap();
+    fs::hard_link(dir.path().join("a"), sub.join("b")).unwrap();
+    let con = app_context(dir.path());
+    let dam = Dam::unlimited();
+    let file_sum = FileSum::from_file(&dir.path().join("a"));
+    // the sub directory is computed first, so that its sum is in cache
+    let sub_sum = FileSum::from_dir(&sub, None, &dam, &con).unwrap();
+    assert!(sub_sum.is_hard_linked());
+    assert!(sub_sum.to_size() >= file_sum.to_size());
+    let root_sum = FileSum::from_dir(dir.path(), None, &dam, &con).unwrap();
+    assert!(root_sum.to_size() < 2 * file_sum.to_size());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_other_devices_not_summed() {
+    use {
+        crate::tree::test_util::app_context,
+        std::os::unix::fs::MetadataExt,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let sub = dir.path().join("sub");
+    fs::create_dir(&sub).unwrap();
+    fs::write(sub.join("a"), vec![1u8; 100_000]).unwrap();
+    let con = app_context(dir.path());
+    let dam = Dam::unlimited();
+    let dev = fs::metadata(dir.path()).unwrap().dev();
+    let same_dev_sum = FileSum::from_dir(dir.path(), Some(dev), &dam, &con).unwrap();
+    assert_eq!(same_dev_sum.to_count(), 3);
+    // pretending the root is on another device, the sub directory isn't entered
+    let other_dev_sum = FileSum::from_dir(dir.path(), Some(dev + 1), &dam, &con).unwrap();
+    assert_eq!(other_dev_sum.to_count(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unreadable_dirs_make_partial_sums() {
+    use {
+        crate::tree::test_util::app_context,
+        std::os::unix::fs::PermissionsExt,
+    };
+    if unsafe { libc::geteuid() } == 0 {
+        return; // root can read everything
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("f1"), vec![1u8; 10_000]).unwrap();
+    fs::create_dir_all(root.join("sub/locked")).unwrap();
+    fs::write(root.join("sub/f2"), vec![1u8; 10_000]).unwrap();
+    fs::write(root.join("sub/locked/f3"), vec![1u8; 10_000]).unwrap();
+    fs::set_permissions(root.join("sub/locked"), fs::Permissions::from_mode(0o000)).unwrap();
+    let con = app_context(root);
+    let dam = Dam::unlimited();
+    let root_sum = FileSum::from_dir(root, None, &dam, &con).unwrap();
+    let locked_sum = FileSum::from_dir(&root.join("sub/locked"), None, &dam, &con).unwrap();
+    fs::set_permissions(root.join("sub/locked"), fs::Permissions::from_mode(0o755)).unwrap();
+    // root, f1, sub, f2 and locked are counted, but not f3
+    assert_eq!(root_sum.to_count(), 5);
+    assert!(root_sum.is_partial());
+    assert!(locked_sum.is_partial());
+    assert!(root_sum.to_size() >= 2 * FileSum::from_file(&root.join("f1")).to_size());
+    // a sum with only readable content isn't partial
+    let f1_sum = FileSum::from_file(&root.join("f1"));
+    assert!(!f1_sum.is_partial());
+}
@@ -51,6 +51,19 @@ fn is_ignored(path: &Path, special_paths: &[SpecialPath]) -> bool {
     }
 }
 
+/// tell whether the entry must be excluded from the sum because it's
+/// not on the device the computation is restricted to
+#[cfg(unix)]
+#[inline(always)]
+fn is_on_other_device(md: &fs::Metadata, dev: Option<u64>) -> bool {
+    dev.map_or(false, |dev| md.dev() != dev)
+}
+#[cfg(not(unix))]
+#[inline(always)]
+fn is_on_other_device(_md: &fs::Metadata, _dev: Option<u64>) -> bool {
+    false
+}
+
 impl DirSummer {
     pub fn new(thread_count: usize) -> Self {
         let thread_pool = ThreadPoolBuilder::new()
@@ -69,7 +82,8 @@ impl DirSummer {
     pub fn compute_dir_sum(
         &mut self,
         path: &Path,
-        cache: &mut AHashMap<PathBuf, FileSum>,
+        dev: Option<u64>,
+        cache: &mut AHashMap<(PathBuf, Option<u64>), FileSum>,
         dam: &Dam,
         con: &AppContext,
     ) -> Option<FileSum> {
@@ -122,12 +136,22 @@ impl DirSummer {
                             continue;
                         }
 
-                        // we check the cache
-                        if let Some(entry_sum) = cache.get(&entry_path) {
-                            sum += *entry_sum;
+                        if is_on_other_device(&md, dev) {
+                            debug!("not summing other device {:?}", entry_path);
                             continue;
                         }
 
+                        // we check the cache, but a cached sum including hard linked
+                        // files can't be used as the files may be elsewhere in the tree
+                        let key = (entry_path, dev);
+                        if let Some(entry_sum) = cache.get(&key) {
+                            if !entry_sum.is_hard_linked() {
+                                sum += *entry_sum;
+                                continue;
+                            }
+                        }
+                        let (entry_path, _) = key;
+
                         // we add the directory to the channel of dirs needing
                         // processing
                         busy += 1;
@@ -136,6 +160,7 @@ impl DirSummer {
 
                         #[cfg(unix)]
                         if md.nlink() > 1 {
+                            sum.mark_hard_linked();
                             let mut nodes = nodes.lock().unwrap();
                             let node_id = NodeId {
                                 inode: md.ino(),
@@ -151,6 +176,8 @@ impl DirSummer {
                     sum += md_sum(&md);
                 }
             }
+        } else {
+            sum.mark_partial();
         }
 
         if busy == 0 {
@@ -193,6 +220,10 @@ impl DirSummer {
                                             continue;
                                         }
 
+                                        if is_on_other_device(&md, dev) {
+                                            continue;
+                                        }
+
                                         // we add the directory to the channel of dirs needing
                                         // processing
                                         busy.fetch_add(1, Ordering::Relaxed);
@@ -201,6 +232,7 @@ impl DirSummer {
 
                                         #[cfg(unix)]
                                         if md.nlink() > 1 {
+                                            thread_sum.mark_hard_linked();
                                             let mut nodes = nodes.lock().unwrap();
                                             let node_id = NodeId {
                                                 inode: md.ino(),
@@ -219,6 +251,8 @@ impl DirSummer {
                                     thread_sum.incr();
                                 }
                             }
+                        } else {
+                            thread_sum.mark_partial();
                         }
                         busy.fetch_sub(1, Ordering::Relaxed);
                     }
@@ -259,7 +293,8 @@ impl DirSummer {
 /// see https://doc.rust-lang.org/std/os/unix/fs/trait.MetadataExt.html#tymethod.blocks
 pub fn compute_dir_sum(
     path: &Path,
-    cache: &mut AHashMap<PathBuf, FileSum>,
+    dev: Option<u64>,
+    cache: &mut AHashMap<(PathBuf, Option<u64>), FileSum>,
     dam: &Dam,
     con: &AppContext,
 ) -> Option<FileSum> {
@@ -270,32 +305,36 @@ pub fn compute_dir_sum(
             Mutex::new(DirSummer::new(con.file_sum_threads_count))
         })
         .lock().unwrap()
-        .compute_dir_sum(path, cache, dam, con)
+        .compute_dir_sum(path, dev, cache, dam, con)
 }
 
 /// compute the sum for a regular file (not a folder)
 pub fn compute_file_sum(path: &Path) -> FileSum {
     match fs::symlink_metadata(path) {
-        Ok(md) => {
-            let seconds = extract_seconds(&md);
+        Ok(md) => compute_metadata_sum(&md),
+        Err(_) => FileSum::new(0, false, 1, 0),
+    }
+}
 
-            #[cfg(unix)]
-            {
-                let nominal_size = md.size();
-                let block_size = md.blocks() * 512;
-                FileSum::new(
-                    block_size.min(nominal_size),
-                    block_size < nominal_size,
-                    1,
-                    seconds,
-                )
-            }
+/// compute the sum for a regular file (not a folder) whose
+/// metadata are already known
+pub fn compute_metadata_sum(md: &fs::Metadata) -> FileSum {
+    let seconds = extract_seconds(md);
 
-            #[cfg(not(unix))]
-            FileSum::new(md.len(), false, 1, seconds)
-        }
-        Err(_) => FileSum::new(0, false, 1, 0),
+    #[cfg(unix)]
+    {
+        let nominal_size = md.size();
+        let block_size = md.blocks() * 512;
+        FileSum::new(
+            block_size.min(nominal_size),
+            block_size < nominal_size,
+            1,
+            seconds,
+        )
     }
+
+    #[cfg(not(unix))]
+    FileSum::new(md.len(), false, 1, seconds)
 }
 
 #[cfg(unix)]
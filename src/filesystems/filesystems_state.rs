@@ -198,6 +198,7 @@ impl PanelState for FilesystemState {
         &mut self,
         pattern: InputPattern,
         _app_state: &AppState,
+        _screen: Screen,
         _con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
         if pattern.is_none() {
@@ -147,7 +147,7 @@ impl GitIgnoreChain {
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct GitIgnorer {
     files: Arena<GitIgnoreFile>,
 }
@@ -1,13 +1,15 @@
 use {
     git2::{self, Repository, Status},
-    ahash::AHashMap,
+    ahash::{AHashMap, AHashSet},
     std::{
         path::{Path, PathBuf},
     },
 };
 
 const INTERESTING: Status = Status::from_bits_truncate(
-    Status::WT_NEW.bits() | Status::CONFLICTED.bits() | Status::WT_MODIFIED.bits(),
+    Status::WT_NEW.bits() | Status::CONFLICTED.bits() | Status::WT_MODIFIED.bits()
+    | Status::INDEX_NEW.bits() | Status::INDEX_MODIFIED.bits()
+    | Status::INDEX_RENAMED.bits() | Status::INDEX_TYPECHANGE.bits(),
 );
 
 /// A git status
@@ -29,50 +31,93 @@ impl LineGitStatus {
 
 /// As a git repo can't tell whether a path has a status, this computer
 /// looks at all the statuses of the repo and build a map path->status
-/// which can then be efficiently queried
+/// which can then be efficiently queried.
+///
+/// The directories containing a file with an interesting status are
+/// remembered too, so that they can be marked as modified.
 pub struct LineStatusComputer {
     interesting_statuses: AHashMap<PathBuf, Status>,
+    dirty_dirs: AHashSet<PathBuf>,
 }
 impl LineStatusComputer {
     pub fn from(repo: Repository) -> Option<Self> {
         let workdir = repo.workdir()?;
         let mut interesting_statuses = AHashMap::default();
+        let mut dirty_dirs = AHashSet::default();
         let statuses = repo.statuses(None).ok()?;
         for entry in statuses.iter() {
             let status = entry.status();
             if status.intersects(INTERESTING) {
                 if let Some(path) = entry.path() {
                     let path = workdir.join(path);
+                    for dir in path.ancestors().skip(1) {
+                        if !dir.starts_with(workdir) || !dirty_dirs.insert(dir.to_path_buf()) {
+                            break;
+                        }
+                    }
                     interesting_statuses.insert(path, status);
                 }
             }
         }
-        Some(Self { interesting_statuses })
+        Some(Self { interesting_statuses, dirty_dirs })
     }
     pub fn line_status(&self, path: &Path) -> Option<LineGitStatus> {
         self.interesting_statuses
             .get(path)
             .map(|&status| LineGitStatus { status })
+            .or_else(|| {
+                self.dirty_dirs
+                    .contains(path)
+                    .then_some(LineGitStatus { status: Status::WT_MODIFIED })
+            })
     }
     pub fn is_interesting(&self, path: &Path) -> bool {
         self.interesting_statuses.contains_key(path)
     }
 }
 
-///
+/// The git status of the repository containing the tree root
 #[derive(Debug, Clone)]
 pub struct TreeGitStatus {
     pub current_branch_name: Option<String>,
     pub insertions: usize,
     pub deletions: usize,
+    pub modified_count: usize,
+    pub untracked_count: usize,
+}
+
+/// the name of the current branch, or the short hash of the commit
+/// when the head is detached
+fn current_branch_name(repo: &Repository) -> Option<String> {
+    match repo.head() {
+        Ok(head) => {
+            if repo.head_detached().unwrap_or(false) {
+                head.target().map(|oid| {
+                    let mut hash = oid.to_string();
+                    hash.truncate(7);
+                    hash
+                })
+            } else {
+                head.shorthand().map(String::from)
+            }
+        }
+        Err(_) => {
+            // the branch may be unborn (no commit yet), its name is
+            // then only in the symbolic target of HEAD
+            repo.find_reference("HEAD")
+                .ok()
+                .and_then(|head| head.symbolic_target().map(String::from))
+                .map(|target| target.trim_start_matches("refs/heads/").to_string())
+        }
+    }
 }
 
 impl TreeGitStatus {
     pub fn from(repo: &Repository) -> Option<Self> {
-        let current_branch_name = repo
-            .head()
-            .ok()
-            .and_then(|head| head.shorthand().map(String::from));
+        if repo.is_bare() {
+            return None;
+        }
+        let current_branch_name = current_branch_name(repo);
         let stats = match repo.diff_index_to_workdir(None, None) {
             Ok(diff) => {
                 match diff.stats() {
@@ -88,10 +133,24 @@ impl TreeGitStatus {
                 return None;
             }
         };
+        let mut modified_count = 0;
+        let mut untracked_count = 0;
+        if let Ok(statuses) = repo.statuses(None) {
+            for entry in statuses.iter() {
+                let status = entry.status();
+                if status.contains(Status::WT_NEW) {
+                    untracked_count += 1;
+                } else if status.intersects(INTERESTING) {
+                    modified_count += 1;
+                }
+            }
+        }
         Some(Self {
             current_branch_name,
             insertions: stats.insertions(),
             deletions: stats.deletions(),
+            modified_count,
+            untracked_count,
         })
     }
 }
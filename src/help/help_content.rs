@@ -10,9 +10,9 @@ static MD: &str = r#"
 It's best used when launched as **br**.
 See **https://dystroy.org/broot** for a complete guide.
 
-The *esc* key gets you back to the previous state.
+The *esc* key gets you back to the previous state (*?* also closes this help).
 The *↑* and *↓* arrow keys can be used to change selection.
-The mouse can be used to select (on click) or open (on double-click).
+The mouse can be used to select (on click) or open (on double-click, or click on the selected directory).
 
 ## Search Modes
 
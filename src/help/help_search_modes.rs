@@ -23,6 +23,7 @@ pub fn search_mode_help(mode: SearchMode, con: &AppContext) -> SearchModeHelp {
             SearchKind::Fuzzy => "fuzzy",
             SearchKind::Regex => "regex",
             SearchKind::Tokens => "tokens",
+            SearchKind::Extension => "extension",
         },
         match mode.object() {
             SearchObject::Name => "file name",
@@ -35,6 +36,7 @@ pub fn search_mode_help(mode: SearchMode, con: &AppContext) -> SearchModeHelp {
         SearchMode::NameFuzzy => format!("`{prefix}conh` matches *DefaultConf.hjson*"),
         SearchMode::NameRegex => format!("`{prefix}rs$` matches *build.rs*"),
         SearchMode::NameTokens => format!("`{prefix}fea,he` matches *HelpFeature.java*"),
+        SearchMode::NameExtension => format!("`{prefix}rs` matches *build.rs*"),
         SearchMode::PathExact => format!("`{prefix}te\\/do` matches *website/docs*"),
         SearchMode::PathFuzzy => format!("`{prefix}flam` matches *src/flag/mod.rs*"),
         SearchMode::PathRegex => format!(r#"`{prefix}\d{{3}}.*txt` matches *dir/a123/b.txt*"#),
@@ -103,6 +103,7 @@ impl PanelState for HelpState {
         &mut self,
         pat: InputPattern,
         _app_state: &AppState,
+        _screen: Screen,
         _con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
         self.pattern = pat.pattern;
@@ -218,7 +219,7 @@ impl PanelState for HelpState {
                     CmdResult::PopState
                 }
             }
-            help => CmdResult::Keep,
+            help => CmdResult::PopState, // `?` toggles the help
             line_down | line_down_no_cycle => {
                 self.scroll += get_arg(input_invocation, internal_exec, 1);
                 CmdResult::Keep
@@ -1,5 +1,6 @@
 
 mod icon_plugin;
+mod nerdfont;
 mod vscode;
 
 pub use {
@@ -9,6 +10,7 @@ pub use {
 pub fn icon_plugin(icon_set: &str) -> Option<Box<dyn IconPlugin + Send + Sync>> {
     match icon_set {
         "vscode" => Some(Box::new(vscode::VsCodeIconPlugin::new())),
+        "nerdfont" => Some(Box::new(nerdfont::NerdFontIconPlugin)),
         _ => None,
     }
 }
@@ -0,0 +1,109 @@
+use {
+    super::*,
+    crate::tree::TreeLineType,
+};
+
+// some code points of the Nerd Fonts (https://www.nerdfonts.com/cheat-sheet)
+const FOLDER: char = '\u{f07b}'; // nf-fa-folder
+const LINK: char = '\u{f0c1}'; // nf-fa-link
+const FILE: char = '\u{f15b}'; // nf-fa-file
+const TEXT: char = '\u{f15c}'; // nf-fa-file_text
+const PDF: char = '\u{f1c1}'; // nf-fa-file_pdf_o
+const IMAGE: char = '\u{f1c5}'; // nf-fa-file_image_o
+const ARCHIVE: char = '\u{f1c6}'; // nf-fa-file_archive_o
+const AUDIO: char = '\u{f1c7}'; // nf-fa-file_audio_o
+const VIDEO: char = '\u{f1c8}'; // nf-fa-file_video_o
+const CODE: char = '\u{f1c9}'; // nf-fa-file_code_o
+const LOCK: char = '\u{f023}'; // nf-fa-lock
+const GIT: char = '\u{f1d3}'; // nf-fa-git
+const CONFIG: char = '\u{e615}'; // nf-seti-config
+const SHELL: char = '\u{f489}'; // nf-oct-terminal
+const MARKDOWN: char = '\u{f48a}'; // nf-oct-markdown
+const RUST: char = '\u{e7a8}'; // nf-dev-rust
+const PYTHON: char = '\u{e73c}'; // nf-dev-python
+const JAVASCRIPT: char = '\u{e74e}'; // nf-dev-javascript
+const TYPESCRIPT: char = '\u{e628}'; // nf-seti-typescript
+const GO: char = '\u{e627}'; // nf-seti-go
+const C: char = '\u{e61e}'; // nf-seti-c
+const CPP: char = '\u{e61d}'; // nf-seti-cpp
+const JAVA: char = '\u{e738}'; // nf-dev-java
+const RUBY: char = '\u{e739}'; // nf-dev-ruby
+const JSON: char = '\u{e60b}'; // nf-seti-json
+const HTML: char = '\u{e736}'; // nf-dev-html5
+const CSS: char = '\u{e749}'; // nf-dev-css3
+const MAKEFILE: char = '\u{e779}'; // nf-dev-gnu
+
+/// An icon plugin for the fonts patched by the Nerd Fonts project
+pub struct NerdFontIconPlugin;
+
+impl NerdFontIconPlugin {
+    fn file_icon(name: &str, ext: Option<&str>) -> char {
+        match name {
+            "cargo.toml" => return RUST,
+            "makefile" | "justfile" => return MAKEFILE,
+            ".gitignore" | ".gitattributes" | ".gitmodules" => return GIT,
+            "license" | "readme" => return TEXT,
+            _ => {}
+        }
+        match ext.unwrap_or("") {
+            "rs" => RUST,
+            "py" | "pyi" => PYTHON,
+            "js" | "mjs" | "cjs" | "jsx" => JAVASCRIPT,
+            "ts" | "tsx" => TYPESCRIPT,
+            "go" => GO,
+            "c" | "h" => C,
+            "cpp" | "cc" | "cxx" | "hpp" | "hh" => CPP,
+            "java" | "jar" | "class" => JAVA,
+            "rb" => RUBY,
+            "json" => JSON,
+            "html" | "htm" => HTML,
+            "css" | "scss" | "sass" | "less" => CSS,
+            "md" | "markdown" => MARKDOWN,
+            "sh" | "bash" | "zsh" | "fish" | "nu" | "ps1" => SHELL,
+            "toml" | "yaml" | "yml" | "hjson" | "ini" | "conf" | "cfg" => CONFIG,
+            "lock" => LOCK,
+            "txt" | "log" | "rst" => TEXT,
+            "pdf" => PDF,
+            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "ico" | "svg" | "webp" | "tif" | "tiff" => IMAGE,
+            "zip" | "tar" | "gz" | "tgz" | "xz" | "bz2" | "zst" | "7z" | "rar" | "deb" | "rpm" => ARCHIVE,
+            "mp3" | "flac" | "wav" | "ogg" | "opus" | "m4a" | "aac" => AUDIO,
+            "mp4" | "mkv" | "avi" | "mov" | "webm" | "wmv" => VIDEO,
+            "xml" | "lua" | "vim" | "kt" | "swift" | "php" | "pl" | "hs" | "ex" | "exs" | "zig" => CODE,
+            _ => FILE,
+        }
+    }
+}
+
+impl IconPlugin for NerdFontIconPlugin {
+    fn get_icon(
+        &self,
+        tree_line_type: &TreeLineType,
+        name: &str,
+        _double_ext: Option<&str>,
+        ext: Option<&str>,
+    ) -> char {
+        match tree_line_type {
+            TreeLineType::Dir => FOLDER,
+            TreeLineType::SymLink { .. } | TreeLineType::BrokenSymLink(_) => LINK,
+            TreeLineType::File => Self::file_icon(
+                &name.to_ascii_lowercase(),
+                ext.map(|e| e.to_ascii_lowercase()).as_deref(),
+            ),
+            _ => FILE,
+        }
+    }
+}
+
+#[test]
+fn test_nerdfont_icons() {
+    let plugin = NerdFontIconPlugin;
+    let file_icon = |name: &str, ext: Option<&str>| {
+        plugin.get_icon(&TreeLineType::File, name, None, ext)
+    };
+    assert_eq!(file_icon("main.rs", Some("rs")), RUST);
+    assert_eq!(file_icon("Cargo.toml", Some("toml")), RUST);
+    assert_eq!(file_icon("photo.JPG", Some("JPG")), IMAGE);
+    assert_eq!(file_icon("Makefile", None), MAKEFILE);
+    assert_eq!(file_icon("unknown.xyz", Some("xyz")), FILE);
+    assert_eq!(plugin.get_icon(&TreeLineType::Dir, "src", None, None), FOLDER);
+}
@@ -30,11 +30,16 @@ pub fn is_key_allowed_for_verb(
     key: KeyEvent,
     mode: Mode,
     input_is_empty: bool,
+    root_is_selected: bool,
 ) -> bool {
+    // backspace goes up the tree only from its root
+    if key==key!(backspace) && !root_is_selected {
+        return false;
+    }
     match mode {
         Mode::Input => {
             // in input mode, keys normally used in the input are forbidden
-            if key==key!(left) || key==key!(right) {
+            if key==key!(left) || key==key!(right) || key==key!(backspace) {
                 input_is_empty
             } else {
                 !is_key_only_modal(key)
@@ -1,6 +1,6 @@
 use {
     crate::{
-        app::AppContext,
+        app::{AppContext, LineNumber},
         display::{
             DisplayableTree,
             Screen,
@@ -23,7 +23,7 @@ use {
     std::{
         env,
         io::{self, Write},
-        path::PathBuf,
+        path::{Path, PathBuf},
         process::Command,
     },
     which::which,
@@ -55,6 +55,7 @@ pub enum Launchable {
         working_dir: Option<PathBuf>,
         switch_terminal: bool,
         capture_mouse: bool,
+        check_status: bool, // whether a non zero exit status is an error
     },
 
     /// open a path
@@ -89,6 +90,38 @@ fn resolve_env_variables(parts: Vec<String>) -> Vec<String> {
     resolved
 }
 
+/// Editors which can be told the line to open the file at with a `+N` argument
+const PLUS_LINE_EDITORS: &[&str] = &[
+    "emacs", "emacsclient", "gvim", "jed", "joe", "kak", "mg",
+    "micro", "nano", "ne", "nvim", "pico", "vi", "view", "vim",
+];
+
+/// Build the command editing the file at the given path, with the editor
+/// given by `$EDITOR`, `$VISUAL`, or `vi` when none of them is set
+fn editor_command(
+    editor: Option<&str>,
+    path: &Path,
+    line: LineNumber,
+) -> Vec<String> {
+    let mut parts: Vec<String> = editor
+        .unwrap_or("vi")
+        .split_whitespace()
+        .map(|s| s.to_string())
+        .collect();
+    if parts.is_empty() {
+        parts.push("vi".to_string());
+    }
+    let accepts_plus_line = Path::new(&parts[0])
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map_or(false, |name| PLUS_LINE_EDITORS.contains(&name));
+    if line > 0 && accepts_plus_line {
+        parts.push(format!("+{line}"));
+    }
+    parts.push(path.to_string_lossy().to_string());
+    parts
+}
+
 impl Launchable {
     pub fn opener(path: PathBuf) -> Launchable {
         Launchable::SystemOpen { path }
@@ -125,23 +158,51 @@ impl Launchable {
                 working_dir,
                 switch_terminal,
                 capture_mouse: con.capture_mouse,
+                check_status: false,
             }),
             None => Err(io::Error::new(io::ErrorKind::Other, "Empty launch string")),
         }
     }
 
+    /// the launchable opening the file in the user's editor, at the
+    /// given line when it's known and the editor supports it
+    pub fn editor(
+        path: &Path,
+        line: LineNumber,
+        con: &AppContext,
+    ) -> Launchable {
+        let editor = ["EDITOR", "VISUAL"]
+            .iter()
+            .filter_map(|var_name| env::var(var_name).ok())
+            .find(|value| !value.trim().is_empty());
+        let mut parts = editor_command(editor.as_deref(), path, line).into_iter();
+        Launchable::Program {
+            exe: parts.next().unwrap(), // editor_command never returns an empty vec
+            args: parts.collect(),
+            working_dir: None,
+            switch_terminal: true,
+            capture_mouse: con.capture_mouse,
+            check_status: true,
+        }
+    }
+
     pub fn execute(
         &self,
         mut w: Option<&mut W>,
     ) -> Result<(), ProgramError> {
         match self {
             Launchable::Printer { to_print } => {
-                println!("{to_print}");
+                // not println, which panics when stdout is closed
+                writeln!(io::stdout().lock(), "{to_print}")?;
                 Ok(())
             }
             Launchable::TreePrinter { tree, skin, ext_colors, width, height } => {
                 let dp = DisplayableTree::out_of_app(tree, skin, ext_colors, *width, *height);
-                dp.write_on(&mut std::io::stdout())
+                // the tree may be long, so we don't write line per line
+                let mut w = io::BufWriter::new(io::stdout().lock());
+                dp.write_on(&mut w)?;
+                w.flush()?;
+                Ok(())
             }
             Launchable::Program {
                 working_dir,
@@ -149,6 +210,7 @@ impl Launchable {
                 exe,
                 args,
                 capture_mouse,
+                check_status,
             } => {
                 debug!("working_dir: {working_dir:?}");
                 debug!("switch_terminal: {working_dir:?}");
@@ -178,6 +240,16 @@ impl Launchable {
                     .map_err(|source| ProgramError::LaunchError {
                         program: exe.clone(),
                         source,
+                    })
+                    .and_then(|status| {
+                        if *check_status && !status.success() {
+                            Err(ProgramError::ProgramFailed {
+                                program: exe.clone(),
+                                status: status.to_string(),
+                            })
+                        } else {
+                            Ok(())
+                        }
                     });
                 if *switch_terminal {
                     if let Some(ref mut w) = &mut w {
@@ -203,3 +275,15 @@ impl Launchable {
         }
     }
 }
+
+#[test]
+fn test_editor_command() {
+    let path = Path::new("/some dir/file.rs");
+    let command = |editor, line| editor_command(editor, path, line);
+    assert_eq!(command(Some("nvim"), 12), vec!["nvim", "+12", "/some dir/file.rs"]);
+    assert_eq!(command(Some("/usr/bin/vim -p"), 3), vec!["/usr/bin/vim", "-p", "+3", "/some dir/file.rs"]);
+    assert_eq!(command(Some("nvim"), 0), vec!["nvim", "/some dir/file.rs"]);
+    assert_eq!(command(Some("code --wait"), 5), vec!["code", "--wait", "/some dir/file.rs"]);
+    assert_eq!(command(None, 7), vec!["vi", "+7", "/some dir/file.rs"]);
+    assert_eq!(command(Some("  "), 0), vec!["vi", "/some dir/file.rs"]);
+}
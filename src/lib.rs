@@ -1,8 +1,10 @@
 #[macro_use] extern crate cli_log;
 
 pub mod app;
+pub mod archive;
 pub mod browser;
 pub mod cli;
+pub mod clipboard;
 pub mod command;
 pub mod conf;
 pub mod content_search;
@@ -28,6 +30,7 @@ pub mod shell_install;
 pub mod skin;
 pub mod syntactic;
 pub mod task_sync;
+pub mod trash;
 pub mod tree;
 pub mod tree_build;
 pub mod verb;
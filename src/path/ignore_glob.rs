@@ -0,0 +1,66 @@
+use {
+    crate::errors::ProgramError,
+    glob,
+    std::path::Path,
+};
+
+/// A glob pattern defining files and directories which are never
+/// listed in trees (defined in conf or given as launch argument).
+///
+/// Patterns containing a slash are matched against the path relative
+/// to the root of the tree, other ones against the file name.
+#[derive(Debug, Clone)]
+pub struct IgnoreGlob {
+    pattern: glob::Pattern,
+    on_path: bool,
+}
+
+impl IgnoreGlob {
+    pub fn new(s: &str) -> Result<Self, ProgramError> {
+        let on_path = s.contains('/');
+        let s = s.trim_start_matches('/');
+        let pattern = glob::Pattern::new(s)
+            .map_err(|_| ProgramError::InvalidGlobError { pattern: s.to_string() })?;
+        Ok(Self { pattern, on_path })
+    }
+    pub fn is_match(&self, name: &str, subpath: &Path) -> bool {
+        if self.on_path {
+            let options = glob::MatchOptions {
+                case_sensitive: true,
+                require_literal_separator: true,
+                require_literal_leading_dot: false,
+            };
+            self.pattern.matches_path_with(subpath, options)
+        } else {
+            self.pattern.matches(name)
+        }
+    }
+}
+
+pub trait IgnoreGlobList {
+    fn excludes(self, name: &str, subpath: &Path) -> bool;
+}
+
+impl IgnoreGlobList for &[IgnoreGlob] {
+    fn excludes(self, name: &str, subpath: &Path) -> bool {
+        self.iter().any(|glob| glob.is_match(name, subpath))
+    }
+}
+
+#[test]
+fn test_ignore_globs() {
+    let globs = ["*.o", ".cache", "build/**/*.tmp", "/docs/[ab]?.md"]
+        .iter()
+        .map(|s| IgnoreGlob::new(s).unwrap())
+        .collect::<Vec<_>>();
+    let globs = &globs[..];
+    assert!(globs.excludes("main.o", Path::new("src/main.o")));
+    assert!(globs.excludes(".cache", Path::new("deep/.cache")));
+    assert!(!globs.excludes("main.rs", Path::new("src/main.rs")));
+    assert!(globs.excludes("a.tmp", Path::new("build/a.tmp")));
+    assert!(globs.excludes("a.tmp", Path::new("build/x/y/a.tmp")));
+    assert!(!globs.excludes("a.tmp", Path::new("src/build/a.tmp")));
+    assert!(globs.excludes("a1.md", Path::new("docs/a1.md")));
+    assert!(!globs.excludes("c1.md", Path::new("docs/c1.md")));
+    assert!(!globs.excludes("a1.md", Path::new("docs/x/a1.md")));
+}
@@ -2,6 +2,7 @@ mod anchor;
 mod common;
 mod closest;
 mod from;
+mod ignore_glob;
 mod normalize;
 mod special_path;
 
@@ -10,6 +11,7 @@ pub use {
     closest::*,
     common::*,
     from::*,
+    ignore_glob::*,
     normalize::*,
     special_path::*,
 };
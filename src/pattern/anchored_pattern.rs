@@ -0,0 +1,123 @@
+//! a pattern matching the start and/or the end of names or paths,
+//! written like `^mod` or `mod$` with the fuzzy syntax.
+
+use {
+    super::NameMatch,
+    crate::errors::PatternError,
+    secular,
+    smallvec::SmallVec,
+    std::fmt::{self, Write},
+};
+
+// weights used in match score computing. Anchored matches are
+// scored above fuzzy ones (whose base is 50_000)
+const BONUS_MATCH: i32 = 60_000;
+const BONUS_EXACT: i32 = 1_000;
+const BONUS_CANDIDATE_LENGTH: i32 = -1; // per char
+
+/// A pattern requiring the candidate to start with the pattern
+/// (`^mod`), to end with it (`mod$`), or to be equal to it (`^mod$`)
+#[derive(Debug, Clone)]
+pub struct AnchoredPattern {
+    chars: Box<[char]>, // secularized characters, unless case sensitive
+    start: bool,
+    end: bool,
+    case_sensitive: bool,
+}
+
+impl fmt::Display for AnchoredPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start {
+            f.write_char('^')?;
+        }
+        for &c in self.chars.iter() {
+            f.write_char(c)?;
+        }
+        if self.end {
+            f.write_char('$')?;
+        }
+        Ok(())
+    }
+}
+
+impl AnchoredPattern {
+    /// parse the core of a fuzzy pattern, return None if it isn't anchored.
+    /// The case is handled like in fuzzy patterns.
+    pub fn parse(core: &str, flags: &str) -> Result<Option<Self>, PatternError> {
+        let (start, pat) = match core.strip_prefix('^') {
+            Some(pat) => (true, pat),
+            None => (false, core),
+        };
+        let (end, pat) = match pat.strip_suffix('$') {
+            Some(pat) => (true, pat),
+            None => (false, pat),
+        };
+        if !(start || end) || pat.is_empty() {
+            return Ok(None);
+        }
+        let case_sensitive = super::is_case_sensitive(pat, flags)?;
+        let chars = if case_sensitive {
+            pat.chars().collect::<Vec<char>>()
+        } else {
+            secular::normalized_lower_lay_string(pat)
+                .chars()
+                .collect::<Vec<char>>()
+        };
+        Ok(Some(Self {
+            chars: chars.into_boxed_slice(),
+            start,
+            end,
+            case_sensitive,
+        }))
+    }
+
+    /// return a match if the candidate starts and/or ends with the pattern
+    pub fn find(&self, candidate: &str) -> Option<NameMatch> {
+        let cand_chars: SmallVec<[char; 32]> = if self.case_sensitive {
+            candidate.chars().collect()
+        } else {
+            candidate.chars().map(secular::lower_lay_char).collect()
+        };
+        let len = self.chars.len();
+        if cand_chars.len() < len || (self.start && self.end && cand_chars.len() != len) {
+            return None;
+        }
+        let offset = if self.start { 0 } else { cand_chars.len() - len };
+        if cand_chars[offset..offset + len] != self.chars[..] {
+            return None;
+        }
+        let mut score = BONUS_MATCH + BONUS_CANDIDATE_LENGTH * cand_chars.len() as i32;
+        if cand_chars.len() == len {
+            score += BONUS_EXACT;
+        }
+        let pos = (offset..offset + len).collect();
+        Some(NameMatch { score, pos })
+    }
+
+    pub fn score_of(&self, candidate: &str) -> Option<i32> {
+        self.find(candidate).map(|m| m.score)
+    }
+}
+
+#[test]
+fn test_anchored_pattern() {
+    assert!(AnchoredPattern::parse("mod", "").unwrap().is_none());
+    assert!(AnchoredPattern::parse("^", "").unwrap().is_none());
+    let p = AnchoredPattern::parse("^mod", "").unwrap().unwrap();
+    assert_eq!(&p.find("Module.rs").unwrap().pos[..], &[0, 1, 2]);
+    assert!(p.find("a_mod.rs").is_none());
+    let p = AnchoredPattern::parse(".rs$", "").unwrap().unwrap();
+    assert_eq!(&p.find("mod.rs").unwrap().pos[..], &[3, 4, 5]);
+    assert!(p.find("mod.rst").is_none());
+    let p = AnchoredPattern::parse("^mod.rs$", "").unwrap().unwrap();
+    assert!(p.find("mod.rs").is_some());
+    assert!(p.find("mod.rs.bak").is_none());
+    let p = AnchoredPattern::parse("^Mod", "").unwrap().unwrap();
+    assert!(p.find("mod.rs").is_none());
+    // anchored matches are preferred to fuzzy ones
+    let fuzzy = super::FuzzyPattern::from("mod");
+    assert!(
+        AnchoredPattern::parse("^mod", "").unwrap().unwrap().score_of("mod_a.rs")
+            > fuzzy.score_of("mod")
+    );
+}
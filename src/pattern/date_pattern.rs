@@ -0,0 +1,81 @@
+//! a pattern filtering files on their last modification date, eg `mtime<2d`
+
+use {
+    super::Candidate,
+    lazy_regex::regex_captures,
+    std::{
+        fmt,
+        fs,
+        time::{Duration, SystemTime},
+    },
+};
+
+/// A pattern keeping the files modified less than a given time ago
+/// (`mtime<`), or more (`mtime>`).
+#[derive(Debug, Clone)]
+pub struct DatePattern {
+    age: Duration,
+    older: bool,
+}
+
+impl fmt::Display for DatePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = if self.older { '>' } else { '<' };
+        write!(f, "mtime{}{}s", op, self.age.as_secs())
+    }
+}
+
+impl DatePattern {
+    /// parse a pattern like `mtime<2d`, return None if the string
+    /// isn't a date filter.
+    /// Units are s, m, h, d and w (the default is the day).
+    pub fn parse(s: &str) -> Option<Self> {
+        let (_, op, digits, unit) = regex_captures!(r"^mtime([<>])(\d+)([smhdw]?)$", s)?;
+        let unit_seconds = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 60 * 60,
+            "w" => 7 * 24 * 60 * 60,
+            _ => 24 * 60 * 60,
+        };
+        let seconds = digits.parse::<u64>().ok()?.checked_mul(unit_seconds)?;
+        Some(Self {
+            age: Duration::from_secs(seconds),
+            older: op == ">",
+        })
+    }
+
+    pub fn score_of(&self, candidate: Candidate) -> Option<i32> {
+        if !candidate.regular_file {
+            // directories are kept only when they have matching descendants
+            return None;
+        }
+        let modified = fs::metadata(candidate.path).ok()?.modified().ok()?;
+        // a file modified in the future is considered as just modified
+        let age = SystemTime::now().duration_since(modified).unwrap_or_default();
+        let matches = if self.older {
+            age > self.age
+        } else {
+            age < self.age
+        };
+        if matches {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn test_parse_date_pattern() {
+    let p = DatePattern::parse("mtime<2d").unwrap();
+    assert_eq!(p.age, Duration::from_secs(2 * 24 * 60 * 60));
+    assert!(!p.older);
+    let p = DatePattern::parse("mtime>30m").unwrap();
+    assert_eq!(p.age, Duration::from_secs(30 * 60));
+    assert!(p.older);
+    assert_eq!(DatePattern::parse("mtime<3").unwrap().age, Duration::from_secs(3 * 24 * 60 * 60));
+    assert!(DatePattern::parse("mtime<").is_none());
+    assert!(DatePattern::parse("mtime<3y").is_none());
+    assert!(DatePattern::parse("time<3d").is_none());
+}
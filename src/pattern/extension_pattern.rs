@@ -0,0 +1,65 @@
+//! a pattern matching the extension of file names, case insensitively
+
+use {
+    super::{NameMatch, Pos},
+    std::fmt,
+};
+
+/// A pattern for filtering files on their extension
+#[derive(Debug, Clone)]
+pub struct ExtensionPattern {
+    extension: String, // lowercase, without the dot
+}
+
+impl fmt::Display for ExtensionPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.extension.fmt(f)
+    }
+}
+
+impl ExtensionPattern {
+    /// build a pattern from an extension, with or without the leading dot
+    pub fn from(pattern: &str) -> Self {
+        Self {
+            extension: pattern.trim_start_matches('.').to_lowercase(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extension.is_empty()
+    }
+
+    /// return a match on the extension part of the name, if it's the
+    /// one of the pattern. Names starting with their only dot (eg
+    /// ".bashrc") have no extension.
+    pub fn find(&self, candidate: &str) -> Option<NameMatch> {
+        let dot_idx = candidate.rfind('.')?;
+        if dot_idx == 0 {
+            return None;
+        }
+        let extension = &candidate[dot_idx + 1..];
+        if extension.to_lowercase() != self.extension {
+            return None;
+        }
+        let start = candidate[..=dot_idx].chars().count();
+        let pos: Pos = (start..start + extension.chars().count()).collect();
+        Some(NameMatch { score: 1, pos })
+    }
+
+    pub fn score_of(&self, candidate: &str) -> Option<i32> {
+        self.find(candidate).map(|m| m.score)
+    }
+}
+
+#[test]
+fn test_extension_pattern() {
+    let pattern = ExtensionPattern::from(".RS");
+    assert!(pattern.find("mod.rs").is_some());
+    assert!(pattern.find("Build.Rs").is_some());
+    assert!(pattern.find("archive.tar.rs").is_some());
+    assert!(pattern.find("rs").is_none());
+    assert!(pattern.find(".rs").is_none());
+    assert!(pattern.find("mod.rsx").is_none());
+    let m = pattern.find("é.rs").unwrap();
+    assert_eq!(&m.pos[..], &[2, 3]);
+}
@@ -4,6 +4,7 @@
 
 use {
     super::NameMatch,
+    crate::errors::PatternError,
     secular,
     smallvec::{smallvec, SmallVec},
     std::fmt::{self, Write},
@@ -24,8 +25,9 @@ const BONUS_SINGLED_CHAR: i32 = -15; // when there's a char, neither first not l
 /// A pattern for fuzzy matching
 #[derive(Debug, Clone)]
 pub struct FuzzyPattern {
-    chars: Box<[char]>, // secularized characters
+    chars: Box<[char]>, // secularized characters, unless case sensitive
     max_nb_holes: usize,
+    case_sensitive: bool,
 }
 
 impl fmt::Display for FuzzyPattern {
@@ -48,13 +50,31 @@ fn is_word_separator(c: char) -> bool {
 }
 
 impl FuzzyPattern {
-    /// build a pattern which will later be usable for fuzzy search.
+    /// build a case insensitive pattern which will later be usable
+    /// for fuzzy search.
     /// A pattern should be reused
     pub fn from(pat: &str) -> Self {
-        let chars = secular::normalized_lower_lay_string(pat)
-            .chars()
-            .collect::<Vec<char>>()
-            .into_boxed_slice();
+        Self::new(pat, false)
+    }
+
+    /// build a pattern, case sensitive when the flags contain `s`,
+    /// ignoring case and diacritics when they contain `i`.
+    /// Without flag, the case is "smart": the pattern is case
+    /// sensitive only when it contains an uppercase char.
+    pub fn from_flags(pat: &str, flags: &str) -> Result<Self, PatternError> {
+        let case_sensitive = super::is_case_sensitive(pat, flags)?;
+        Ok(Self::new(pat, case_sensitive))
+    }
+
+    fn new(pat: &str, case_sensitive: bool) -> Self {
+        let chars = if case_sensitive {
+            pat.chars().collect::<Vec<char>>()
+        } else {
+            secular::normalized_lower_lay_string(pat)
+                .chars()
+                .collect::<Vec<char>>()
+        };
+        let chars = chars.into_boxed_slice();
         let max_nb_holes = match chars.len() {
             1 => 0,
             2 => 1,
@@ -69,6 +89,7 @@ impl FuzzyPattern {
         FuzzyPattern {
             chars,
             max_nb_holes,
+            case_sensitive,
         }
     }
 
@@ -144,14 +165,22 @@ impl FuzzyPattern {
             return MatchSearchResult::None;
         }
         let match_len = 1 + cand_idx - pos[0];
+        // When the candidate is a path, the components before the one
+        // where the match starts are ignored, so that a match in a deep
+        // file name isn't penalized relative to a match higher in the tree
+        let comp_start = cand_chars[..pos[0]]
+            .iter()
+            .rposition(|&c| c == '/')
+            .map_or(0, |idx| idx + 1);
+        let cand_len = cand_chars.len() - comp_start;
         let mut score = BONUS_MATCH;
-        score += BONUS_CANDIDATE_LENGTH * (cand_chars.len() as i32);
+        score += BONUS_CANDIDATE_LENGTH * (cand_len as i32);
         score += BONUS_SINGLED_CHAR * nb_singled_chars;
         score += BONUS_NB_HOLES * (nb_holes as i32);
         score += match_len as i32 * BONUS_MATCH_LENGTH;
-        if pos[0] == 0 {
+        if pos[0] == comp_start {
             score += BONUS_START + BONUS_START_WORD;
-            if cand_chars.len() == self.chars.len() {
+            if cand_len == self.chars.len() {
                 score += BONUS_EXACT;
                 return MatchSearchResult::Perfect(NameMatch { score, pos });
             }
@@ -175,7 +204,11 @@ impl FuzzyPattern {
             return None;
         }
         let mut cand_chars: CandChars = SmallVec::with_capacity(candidate.len());
-        cand_chars.extend(candidate.chars().map(secular::lower_lay_char));
+        if self.case_sensitive {
+            cand_chars.extend(candidate.chars());
+        } else {
+            cand_chars.extend(candidate.chars().map(secular::lower_lay_char));
+        }
         if cand_chars.len() < self.chars.len() {
             return None;
         }
@@ -393,4 +426,47 @@ mod fuzzy_pattern_tests {
         let fp = FuzzyPattern::from("устр");
         assert!(fp.find("Устройства").is_some());
     }
+    #[test]
+    fn check_case_flags() {
+        let fp = FuzzyPattern::from_flags("Rea", "i").unwrap();
+        assert!(fp.find("readme.md").is_some());
+        assert!(fp.find("RÉADME.md").is_some());
+        let fp = FuzzyPattern::from_flags("Rea", "s").unwrap();
+        assert!(fp.find("readme.md").is_none());
+        let m = fp.find("my_README.Read").unwrap();
+        assert_eq!(&m.pos[..], &[10, 11, 12]);
+        assert!(FuzzyPattern::from_flags("Rea", "x").is_err());
+    }
+    fn smart_score(pattern: &str, name: &str) -> Option<i32> {
+        FuzzyPattern::from_flags(pattern, "").unwrap().score_of(name)
+    }
+    /// lowercase patterns are case insensitive and keep the scores of
+    /// the insensitive mode, uppercase chars make the pattern case sensitive
+    #[test]
+    fn check_smart_case_scores() {
+        for (pattern, name, score) in [
+            ("broot", "broot.toml", Some(49955)),
+            ("broot", "Broot", Some(50960)),
+            ("brt", "my_BROOT.txt", Some(49913)),
+            ("eleve", "Élève.md", Some(49957)),
+        ] {
+            assert_eq!(smart_score(pattern, name), score);
+            assert_eq!(FuzzyPattern::from(pattern).score_of(name), score);
+        }
+        assert_eq!(smart_score("Broot", "Broot"), Some(50960));
+        assert_eq!(smart_score("Broot", "broot.toml"), None);
+        assert_eq!(smart_score("Br", "aBr_broot"), Some(49971));
+        assert_eq!(smart_score("Élè", "Élève.md"), Some(49977));
+        assert_eq!(smart_score("Élè", "eleve.md"), None);
+    }
+    /// in paths, the file name is preferred to a component higher up
+    #[test]
+    fn check_path_scores() {
+        let fp = FuzzyPattern::from("flat");
+        let score = |path: &str| fp.score_of(path).unwrap();
+        assert!(score("src/tree/flat.rs") > score("flat-stuff"));
+        assert!(score("flat-stuff") > score("flat-stuff/readme.md"));
+        assert_eq!(score("src/flat.rs"), score("flat.rs"));
+        assert_eq!(&fp.find("flat/flat").unwrap().pos[..], &[5, 6, 7, 8]);
+    }
 }
@@ -1,9 +1,12 @@
 
+mod anchored_pattern;
 mod candidate;
 mod composite_pattern;
 mod content_pattern;
 mod content_regex_pattern;
+mod date_pattern;
 mod exact_pattern;
+mod extension_pattern;
 mod fuzzy_pattern;
 mod input_pattern;
 mod name_match;
@@ -14,14 +17,18 @@ mod pattern_parts;
 mod pos;
 mod regex_pattern;
 mod search_mode;
+mod size_pattern;
 mod tok_pattern;
 
 pub use {
+    anchored_pattern::AnchoredPattern,
     candidate::Candidate,
     composite_pattern::CompositePattern,
     content_pattern::ContentExactPattern,
     content_regex_pattern::ContentRegexPattern,
+    date_pattern::DatePattern,
     exact_pattern::ExactPattern,
+    extension_pattern::ExtensionPattern,
     fuzzy_pattern::FuzzyPattern,
     input_pattern::InputPattern,
     name_match::NameMatch,
@@ -32,6 +39,7 @@ pub use {
     operator::PatternOperator,
     regex_pattern::RegexPattern,
     search_mode::*,
+    size_pattern::SizePattern,
     tok_pattern::*,
 };
 
@@ -57,3 +65,18 @@ pub fn build_regex(pat: &str, flags: &str) -> Result<regex::Regex, PatternError>
     }
     Ok(builder.build()?)
 }
+
+/// tell whether a fuzzy-like pattern should be case sensitive:
+/// `s` and `i` flags force the mode, and without flag the
+/// pattern is case sensitive only when it contains an uppercase char
+pub fn is_case_sensitive(pat: &str, flags: &str) -> Result<bool, PatternError> {
+    let mut case_sensitive = None;
+    for c in flags.chars() {
+        match c {
+            'i' => case_sensitive = Some(false),
+            's' => case_sensitive = Some(true),
+            _ => return Err(PatternError::UnknownFuzzyFlag { bad: c }),
+        }
+    }
+    Ok(case_sensitive.unwrap_or_else(|| pat.chars().any(char::is_uppercase)))
+}
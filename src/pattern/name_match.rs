@@ -8,7 +8,7 @@ use {
 #[derive(Debug, Clone)]
 pub struct NameMatch {
     pub score: i32, // score of the match, guaranteed strictly positive, bigger is better
-    pub pos: Pos, // positions of the matching chars
+    pub pos: Pos, // indexes, in chars (not bytes), of the matching chars
 }
 
 impl NameMatch {
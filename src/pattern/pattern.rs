@@ -4,8 +4,9 @@ use {
         content_search::ContentMatch,
         errors::PatternError,
     },
-    bet::BeTree,
+    bet::{BeTree, Child},
     std::{
+        cell::Cell,
         path::Path,
     },
 };
@@ -16,14 +17,19 @@ pub enum Pattern {
     None,
     NameExact(ExactPattern),
     NameFuzzy(FuzzyPattern),
+    NameAnchored(AnchoredPattern),
     NameRegex(RegexPattern),
     NameTokens(TokPattern),
+    NameExtension(ExtensionPattern),
     PathExact(ExactPattern),
     PathFuzzy(FuzzyPattern),
+    PathAnchored(AnchoredPattern),
     PathRegex(RegexPattern),
     PathTokens(TokPattern),
     ContentExact(ContentExactPattern),
     ContentRegex(ContentRegexPattern),
+    Size(SizePattern),
+    Date(DatePattern),
     Composite(CompositePattern),
 }
 
@@ -34,35 +40,80 @@ impl Pattern {
         search_modes: &SearchModeMap,
         content_search_max_file_size: usize,
     ) -> Result<Self, PatternError> {
+        let negated = negated_atoms(raw_expr);
+        let atom_idx = Cell::new(0);
         let expr: BeTree<PatternOperator, Pattern> = raw_expr
             .try_map_atoms::<_, PatternError, _>(|pattern_parts| {
+                // atoms are mapped in the order of their ids
+                let is_negated = negated[atom_idx.replace(atom_idx.get() + 1)];
                 let core = pattern_parts.core();
                 Ok(
                     if core.is_empty() {
                         Pattern::None
+                    } else if let Some(sp) = SizePattern::parse(core)
+                        .filter(|_| pattern_parts.mode().is_none())
+                    {
+                        // a size filter, eg "size>100M", isn't a search mode
+                        Pattern::Size(sp)
+                    } else if let Some(dp) = DatePattern::parse(core)
+                        .filter(|_| pattern_parts.mode().is_none())
+                    {
+                        Pattern::Date(dp)
                     } else {
                         let parts_mode = pattern_parts.mode();
-                        let mode = search_modes.search_mode(parts_mode)?;
-                        let flags = pattern_parts.flags();
+                        let whole_path;
+                        let (mode, core, flags) = match search_modes.search_mode(parts_mode) {
+                            Ok(mode) => (mode, core, pattern_parts.flags()),
+                            Err(e) => {
+                                // when the part before the slash isn't a known mode,
+                                // like in `src/flat`, the input is most probably a path
+                                if parts_mode.map_or(true, |m| m.is_empty()) {
+                                    return Err(e);
+                                }
+                                whole_path = pattern_parts.to_string();
+                                (SearchMode::PathFuzzy, whole_path.as_str(), None)
+                            }
+                        };
                         match mode {
                             SearchMode::NameExact => Self::NameExact(
                                 ExactPattern::from(core)
                             ),
-                            SearchMode::NameFuzzy => Self::NameFuzzy(
-                                FuzzyPattern::from(core)
-                            ),
+                            SearchMode::NameFuzzy => {
+                                if let Some(ep) = quoted_exact_pattern(core) {
+                                    Self::NameExact(ep)
+                                } else if let Some(ap) = AnchoredPattern::parse(core, flags.unwrap_or(""))? {
+                                    Self::NameAnchored(ap)
+                                } else if is_negated {
+                                    // excluding on a fuzzy match would be unpredictable
+                                    Self::NameExact(ExactPattern::from(core))
+                                } else {
+                                    Self::NameFuzzy(FuzzyPattern::from_flags(core, flags.unwrap_or(""))?)
+                                }
+                            }
                             SearchMode::NameRegex => Self::NameRegex(
                                 RegexPattern::from(core, flags.unwrap_or(""))?
                             ),
                             SearchMode::NameTokens => Self::NameTokens(
                                 TokPattern::new(core)
                             ),
+                            SearchMode::NameExtension => Self::NameExtension(
+                                ExtensionPattern::from(core)
+                            ),
                             SearchMode::PathExact => Self::PathExact(
                                 ExactPattern::from(core)
                             ),
-                            SearchMode::PathFuzzy => Self::PathFuzzy(
-                                FuzzyPattern::from(core)
-                            ),
+                            SearchMode::PathFuzzy => {
+                                if let Some(ep) = quoted_exact_pattern(core) {
+                                    Self::PathExact(ep)
+                                } else if let Some(ap) = AnchoredPattern::parse(core, flags.unwrap_or(""))? {
+                                    Self::PathAnchored(ap)
+                                } else if is_negated {
+                                    // excluding on a fuzzy match would be unpredictable
+                                    Self::PathExact(ExactPattern::from(core))
+                                } else {
+                                    Self::PathFuzzy(FuzzyPattern::from_flags(core, flags.unwrap_or(""))?)
+                                }
+                            }
                             SearchMode::PathRegex => Self::PathRegex(
                                 RegexPattern::from(core, flags.unwrap_or(""))?
                             ),
@@ -95,11 +146,13 @@ impl Pattern {
     pub fn object(&self) -> PatternObject {
         let mut object = PatternObject::default();
         match self {
-            Self::None => {}
-            Self::NameExact(_) | Self::NameFuzzy(_) | Self::NameRegex(_) | Self::NameTokens(_) => {
+            Self::None | Self::Size(_) | Self::Date(_) => {}
+            Self::NameExact(_) | Self::NameFuzzy(_) | Self::NameRegex(_) | Self::NameTokens(_)
+                | Self::NameExtension(_) | Self::NameAnchored(_) => {
                 object.name = true;
             }
-            Self::PathExact(_) | Self::PathFuzzy(_) | Self::PathRegex(_) | Self::PathTokens(_) => {
+            Self::PathExact(_) | Self::PathFuzzy(_) | Self::PathRegex(_) | Self::PathTokens(_)
+                | Self::PathAnchored(_) => {
                 object.subpath = true;
             }
             Self::ContentExact(_) | Self::ContentRegex(_) => {
@@ -121,8 +174,10 @@ impl Pattern {
         match self {
             Self::NameExact(ep) | Self::PathExact(ep) => ep.find(candidate),
             Self::NameFuzzy(fp) | Self::PathFuzzy(fp) => fp.find(candidate),
+            Self::NameAnchored(ap) | Self::PathAnchored(ap) => ap.find(candidate),
             Self::NameRegex(rp) | Self::PathRegex(rp) => rp.find(candidate),
             Self::NameTokens(tp) | Self::PathTokens(tp) => tp.find(candidate),
+            Self::NameExtension(xp) => xp.find(candidate),
             Self::Composite(cp) => cp.search_string(candidate),
             _ => None,
         }
@@ -160,14 +215,26 @@ impl Pattern {
         match self {
             Self::NameExact(ep) => ep.score_of(candidate.name),
             Self::NameFuzzy(fp) => fp.score_of(candidate.name),
+            Self::NameAnchored(ap) => ap.score_of(candidate.name),
             Self::NameRegex(rp) => rp.find(candidate.name).map(|m| m.score),
             Self::NameTokens(tp) => tp.score_of(candidate.name),
+            Self::NameExtension(xp) => {
+                // directories never match an extension
+                if candidate.regular_file {
+                    xp.score_of(candidate.name)
+                } else {
+                    None
+                }
+            }
             Self::PathExact(ep) => ep.score_of(candidate.subpath),
             Self::PathFuzzy(fp) => fp.score_of(candidate.subpath),
+            Self::PathAnchored(ap) => ap.score_of(candidate.subpath),
             Self::PathRegex(rp) => rp.find(candidate.subpath).map(|m| m.score),
             Self::PathTokens(tp) => tp.score_of(candidate.subpath),
             Self::ContentExact(cp) => cp.score_of(candidate),
             Self::ContentRegex(cp) => cp.score_of(candidate),
+            Self::Size(sp) => sp.score_of(candidate),
+            Self::Date(dp) => dp.score_of(candidate),
             Self::Composite(cp) => cp.score_of(candidate),
             Self::None => Some(1),
         }
@@ -177,14 +244,19 @@ impl Pattern {
         match self {
             Self::NameExact(ep) => ep.score_of(candidate),
             Self::NameFuzzy(fp) => fp.score_of(candidate),
+            Self::NameAnchored(ap) => ap.score_of(candidate),
             Self::NameRegex(rp) => rp.find(candidate).map(|m| m.score),
             Self::NameTokens(tp) => tp.score_of(candidate),
+            Self::NameExtension(xp) => xp.score_of(candidate),
             Self::PathExact(ep) => ep.score_of(candidate),
             Self::PathFuzzy(fp) => fp.score_of(candidate),
+            Self::PathAnchored(ap) => ap.score_of(candidate),
             Self::PathRegex(rp) => rp.find(candidate).map(|m| m.score),
             Self::PathTokens(tp) => tp.score_of(candidate),
             Self::ContentExact(_) => None, // this isn't suitable
             Self::ContentRegex(_) => None, // this isn't suitable
+            Self::Size(_) => None, // this isn't suitable
+            Self::Date(_) => None, // this isn't suitable
             Self::Composite(cp) => cp.score_of_string(candidate),
             Self::None => Some(1),
         }
@@ -201,10 +273,13 @@ impl Pattern {
             Self::NameExact(ep) | Self::PathExact(ep) => ep.is_empty(),
             Self::ContentExact(ep) => ep.is_empty(),
             Self::NameFuzzy(fp) | Self::PathFuzzy(fp) => fp.is_empty(),
+            Self::NameAnchored(_) | Self::PathAnchored(_) => false,
             Self::NameRegex(rp) | Self::PathRegex(rp) => rp.is_empty(),
             Self::ContentRegex(rp) => rp.is_empty(),
             Self::NameTokens(tp) | Self::PathTokens(tp) => tp.is_empty(),
+            Self::NameExtension(xp) => xp.is_empty(),
             Self::Composite(cp) => cp.is_empty(),
+            Self::Size(_) | Self::Date(_) => false,
             Self::None => true,
         }
     }
@@ -214,8 +289,8 @@ impl Pattern {
     /// in order to select the best ones.
     pub fn has_real_scores(&self) -> bool {
         match self {
-            Self::NameExact(_) | Self::NameFuzzy(_) => true,
-            Self::PathExact(_) | Self::PathFuzzy(_) => true,
+            Self::NameExact(_) | Self::NameFuzzy(_) | Self::NameAnchored(_) => true,
+            Self::PathExact(_) | Self::PathFuzzy(_) | Self::PathAnchored(_) => true,
             Self::Composite(cp) => cp.has_real_scores(),
             _ => false,
         }
@@ -223,3 +298,84 @@ impl Pattern {
 
 }
 
+/// in fuzzy modes, a pattern between double quotes (eg `"mod.rs"`)
+/// is an exact one
+fn quoted_exact_pattern(core: &str) -> Option<ExactPattern> {
+    core.strip_prefix('"')
+        .and_then(|core| core.strip_suffix('"'))
+        .map(ExactPattern::from)
+}
+
+/// tell, for each atom of the expression, whether it's under a `!` operator
+fn negated_atoms(expr: &BeTree<PatternOperator, PatternParts>) -> Vec<bool> {
+    fn visit(
+        expr: &BeTree<PatternOperator, PatternParts>,
+        child: Child,
+        in_not: bool,
+        negated: &mut Vec<bool>,
+    ) {
+        match child {
+            Child::Atom(atom_id) => {
+                if let Some(n) = negated.get_mut(atom_id) {
+                    *n = in_not;
+                }
+            }
+            Child::Node(node_id) => {
+                if let Some(node) = expr.node(node_id) {
+                    let in_not = in_not != (node.operator == Some(PatternOperator::Not));
+                    visit(expr, node.left, in_not, negated);
+                    visit(expr, node.right, in_not, negated);
+                }
+            }
+            Child::None => {}
+        }
+    }
+    let mut negated = vec![false; expr.iter_atoms().count()];
+    let head = expr.head();
+    let in_not = head.operator == Some(PatternOperator::Not);
+    visit(expr, head.left, in_not, &mut negated);
+    visit(expr, head.right, in_not, &mut negated);
+    negated
+}
+
+#[test]
+fn test_negated_atoms_are_exact() {
+    use crate::command::CommandParts;
+    let pattern = |raw: &str| {
+        let parts = CommandParts::from(raw);
+        Pattern::new(&parts.pattern, &SearchModeMap::default(), 10_000).unwrap()
+    };
+    assert!(FuzzyPattern::from("test").find("tes_t.rs").is_some());
+    let p = pattern("conf&!test");
+    assert!(p.score_of_string("conf.toml").is_some());
+    assert!(p.score_of_string("conf_tes_t.toml").is_some()); // would be excluded with fuzzy
+    assert!(p.score_of_string("conftest.toml").is_none());
+    // only negations: everything but the excluded
+    let p = pattern("!test");
+    assert!(p.score_of_string("tes_t.rs").is_some());
+    assert!(p.score_of_string("mytest.rs").is_none());
+    // the match of the positive atom is the one highlighted
+    let m = pattern("!test&conf").search_string("myconf.toml").unwrap();
+    assert_eq!(&m.pos[..], &[2, 3, 4, 5]);
+    // a double negation is a positive atom, thus still fuzzy
+    let p = pattern("!(!test)");
+    assert!(p.score_of_string("tes_t.rs").is_some());
+    assert!(p.score_of_string("conf.toml").is_none());
+}
+
+#[test]
+fn test_unknown_mode_is_a_path() {
+    use crate::command::CommandParts;
+    let parts = CommandParts::from("src/flat");
+    let pattern = Pattern::new(&parts.pattern, &SearchModeMap::default(), 10_000).unwrap();
+    assert!(matches!(pattern, Pattern::PathFuzzy(_)));
+    assert!(pattern.score_of_string("src/tree/flat.rs").is_some());
+    let parts = CommandParts::from("p/flat");
+    let pattern = Pattern::new(&parts.pattern, &SearchModeMap::default(), 10_000).unwrap();
+    assert!(pattern.score_of_string("src/tree/flat.rs").is_some());
+    // no part of a deep path is lost
+    let parts = CommandParts::from("src/tree/flat/deep");
+    let pattern = Pattern::new(&parts.pattern, &SearchModeMap::default(), 10_000).unwrap();
+    assert!(pattern.score_of_string("src/tree/flat/deep.rs").is_some());
+    assert!(pattern.score_of_string("src/tree/flat.rs").is_none());
+}
@@ -11,11 +11,7 @@ pub struct PatternParts {
 
 impl fmt::Display for PatternParts {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.parts.len() {
-            1 => write!(f, "{}", &self.parts[0]),
-            2 => write!(f, "{}/{}", &self.parts[0], &self.parts[1]),
-            _ => write!(f, "{}/{}/{}", &self.parts[0], &self.parts[1], &self.parts[2]),
-        }
+        write!(f, "{}", self.parts.join("/"))
     }
 }
 
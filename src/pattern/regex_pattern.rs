@@ -4,7 +4,6 @@ use {
     super::NameMatch,
     crate::errors::PatternError,
     lazy_regex::regex,
-    smallvec::SmallVec,
     std::fmt,
 };
 
@@ -32,10 +31,10 @@ impl RegexPattern {
         // note that there's no significative cost related to using
         //  find over is_match
         self.rex.find(candidate).map(|rm| {
-            let mut pos = SmallVec::with_capacity(rm.end() - rm.start());
-            for i in rm.start()..rm.end() {
-                pos.push(i);
-            }
+            // the regex gives byte offsets while pos are char indexes
+            let start = candidate[..rm.start()].chars().count();
+            let len = rm.as_str().chars().count();
+            let pos = (start..start + len).collect();
             super::NameMatch { score: 1, pos }
         })
     }
@@ -44,3 +43,11 @@ impl RegexPattern {
     }
 
 }
+
+#[test]
+fn test_regex_match_pos() {
+    let pattern = RegexPattern::from("b.r", "").unwrap();
+    assert_eq!(&pattern.find("foobar").unwrap().pos[..], &[3, 4, 5]);
+    // positions are in chars, not bytes
+    assert_eq!(&pattern.find("éèbàr").unwrap().pos[..], &[2, 3, 4]);
+}
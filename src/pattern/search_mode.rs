@@ -23,6 +23,7 @@ pub enum SearchKind {
     Fuzzy,
     Regex,
     Tokens,
+    Extension,
 }
 
 /// a valid combination of SearchObject and SearchKind,
@@ -33,6 +34,7 @@ pub enum SearchMode {
     NameFuzzy,
     NameRegex,
     NameTokens,
+    NameExtension,
     PathExact,
     PathFuzzy,
     PathRegex,
@@ -46,6 +48,7 @@ pub static SEARCH_MODES: &[SearchMode] = &[
     SearchMode::NameRegex,
     SearchMode::NameExact,
     SearchMode::NameTokens,
+    SearchMode::NameExtension,
     SearchMode::PathExact,
     SearchMode::PathFuzzy,
     SearchMode::PathRegex,
@@ -65,16 +68,19 @@ impl SearchMode {
             (Name, Fuzzy) => Some(Self::NameFuzzy),
             (Name, Regex) => Some(Self::NameRegex),
             (Name, Tokens) => Some(Self::NameTokens),
+            (Name, Extension) => Some(Self::NameExtension),
 
             (Path, Exact) => Some(Self::PathExact),
             (Path, Fuzzy) => Some(Self::PathFuzzy),
             (Path, Regex) => Some(Self::PathRegex),
             (Path, Tokens) => Some(Self::PathTokens),
+            (Path, Extension) => None, // an extension is only in the name
 
             (Content, Exact) => Some(Self::ContentExact),
             (Content, Fuzzy) => None, // unsupported for now - could be but why ?
             (Content, Regex) => Some(Self::ContentRegex),
             (Content, Tokens) => None, // unsupported for now - could be but need bench
+            (Content, Extension) => None,
         }
     }
     /// Return the prefix to type, eg "/" in standard for a name-regex,
@@ -87,7 +93,8 @@ impl SearchMode {
     }
     pub fn object(self) -> SearchObject {
         match self {
-            Self::NameExact | Self::NameFuzzy | Self::NameRegex | Self::NameTokens => SearchObject::Name,
+            Self::NameExact | Self::NameFuzzy | Self::NameRegex | Self::NameTokens
+                | Self::NameExtension => SearchObject::Name,
             Self::PathExact | Self::PathFuzzy | Self::PathRegex | Self::PathTokens => SearchObject::Path,
             Self::ContentExact | Self::ContentRegex => SearchObject::Content,
         }
@@ -98,6 +105,7 @@ impl SearchMode {
             Self::NameFuzzy => SearchKind::Fuzzy,
             Self::NameRegex => SearchKind::Regex,
             Self::NameTokens => SearchKind::Tokens,
+            Self::NameExtension => SearchKind::Extension,
             Self::PathExact => SearchKind::Exact,
             Self::PathFuzzy => SearchKind::Fuzzy,
             Self::PathRegex => SearchKind::Regex,
@@ -135,6 +143,7 @@ impl SearchModeMapEntry {
                 "fuzzy" => search_kinds.push(SearchKind::Fuzzy),
                 "regex" => search_kinds.push(SearchKind::Regex),
                 "tokens" => search_kinds.push(SearchKind::Tokens),
+                "extension" => search_kinds.push(SearchKind::Extension),
                 "name" => search_objects.push(SearchObject::Name),
                 "content" => search_objects.push(SearchObject::Content),
                 "path" => search_objects.push(SearchObject::Path),
@@ -148,7 +157,7 @@ impl SearchModeMapEntry {
         if search_kinds.is_empty() {
             return Err(ConfError::InvalidSearchMode {
                 details: "missing search kind in search mode definition\
-                    (the search kind must be one of 'exact', 'fuzzy', 'regex', 'tokens')".to_string()
+                    (the search kind must be one of 'exact', 'fuzzy', 'regex', 'tokens', 'extension')".to_string()
             });
         }
         if search_kinds.len() > 1 {
@@ -209,6 +218,7 @@ impl Default for SearchModeMap {
         smm.setm(&["rx", "cr"], SearchMode::ContentRegex);
         smm.setm(&["pt", "tp", "t"], SearchMode::PathTokens);
         smm.setm(&["tn", "nt"], SearchMode::NameTokens);
+        smm.setm(&["x", "ext"], SearchMode::NameExtension);
         smm.set(SearchModeMapEntry { key: None, mode: SearchMode::PathFuzzy });
         smm
     }
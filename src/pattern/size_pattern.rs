@@ -0,0 +1,77 @@
+//! a pattern filtering files on their size, eg `size>100M`
+
+use {
+    super::Candidate,
+    crate::file_sum::FileSum,
+    lazy_regex::regex_captures,
+    std::fmt,
+};
+
+/// A pattern keeping the files whose size is at or above a
+/// threshold (`size>`), or below it (`size<`).
+///
+/// Sizes are the ones displayed in the size column, with
+/// K, M and G multipliers in powers of 1000.
+#[derive(Debug, Clone)]
+pub struct SizePattern {
+    threshold: u64,
+    below: bool,
+}
+
+impl fmt::Display for SizePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = if self.below { '<' } else { '>' };
+        write!(f, "size{}{}", op, self.threshold)
+    }
+}
+
+impl SizePattern {
+    /// parse a pattern like `size>100M`, return None if the string
+    /// isn't a size filter
+    pub fn parse(s: &str) -> Option<Self> {
+        let (_, op, digits, unit) = regex_captures!(r"^size([<>])(\d+)([kmgKMG]?)$", s)?;
+        let multiplier = match unit {
+            "k" | "K" => 1_000,
+            "m" | "M" => 1_000_000,
+            "g" | "G" => 1_000_000_000,
+            _ => 1,
+        };
+        let threshold = digits.parse::<u64>().ok()?.checked_mul(multiplier)?;
+        Some(Self {
+            threshold,
+            below: op == "<",
+        })
+    }
+
+    pub fn score_of(&self, candidate: Candidate) -> Option<i32> {
+        if !candidate.regular_file {
+            // directories are kept only when they have matching descendants
+            return None;
+        }
+        let size = FileSum::from_file(candidate.path).to_size();
+        let matches = if self.below {
+            size < self.threshold
+        } else {
+            size >= self.threshold
+        };
+        if matches {
+            Some(1)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn test_parse_size_pattern() {
+    let p = SizePattern::parse("size>100M").unwrap();
+    assert_eq!(p.threshold, 100_000_000);
+    assert!(!p.below);
+    let p = SizePattern::parse("size<3k").unwrap();
+    assert_eq!(p.threshold, 3_000);
+    assert!(p.below);
+    assert!(SizePattern::parse("size>").is_none());
+    assert!(SizePattern::parse("size=3").is_none());
+    assert!(SizePattern::parse("size>3T").is_none());
+    assert!(SizePattern::parse("sizes>3").is_none());
+}
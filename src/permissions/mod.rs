@@ -6,6 +6,12 @@ pub mod permissions_unix;
 #[cfg(not(any(target_family = "windows", target_os = "android")))]
 pub use permissions_unix::*;
 
+#[cfg(not(any(target_family = "windows", target_os = "android")))]
+mod mode_change;
+
+#[cfg(not(any(target_family = "windows", target_os = "android")))]
+pub use mode_change::*;
+
 //////////////////// WINDOWS
 
 #[cfg(windows)]
@@ -0,0 +1,249 @@
+//! The mode argument of `:chmod`, either octal (`755`) or
+//! symbolic (`u+x,go-w`), as understood by the `chmod` command.
+
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    str::FromStr,
+};
+
+const USER: u32 = 0o4700;
+const GROUP: u32 = 0o2070;
+const OTHERS: u32 = 0o1007;
+const ALL: u32 = 0o7777;
+
+/// A change of the permission bits of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModeChange {
+    Octal(u32),
+    Symbolic(Vec<Clause>),
+}
+
+/// A part of a symbolic mode, eg `go-w` in `u+x,go-w`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Clause {
+    who: u32, // the bits which may be changed, 0 when unspecified
+    actions: Vec<Action>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Action {
+    op: char, // '+', '-' or '='
+    perms: Perms,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Perms {
+    Bits {
+        bits: u32,
+        conditional_x: bool, // 'X': execute only for directories and executable files
+    },
+    Copy {
+        shift: u32, // 'u', 'g' or 'o': the bits of this class
+    },
+}
+
+impl FromStr for ModeChange {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid mode: {s:?}");
+        if s.is_empty() {
+            return Err("no mode given".to_string());
+        }
+        if s.chars().all(|c| c.is_ascii_digit()) {
+            if s.len() > 4 {
+                return Err(invalid());
+            }
+            return u32::from_str_radix(s, 8)
+                .map(Self::Octal)
+                .map_err(|_| invalid());
+        }
+        let mut clauses = Vec::new();
+        for clause in s.split(',') {
+            let mut chars = clause.chars().peekable();
+            let mut who = 0;
+            while let Some(&c) = chars.peek() {
+                who |= match c {
+                    'u' => USER,
+                    'g' => GROUP,
+                    'o' => OTHERS,
+                    'a' => ALL,
+                    _ => break,
+                };
+                chars.next();
+            }
+            let mut actions = Vec::new();
+            while let Some(op) = chars.next() {
+                if !matches!(op, '+' | '-' | '=') {
+                    return Err(invalid());
+                }
+                let mut bits = 0;
+                let mut conditional_x = false;
+                let mut copy = None;
+                while let Some(&c) = chars.peek() {
+                    if copy.is_some() && !matches!(c, '+' | '-' | '=') {
+                        return Err(invalid());
+                    }
+                    match c {
+                        'r' => bits |= 0o444,
+                        'w' => bits |= 0o222,
+                        'x' => bits |= 0o111,
+                        'X' => conditional_x = true,
+                        's' => bits |= 0o6000,
+                        't' => bits |= 0o1000,
+                        'u' | 'g' | 'o' if bits == 0 && !conditional_x => {
+                            copy = Some(match c {
+                                'u' => 6,
+                                'g' => 3,
+                                _ => 0,
+                            });
+                        }
+                        '+' | '-' | '=' => break,
+                        _ => return Err(invalid()),
+                    }
+                    chars.next();
+                }
+                let perms = match copy {
+                    Some(shift) => Perms::Copy { shift },
+                    None => Perms::Bits { bits, conditional_x },
+                };
+                actions.push(Action { op, perms });
+            }
+            if actions.is_empty() {
+                return Err(invalid());
+            }
+            clauses.push(Clause { who, actions });
+        }
+        Ok(Self::Symbolic(clauses))
+    }
+}
+
+impl ModeChange {
+    /// Compute the new mode from the current one.
+    ///
+    /// As with `chmod`, when a clause doesn't tell whose permissions
+    /// are changed, it's all of them but the ones masked by the umask.
+    pub fn apply(&self, mode: u32, is_dir: bool, umask: u32) -> u32 {
+        let clauses = match self {
+            Self::Octal(bits) => {
+                return (mode & !ALL) | bits;
+            }
+            Self::Symbolic(clauses) => clauses,
+        };
+        let mut mode = mode;
+        for clause in clauses {
+            let who = if clause.who == 0 { ALL & !umask } else { clause.who };
+            for action in &clause.actions {
+                let bits = match action.perms {
+                    Perms::Bits { bits, conditional_x } => {
+                        if conditional_x && (is_dir || mode & 0o111 != 0) {
+                            bits | 0o111
+                        } else {
+                            bits
+                        }
+                    }
+                    Perms::Copy { shift } => ((mode >> shift) & 0o7) * 0o111,
+                };
+                let bits = bits & who;
+                mode = match action.op {
+                    '+' => mode | bits,
+                    '-' => mode & !bits,
+                    _ => (mode & !who) | bits,
+                };
+            }
+        }
+        mode
+    }
+}
+
+/// Read the arguments of `:chmod`: the mode, maybe preceded
+/// with `-R` for a recursive change
+pub fn parse_chmod_args(args: &str) -> Result<(ModeChange, bool), String> {
+    let args = args.trim();
+    match args.strip_prefix("-R") {
+        Some(mode) => mode.trim().parse().map(|change| (change, true)),
+        None => args.parse().map(|change| (change, false)),
+    }
+}
+
+/// the current umask of the process
+#[allow(clippy::useless_conversion)] // mode_t isn't u32 on all platforms
+pub fn current_umask() -> u32 {
+    // the umask can only be read by setting it
+    let umask = unsafe {
+        let umask = libc::umask(0);
+        libc::umask(umask);
+        umask
+    };
+    u32::from(umask)
+}
+
+/// Change the mode of the file or directory or, when `recursive` is
+/// true, of the directory and all its content, links encountered in
+/// the directories being skipped. Stop at the first failure.
+pub fn change_mode(
+    path: &Path,
+    change: &ModeChange,
+    recursive: bool,
+) -> Result<(), String> {
+    let umask = current_umask();
+    let set_mode = |path: &Path, metadata: fs::Metadata| {
+        let mode = change.apply(metadata.permissions().mode(), metadata.is_dir(), umask);
+        fs::set_permissions(path, fs::Permissions::from_mode(mode))
+            .map_err(|e| format!("Failed to change the mode of {}: {e}", path.to_string_lossy()))
+    };
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("{}: {e}", path.to_string_lossy()))?;
+    let is_dir = metadata.is_dir();
+    set_mode(path, metadata)?;
+    if !(recursive && is_dir) {
+        return Ok(());
+    }
+    let mut dirs = vec![path.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read {}: {e}", dir.to_string_lossy()))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if metadata.file_type().is_symlink() {
+                continue;
+            }
+            if metadata.is_dir() {
+                dirs.push(path.clone());
+            }
+            set_mode(&path, metadata)?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_mode_change() {
+    let apply = |change: &str, mode: u32, is_dir: bool| {
+        change.parse::<ModeChange>().unwrap().apply(mode, is_dir, 0o022)
+    };
+    assert_eq!(apply("755", 0o100600, false), 0o100755);
+    assert_eq!(apply("u+x", 0o644, false), 0o744);
+    assert_eq!(apply("go-w", 0o666, false), 0o644);
+    assert_eq!(apply("a=r", 0o777, false), 0o444);
+    assert_eq!(apply("u=rwx,g=rx,o=", 0o600, false), 0o750);
+    assert_eq!(apply("g=u", 0o640, false), 0o660);
+    assert_eq!(apply("u+s,g-r+w", 0o755, false), 0o4735);
+    assert_eq!(apply("+t", 0o777, true), 0o1777);
+    // without who, the umask is respected
+    assert_eq!(apply("+w", 0o444, false), 0o644);
+    assert_eq!(apply("+x", 0o644, false), 0o755);
+    // X only gives execute permission to directories and executables
+    assert_eq!(apply("a+X", 0o644, false), 0o644);
+    assert_eq!(apply("a+X", 0o744, false), 0o755);
+    assert_eq!(apply("a+X", 0o700, true), 0o711);
+    for bad in ["", "9", "77777", "u", "u+q", "z+x", "g=ur", "u+x,"] {
+        assert!(bad.parse::<ModeChange>().is_err(), "{bad:?} should be rejected");
+    }
+    assert_eq!(parse_chmod_args(" -R go-w ").unwrap(), ("go-w".parse().unwrap(), true));
+    assert_eq!(parse_chmod_args("644").unwrap(), (ModeChange::Octal(0o644), false));
+}
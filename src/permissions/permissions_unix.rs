@@ -17,7 +17,7 @@ pub fn user_name(uid: u32) -> String {
         .entry(uid)
         .or_insert_with(|| {
             users::get_user_by_uid(uid).map_or_else(
-                || "????".to_string(),
+                || uid.to_string(),
                 |u| u.name().to_string_lossy().to_string(),
             )
         });
@@ -33,7 +33,7 @@ pub fn group_name(gid: u32) -> String {
         .entry(gid)
         .or_insert_with(|| {
             users::get_group_by_gid(gid).map_or_else(
-                || "????".to_string(),
+                || gid.to_string(),
                 |u| u.name().to_string_lossy().to_string(),
             )
         });
@@ -120,6 +120,8 @@ impl Preview {
             // not previewable as UTF8 text
             // we'll try reading it as binary
             Err(ProgramError::UnprintableFile) => Self::hex(path),
+            // eg the file can't be read
+            Err(ProgramError::Io { source }) => Self::IoError(source),
             _ => Self::hex(path),
         }
     }
@@ -136,6 +138,8 @@ impl Preview {
             }
             // not previewable as UTF8 text - we'll try reading it as binary
             Err(ProgramError::UnprintableFile) => Self::hex(path),
+            // eg the file can't be read
+            Err(ProgramError::Io { source }) => Self::IoError(source),
             _ => Self::hex(path),
         }
     }
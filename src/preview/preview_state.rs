@@ -2,6 +2,8 @@ use {
     super::*,
     crate::{
         app::*,
+        archive,
+        clipboard,
         command::{Command, ScrollCommand, TriggerType},
         display::{Screen, W},
         errors::ProgramError,
@@ -15,10 +17,18 @@ use {
         cursor,
         QueueableCommand,
     },
-    std::path::{Path, PathBuf},
+    std::{
+        io,
+        path::{Path, PathBuf},
+    },
+    tempfile::TempPath,
     termimad::{Area, CropWriter, SPACE_FILLING},
 };
 
+/// the maximal size of the content extracted from an
+/// archive to preview one of its files
+const MAX_EXTRACTED_SIZE: u64 = 20_000_000;
+
 /// an application state dedicated to previewing files.
 /// It's usually the only state in its panel and is kept when
 /// the selection changes (other panels indirectly call
@@ -27,6 +37,7 @@ pub struct PreviewState {
     pub preview_area: Area,
     dirty: bool,   // true when background must be cleared
     path: PathBuf, // path to the previewed file
+    extracted: Option<TempPath>, // the content, when the file is in an archive
     preview: Preview,
     pending_pattern: InputPattern, // a pattern (or not) which has not yet be applied
     filtered_preview: Option<Preview>,
@@ -45,11 +56,12 @@ impl PreviewState {
         con: &AppContext,
     ) -> PreviewState {
         let preview_area = Area::uninitialized(); // will be fixed at drawing time
-        let preview = Preview::new(&path, preferred_mode, con);
+        let (preview, extracted) = load_preview(&path, preferred_mode, con);
         PreviewState {
             preview_area,
             dirty: true,
             path,
+            extracted,
             preview,
             pending_pattern,
             filtered_preview: None,
@@ -59,6 +71,11 @@ impl PreviewState {
             mode: initial_mode(con),
         }
     }
+    /// the path of the file whose content is previewed, which
+    /// isn't the selected path for files in archives
+    fn content_path(&self) -> &Path {
+        self.extracted.as_deref().unwrap_or(&self.path)
+    }
     fn vis_preview(&self) -> &Preview {
         self.filtered_preview.as_ref().unwrap_or(&self.preview)
     }
@@ -73,7 +90,7 @@ impl PreviewState {
         if self.preview.get_mode() == Some(mode) {
             return Ok(CmdResult::Keep);
         }
-        Ok(match Preview::with_mode(&self.path, mode, con) {
+        Ok(match Preview::with_mode(self.content_path(), mode, con) {
             Ok(preview) => {
                 self.preview = preview;
                 self.preferred_mode = Some(mode);
@@ -124,6 +141,7 @@ impl PanelState for PreviewState {
         &mut self,
         pat: InputPattern,
         _app_state: &AppState,
+        _screen: Screen,
         _con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
         if pat.is_none() {
@@ -159,7 +177,7 @@ impl PanelState for PreviewState {
             self.filtered_preview = time!(
                 Info,
                 "preview filtering",
-                self.preview.filtered(&self.path, pattern, dam, con),
+                self.preview.filtered(self.content_path(), pattern, dam, con),
             ); // can be None if a cancellation was required
             if let Some(ref mut filtered_preview) = self.filtered_preview {
                 if let Some(number) = old_selection {
@@ -183,7 +201,7 @@ impl PanelState for PreviewState {
         if let Some(fp) = &self.filtered_preview {
             self.pending_pattern = fp.pattern();
         };
-        self.preview = Preview::new(&path, self.preferred_mode, con);
+        (self.preview, self.extracted) = load_preview(&path, self.preferred_mode, con);
         if let Some(number) = selected_line_number {
             self.preview.try_select_line_number(number);
         }
@@ -314,28 +332,21 @@ impl PanelState for PreviewState {
         match internal_exec.internal {
             Internal::back => {
                 if self.filtered_preview.is_some() {
-                    self.on_pattern(InputPattern::none(), app_state, con)
+                    self.on_pattern(InputPattern::none(), app_state, cc.app.screen, con)
                 } else {
                     Ok(CmdResult::PopState)
                 }
             }
             Internal::copy_line => {
-                #[cfg(not(feature = "clipboard"))]
-                {
-                    Ok(CmdResult::error("Clipboard feature not enabled at compilation"))
-                }
-                #[cfg(feature = "clipboard")]
-                {
-                    Ok(match self.mut_preview().get_selected_line() {
-                        Some(line) => {
-                            match terminal_clipboard::set_string(line) {
-                                Ok(()) => CmdResult::Keep,
-                                Err(_) => CmdResult::error("Clipboard error while copying path"),
-                            }
+                Ok(match self.mut_preview().get_selected_line() {
+                    Some(line) => {
+                        match clipboard::copy(&line, w) {
+                            Ok(()) => CmdResult::Keep,
+                            Err(e) => CmdResult::error(e.to_string()),
                         }
-                        None => CmdResult::error("No selected line in preview"),
-                    })
-                }
+                    }
+                    None => CmdResult::error("No selected line in preview"),
+                })
             }
             Internal::line_down => {
                 let count = get_arg(input_invocation, internal_exec, 1);
@@ -379,10 +390,10 @@ impl PanelState for PreviewState {
                 Ok(CmdResult::Keep)
             }
             Internal::panel_right if self.filtered_preview.is_some() => {
-                self.on_pattern(InputPattern::none(), app_state, con)
+                self.on_pattern(InputPattern::none(), app_state, cc.app.screen, con)
             }
             Internal::panel_right_no_open if self.filtered_preview.is_some() => {
-                self.on_pattern(InputPattern::none(), app_state, con)
+                self.on_pattern(InputPattern::none(), app_state, cc.app.screen, con)
             }
             Internal::select_first => {
                 self.mut_preview().select_first();
@@ -419,3 +430,17 @@ impl PanelState for PreviewState {
     }
 
 }
+
+/// build the preview of the path, extracting the content first
+/// when the path is the one of a file in an archive
+fn load_preview(
+    path: &Path,
+    preferred_mode: Option<PreviewMode>,
+    con: &AppContext,
+) -> (Preview, Option<TempPath>) {
+    match archive::extract_to_temp_file(path, MAX_EXTRACTED_SIZE) {
+        Ok(Some(extracted)) => (Preview::new(&extracted, preferred_mode, con), Some(extracted)),
+        Ok(None) => (Preview::new(path, preferred_mode, con), None),
+        Err(e) => (Preview::IoError(io::Error::new(io::ErrorKind::Other, e.to_string())), None),
+    }
+}
@@ -3,15 +3,22 @@
 use {
     crate::{
         app::*,
+        cli::OutFormat,
+        command::{Command, Sequence},
         display::Screen,
         errors::ProgramError,
+        git,
         launchable::Launchable,
-        skin::{PanelSkin, StyleMap},
+        pattern::InputPattern,
+        skin::{ExtColorMap, PanelSkin, StyleMap},
+        task_sync::Dam,
         tree::Tree,
+        tree_build::TreeBuilder,
     },
     crokey::crossterm::tty::IsTty,
     pathdiff,
     std::{
+        fs,
         io::{self, stdout},
         path::Path,
     },
@@ -97,3 +104,57 @@ pub fn print_tree(
         con.ext_colors.clone(),
     )))
 }
+
+/// build the tree of the initial root and return a launchable printing
+/// it without style, for when broot can't, or mustn't, be interactive.
+///
+/// With `--print`, the tree isn't limited to the size of the terminal
+/// (unless a height is given) and the search is complete.
+/// The pattern, if any, is the one found in `--cmd`, whose verbs
+/// are ignored.
+pub fn plain_tree_printer(con: &AppContext) -> Result<Launchable, ProgramError> {
+    let whole = con.launch_args.print;
+    let mut screen = Screen::new(con)?;
+    if whole {
+        screen.width = u16::MAX;
+        if con.launch_args.height.is_none() {
+            screen.height = u16::MAX;
+        }
+    }
+    // we check the root can be read, as the builder would just flag it
+    fs::read_dir(&con.initial_root)?;
+    let mut options = con.initial_tree_options.clone();
+    if let Some(raw) = &con.launch_args.cmd {
+        let commands = Sequence::new_local(raw.to_string()).parse(con)?;
+        for (_, command) in commands {
+            if let Command::PatternEdit { raw, expr } = command {
+                options.pattern = InputPattern::new(raw, &expr, con)?;
+            }
+        }
+    }
+    let mut dam = Dam::unlimited();
+    let mut tree = TreeBuilder::from(
+        con.initial_root.clone(),
+        options,
+        screen.height as usize,
+        con,
+    )?.build_tree(whole, &dam)?;
+    // there's no later pending task to complete the tree: it's done now
+    if tree.is_missing_git_status_computation() {
+        tree.git_status = git::get_tree_status(tree.root(), &mut dam);
+    }
+    while tree.has_dir_missing_sum() {
+        tree.fetch_some_missing_dir_sum(&dam, con);
+    }
+    if con.launch_args.out_format == OutFormat::Json {
+        let json = serde_json::to_string_pretty(&tree.to_json_node())
+            .map_err(|e| ProgramError::InternalError { details: e.to_string() })?;
+        return Ok(Launchable::printer(json));
+    }
+    Ok(Launchable::tree_printer(
+        &tree,
+        screen,
+        StyleMap::no_term(),
+        ExtColorMap::default(),
+    ))
+}
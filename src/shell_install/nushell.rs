@@ -21,7 +21,7 @@ use {
 };
 
 const NAME: &str = "nushell";
-const VERSION: &str = "4";
+const VERSION: &str = "5";
 
 const NU_FUNC: &str = r#"
 # Launch broot
@@ -112,10 +112,12 @@ def-env br [
 
     let cmd_file = ([ $nu.temp-path, $"broot-(random chars).tmp" ] | path join)
     touch $cmd_file
-    if ($file == null) {
-        ^broot --outcmd $cmd_file $args
-    } else {
-        ^broot --outcmd $cmd_file $args $file
+    with-env { BR_SHELL: nushell } {
+        if ($file == null) {
+            ^broot --outcmd $cmd_file $args
+        } else {
+            ^broot --outcmd $cmd_file $args $file
+        }
     }
     let $cmd = (open $cmd_file)
     rm -p -f $cmd_file
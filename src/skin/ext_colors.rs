@@ -1,11 +1,11 @@
 use {
+    super::ls_colors,
     crate::{
         errors::InvalidSkinError,
     },
     ahash::AHashMap,
     crokey::crossterm::style::Color,
     lazy_regex::*,
-    std::convert::TryFrom,
     termimad::parse_color,
 };
 
@@ -24,18 +24,25 @@ impl ExtColorMap {
         self.map.get(ext).copied()
     }
     pub fn set(&mut self, ext: String, raw_color: &str) -> Result<(), InvalidSkinError> {
-        if !regex_is_match!("^none$"i, raw_color) {
+        if regex_is_match!("^none$"i, raw_color) {
+            self.map.remove(&ext);
+        } else {
             let color = parse_color(raw_color)?;
             self.map.insert(ext, color);
         }
         Ok(())
     }
-}
-
-impl TryFrom<&AHashMap<String, String>> for ExtColorMap {
-    type Error = InvalidSkinError;
-    fn try_from(raw_map: &AHashMap<String, String>) -> Result<Self, Self::Error> {
+    /// build the map from the `ext_colors` of the configuration,
+    /// which take precedence over the ones of `LS_COLORS` when
+    /// it's used
+    pub fn new(
+        raw_map: &AHashMap<String, String>,
+        use_ls_colors: bool,
+    ) -> Result<Self, InvalidSkinError> {
         let mut map = ExtColorMap::default();
+        if use_ls_colors {
+            map.map.extend(ls_colors::ext_colors_from_env());
+        }
         for (k, v) in raw_map {
             map.set(k.to_string(), v)?;
         }
@@ -0,0 +1,90 @@
+//! Reading of the `LS_COLORS` environment variable, whose format
+//! is defined by GNU dircolors, eg `di=01;34:ln=01;36:*.rs=33`.
+//!
+//! Only the rules on extensions (`*.ext=...`) are used: the styles
+//! of directories, links, executables, etc. are the ones of the skin.
+
+use {
+    crokey::crossterm::style::Color,
+    std::env,
+};
+
+/// return the foreground colors defined by extension in the
+/// `LS_COLORS` environment variable, if it's set
+pub fn ext_colors_from_env() -> Vec<(String, Color)> {
+    match env::var("LS_COLORS") {
+        Ok(raw) => parse_ext_colors(&raw),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// parse the extension rules of a `LS_COLORS` string, ignoring
+/// the invalid ones and the ones without foreground color
+pub fn parse_ext_colors(raw: &str) -> Vec<(String, Color)> {
+    raw.split(':')
+        .filter_map(|rule| rule.split_once('='))
+        .filter_map(|(key, codes)| {
+            let ext = key.strip_prefix("*.")?;
+            if ext.is_empty() || ext.contains(['*', '?', '[']) {
+                return None;
+            }
+            let color = parse_fg(codes)?;
+            Some((ext.to_string(), color))
+        })
+        .collect()
+}
+
+/// return the foreground color of a list of SGR codes, like `01;38;5;208`
+fn parse_fg(codes: &str) -> Option<Color> {
+    let mut fg = None;
+    let mut codes = codes.split(';').map(|code| code.parse::<u8>().ok());
+    while let Some(code) = codes.next() {
+        match code? {
+            c @ 30..=37 => fg = Some(Color::AnsiValue(c - 30)),
+            39 => fg = None,
+            c @ 90..=97 => fg = Some(Color::AnsiValue(c - 90 + 8)),
+            38 => match codes.next()?? {
+                5 => fg = Some(Color::AnsiValue(codes.next()??)),
+                2 => {
+                    let r = codes.next()??;
+                    let g = codes.next()??;
+                    let b = codes.next()??;
+                    fg = Some(Color::Rgb { r, g, b });
+                }
+                _ => return None,
+            },
+            48 => {
+                // background colors are ignored but their arguments
+                // must be skipped
+                match codes.next()?? {
+                    5 => {
+                        codes.next();
+                    }
+                    2 => {
+                        codes.nth(2);
+                    }
+                    _ => return None,
+                }
+            }
+            _ => {} // attributes and plain background colors
+        }
+    }
+    fg
+}
+
+#[test]
+fn test_parse_ext_colors() {
+    let colors = parse_ext_colors(
+        "rs=0:di=01;34:*.rs=33:*.png=01;38;5;208:*.toml=48;5;4;38;2;1;2;3:\
+        *.md=01:*README=32:*.log=91:*.x=bad:*.=31",
+    );
+    assert_eq!(
+        colors,
+        vec![
+            ("rs".to_string(), Color::AnsiValue(3)),
+            ("png".to_string(), Color::AnsiValue(208)),
+            ("toml".to_string(), Color::Rgb { r: 1, g: 2, b: 3 }),
+            ("log".to_string(), Color::AnsiValue(9)),
+        ],
+    );
+}
@@ -2,6 +2,7 @@ mod app_skin;
 mod cli_mad_skin;
 mod ext_colors;
 mod help_mad_skin;
+mod ls_colors;
 mod panel_skin;
 mod purpose_mad_skin;
 mod skin_entry;
@@ -162,6 +162,7 @@ StyleMap! {
     directory: ansi(110), None, [Bold] / ansi(110), None, []
     exe: Some(Cyan), None, []
     link: Some(Magenta), None, []
+    special_file: ansi(179), None, []
     pruning: gray(12), None, [Italic]
     perm__: gray(5), None, []
     perm_r: ansi(94), None, []
@@ -177,18 +178,22 @@ StyleMap! {
     device_id_major: ansi(138), None, []
     device_id_sep: ansi(102), None, []
     device_id_minor: ansi(138), None, []
+    hard_links: gray(12), None, []
+    hard_links_multiple: ansi(214), None, [Bold]
     git_branch: ansi(178), None, []
     git_insertions: ansi(28), None, []
     git_deletions: ansi(160), None, []
     git_status_current: gray(5), None, []
     git_status_modified: ansi(28), None, []
     git_status_new: ansi(94), None, [Bold]
+    git_status_staged: ansi(29), None, []
     git_status_ignored: gray(17), None, []
     git_status_conflicted: ansi(88), None, []
     git_status_other: ansi(88), None, []
     selected_line: None, gray(6), [] / None, gray(4), []
     char_match: Some(Green), None, []
     file_error: Some(Red), None, []
+    new_entry: None, ansi(22), []
     flag_label: gray(15), gray(2), []
     flag_value: ansi(178), gray(2), [Bold]
     input: Some(White), gray(2), [] / gray(15), None, []
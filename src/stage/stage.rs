@@ -64,6 +64,18 @@ impl Stage {
     pub fn paths(&self) -> &[PathBuf] {
         &self.paths
     }
+    /// the staged paths, without the ones under another staged
+    /// directory, for operations which already apply to the content
+    /// of directories
+    pub fn paths_without_descendants(&self) -> Vec<PathBuf> {
+        self.paths
+            .iter()
+            .filter(|path| {
+                !self.paths.iter().any(|p| p != *path && path.starts_with(p))
+            })
+            .cloned()
+            .collect()
+    }
     /// removes paths to non existing files
     pub fn refresh(&mut self) {
         let len_before = self.paths.len();
@@ -82,7 +94,7 @@ impl Stage {
         let mut sum = FileSum::zero();
         for path in &self.paths {
             if path.is_dir() {
-                let dir_sum = FileSum::from_dir(path, dam, con);
+                let dir_sum = FileSum::from_dir(path, None, dam, con);
                 if let Some(dir_sum) = dir_sum {
                     sum += dir_sum;
                 } else {
@@ -95,3 +107,15 @@ impl Stage {
         Some(sum)
     }
 }
+
+#[test]
+fn test_paths_without_descendants() {
+    let mut stage = Stage::default();
+    for path in ["/a/b/c", "/a/b", "/a/bc", "/d/e", "/a/b/f/g"] {
+        stage.add(PathBuf::from(path));
+    }
+    assert_eq!(
+        stage.paths_without_descendants(),
+        vec![PathBuf::from("/a/b"), PathBuf::from("/a/bc"), PathBuf::from("/d/e")],
+    );
+}
@@ -5,6 +5,7 @@ use {
         command::*,
         display::{MatchedString, Screen, W},
         errors::ProgramError,
+        path::{self, PathAnchor},
         pattern::*,
         skin::*,
         task_sync::Dam,
@@ -40,6 +41,8 @@ pub struct StageState {
 
     stage_sum: StageSum,
 
+    removal_request: Option<RemovalRequest>,
+    overwrite_request: Option<OverwriteRequest>,
 }
 
 impl StageState {
@@ -60,6 +63,8 @@ impl StageState {
             mode: initial_mode(con),
             page_height: 0,
             stage_sum: StageSum::default(),
+            removal_request: None,
+            overwrite_request: None,
         }
     }
 
@@ -191,6 +196,8 @@ impl PanelState for StageState {
 
     fn clear_pending(&mut self) {
         self.stage_sum.clear();
+        RemovalRequest::age(&mut self.removal_request);
+        OverwriteRequest::age(&mut self.overwrite_request);
     }
     fn do_pending_task(
         &mut self,
@@ -256,6 +263,8 @@ impl PanelState for StageState {
                 tree_options: new_options,
                 page_height: self.page_height,
                 stage_sum: self.stage_sum,
+                removal_request: None,
+                overwrite_request: None,
             });
             CmdResult::NewState { state, message: Some(message) }
         }
@@ -279,6 +288,7 @@ impl PanelState for StageState {
         &mut self,
         pat: InputPattern,
         app_state: &AppState,
+        _screen: Screen,
         _con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
         self.filtered_stage.set_pattern(&app_state.stage, pat);
@@ -493,6 +503,118 @@ impl PanelState for StageState {
                     CmdResult::error("you must select a path to unstage")
                 }
             }
+            Internal::rm | Internal::rm_permanently => {
+                let permanently = internal_exec.internal == Internal::rm_permanently;
+                let paths = app_state.stage.paths_without_descendants();
+                match internal_rm::remove(
+                    paths,
+                    &app_state.root,
+                    &mut self.removal_request,
+                    permanently,
+                ) {
+                    Removal::Done => CmdResult::RefreshState { clear_cache: true },
+                    Removal::Confirm(message) => CmdResult::DisplayError(message),
+                    Removal::Failed(message) => {
+                        app_state.stage.refresh();
+                        CmdResult::error(message)
+                    }
+                }
+            }
+            #[cfg(not(any(target_family = "windows", target_os = "android")))]
+            Internal::chmod => {
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                if let Some(arg) = arg {
+                    let res = crate::permissions::parse_chmod_args(arg).and_then(|(change, recursive)| {
+                        let paths = if recursive {
+                            app_state.stage.paths_without_descendants()
+                        } else {
+                            app_state.stage.paths().to_vec()
+                        };
+                        paths
+                            .iter()
+                            .try_for_each(|path| crate::permissions::change_mode(path, &change, recursive))
+                    });
+                    match res {
+                        Ok(()) => CmdResult::RefreshState { clear_cache: true },
+                        Err(message) => CmdResult::error(message),
+                    }
+                } else {
+                    CmdResult::error("Usage: :chmod [-R] <mode>")
+                }
+            }
+            Internal::cp | Internal::mv => {
+                let kind = if internal_exec.internal == Internal::cp {
+                    TransferKind::Copy
+                } else {
+                    TransferKind::Move
+                };
+                let arg = input_invocation
+                    .and_then(|inv| inv.args.as_ref())
+                    .or(internal_exec.arg.as_ref());
+                if let Some(arg) = arg {
+                    // as for a single selection, relative paths start from the parent
+                    // staged paths inside a staged directory go with it
+                    let paths = app_state.stage.paths_without_descendants()
+                        .into_iter()
+                        .map(|src| {
+                            let dst = path::path_from(&src, PathAnchor::Parent, arg);
+                            (src, dst)
+                        })
+                        .collect();
+                    match internal_cp::start(kind, paths, &app_state.root, kind.internal_name(), &mut self.overwrite_request) {
+                        TransferStart::Started(transfers) => {
+                            let mut res = CmdResult::RefreshState { clear_cache: true };
+                            for mut transfer in transfers {
+                                if let Err(e) = transfer.finish() {
+                                    res = CmdResult::error(format!("Failed to {}: {e}", kind.verb()));
+                                    break;
+                                }
+                            }
+                            // moved paths aren't staged anymore
+                            app_state.stage.refresh();
+                            res
+                        }
+                        TransferStart::Confirm(message) => CmdResult::DisplayError(message),
+                        TransferStart::Failed(message) => CmdResult::error(message),
+                    }
+                } else {
+                    CmdResult::error(format!("Usage: :{} <newpath>", kind.internal_name()))
+                }
+            }
+            Internal::copy_to_panel | Internal::move_to_panel => {
+                let kind = if internal_exec.internal == Internal::copy_to_panel {
+                    TransferKind::Copy
+                } else {
+                    TransferKind::Move
+                };
+                if let Some(other_path) = &app_state.other_panel_path {
+                    let dst = path::closest_dir(other_path);
+                    let paths = app_state.stage.paths_without_descendants()
+                        .into_iter()
+                        .map(|src| (src, dst.clone()))
+                        .collect();
+                    let verb_name = internal_exec.internal.name();
+                    match internal_cp::start(kind, paths, &app_state.root, verb_name, &mut self.overwrite_request) {
+                        TransferStart::Started(transfers) => {
+                            let mut res = CmdResult::RefreshState { clear_cache: true };
+                            for mut transfer in transfers {
+                                if let Err(e) = transfer.finish() {
+                                    res = CmdResult::error(format!("Failed to {}: {e}", kind.verb()));
+                                    break;
+                                }
+                            }
+                            app_state.stage.refresh();
+                            res
+                        }
+                        TransferStart::Confirm(message) => CmdResult::DisplayError(message),
+                        TransferStart::Failed(message) => CmdResult::error(message),
+                    }
+                } else {
+                    CmdResult::error("This verb needs exactly two panels")
+                }
+            }
             _ => self.on_internal_generic(
                 w,
                 internal_exec,
@@ -3,6 +3,7 @@ use {
     crate::{
         app::{AppContext, LineNumber},
         command::{ScrollCommand, move_sel},
+        content_search::{decode_utf16, ContentType},
         display::{Screen, W},
         errors::*,
         pattern::{InputPattern, NameMatch},
@@ -15,14 +16,17 @@ use {
         QueueableCommand,
     },
     memmap2::Mmap,
-    once_cell::sync::Lazy,
     std::{
         fs::File,
-        io::{BufRead, BufReader},
+        io::{self, BufRead, BufReader, Cursor, Read},
         path::{Path, PathBuf},
         str,
+        time::{Duration, Instant},
+    },
+    syntect::{
+        easy::HighlightLines,
+        highlighting::Style,
     },
-    syntect::highlighting::Style,
     termimad::{Area, CropWriter, SPACE_FILLING},
 };
 
@@ -38,6 +42,20 @@ pub struct Region {
 /// file.
 const MAX_SIZE_FOR_STYLING: u64 = 2_000_000;
 
+/// a line longer than that (probably a minified file) isn't styled,
+/// and neither are the following ones
+const MAX_LINE_LEN_FOR_STYLING: usize = 5_000;
+
+/// when styling a line takes longer than that, the following ones
+/// aren't styled, so that pathological files don't freeze broot
+const MAX_LINE_STYLING_DURATION: Duration = Duration::from_millis(50);
+
+/// when there's no pattern, only the start of the file is read,
+/// so that previewing a huge log is fast.
+/// Filtering goes through the whole file, except for UTF-16 files
+/// which are decoded in memory.
+const MAX_SIZE_UNFILTERED: u64 = 20_000_000;
+
 impl Region {
     pub fn from_syntect(region: &(Style, &str)) -> Self {
         let fg = Color::Rgb {
@@ -53,9 +71,9 @@ impl Region {
 #[derive(Debug)]
 pub struct Line {
     pub number: LineNumber,   // starting at 1
-    pub start: usize,         // offset in the file, in bytes
+    pub start: usize,         // offset in the file (or decoded text), in bytes
     pub len: usize,           // len in bytes
-    pub regions: Vec<Region>, // not always computed
+    pub regions: Vec<Region>, // computed when the line is first displayed, if styled
     pub name_match: Option<NameMatch>,
 }
 
@@ -67,6 +85,10 @@ pub struct SyntacticView {
     page_height: usize,
     selection_idx: Option<usize>, // index in lines of the selection, if any
     total_lines_count: usize,     // including lines not filtered out
+    truncated: bool,              // whether the end of the file wasn't read
+    decoded: Option<String>,      // the text, when the file isn't UTF-8 but UTF-16
+    highlighter: Option<HighlightLines<'static>>, // None when not styled (any more)
+    highlighted_count: usize,     // number of lines whose regions were computed
 }
 
 impl SyntacticView {
@@ -89,6 +111,10 @@ impl SyntacticView {
             page_height: 0,
             selection_idx: None,
             total_lines_count: 0,
+            truncated: false,
+            decoded: None,
+            highlighter: None,
+            highlighted_count: 0,
         };
         if sv.read_lines(dam, con, no_style)? {
             sv.select_first();
@@ -118,25 +144,54 @@ impl SyntacticView {
         if md.len() == 0 {
             return Err(ProgramError::ZeroLenFile);
         }
-        let with_style = !no_style && md.len() < MAX_SIZE_FOR_STYLING;
-        let mut reader = BufReader::new(f);
+        let with_style = !no_style
+            && con.syntax_highlighting
+            && md.len() < MAX_SIZE_FOR_STYLING;
+        let content_type = ContentType::of_file(&self.path)?;
+        let is_utf16 = matches!(content_type, ContentType::Utf16 { .. });
+        let max_size = if self.pattern.is_none() || is_utf16 {
+            MAX_SIZE_UNFILTERED
+        } else {
+            u64::MAX
+        };
+        self.truncated = md.len() > max_size;
+        let mut reader: Box<dyn BufRead + '_> = match content_type {
+            ContentType::Binary => {
+                return Err(ProgramError::UnprintableFile);
+            }
+            ContentType::Utf16 { big_endian } => {
+                // the text is decoded in memory, and the lines will be
+                // read from there instead of from the file
+                let mut bytes = Vec::new();
+                f.take(max_size).read_to_end(&mut bytes)?;
+                let text = self.decoded.insert(decode_utf16(&bytes, big_endian));
+                Box::new(Cursor::new(text.as_bytes()))
+            }
+            ContentType::Text => {
+                self.decoded = None;
+                Box::new(BufReader::new(f.take(max_size)))
+            }
+        };
         self.lines.clear();
-        let mut line = String::new();
+        let mut bytes = Vec::new();
         self.total_lines_count = 0;
         let mut offset = 0;
         let mut number = 0;
-        static SYNTAXER: Lazy<Syntaxer> = Lazy::new(Syntaxer::default);
-        let mut highlighter = if with_style {
+        // lines are styled only when displayed
+        self.highlighter = if with_style {
             SYNTAXER.highlighter_for(&self.path, con)
         } else {
             None
         };
+        self.highlighted_count = 0;
         let pattern = &self.pattern.pattern;
-        while reader.read_line(&mut line)? > 0 {
+        while reader.read_until(b'\n', &mut bytes)? > 0 {
             number += 1;
             self.total_lines_count += 1;
             let start = offset;
-            offset += line.len();
+            offset += bytes.len();
+            // invalid UTF-8 sequences are displayed as replacement chars
+            let line = String::from_utf8_lossy(&bytes);
             for c in line.chars() {
                 if !is_char_printable(c) {
                     debug!("unprintable char: {:?}", c);
@@ -148,25 +203,15 @@ impl SyntacticView {
             // Those chars are removed on printing
             if pattern.is_empty() || pattern.score_of_string(&line).is_some() {
                 let name_match = pattern.search_string(&line);
-                let regions = if let Some(highlighter) = highlighter.as_mut() {
-                    highlighter
-                        .highlight(&line, &SYNTAXER.syntax_set)
-                        .map_err(|e| ProgramError::SyntectCrashed { details: e.to_string() })?
-                        .iter()
-                        .map(Region::from_syntect)
-                        .collect()
-                } else {
-                    Vec::new()
-                };
                 self.lines.push(Line {
-                    regions,
+                    regions: Vec::new(),
                     start,
-                    len: line.len(),
+                    len: bytes.len(),
                     name_match,
                     number,
                 });
             }
-            line.clear();
+            bytes.clear();
             if dam.has_event() {
                 info!("event interrupted preview filtering");
                 return Ok(false);
@@ -207,17 +252,74 @@ impl SyntacticView {
     pub fn get_selected_line(&self) -> Option<String> {
         self.selection_idx
             .and_then(|idx| self.lines.get(idx))
-            .and_then(|line| {
-                File::open(&self.path)
-                    .and_then(|file| unsafe { Mmap::map(&file) })
-                    .ok()
-                    .filter(|mmap| mmap.len() >= line.start + line.len)
-                    .and_then(|mmap| {
-                        String::from_utf8(
-                            (mmap[line.start..line.start + line.len]).to_vec(),
-                        ).ok()
-                    })
-            })
+            .and_then(|line| self.line_content(line, &mut None).ok().flatten())
+    }
+
+    /// Compute the syntax coloring of the lines before the given
+    /// index, if not already done.
+    ///
+    /// Lines are styled in order, as the styling of a line depends on
+    /// the previous ones. Styling is abandoned for the rest of the file
+    /// when a line is too long or too slow to style.
+    fn highlight_up_to(&mut self, end: usize) {
+        let end = end.min(self.lines.len());
+        if self.highlighted_count >= end {
+            return;
+        }
+        let Some(mut highlighter) = self.highlighter.take() else {
+            return;
+        };
+        let mut op_mmap = None;
+        while self.highlighted_count < end {
+            let line = &self.lines[self.highlighted_count];
+            if line.len > MAX_LINE_LEN_FOR_STYLING {
+                info!("line {} too long to be styled", line.number);
+                return;
+            }
+            let Ok(Some(content)) = self.line_content(line, &mut op_mmap) else {
+                warn!("file changed since parsing");
+                return;
+            };
+            let start = Instant::now();
+            let regions = match highlighter.highlight(&content, &SYNTAXER.syntax_set) {
+                Ok(regions) => regions.iter().map(Region::from_syntect).collect(),
+                Err(e) => {
+                    warn!("syntect crashed with message : {e:?}");
+                    return;
+                }
+            };
+            let duration = start.elapsed();
+            if duration > MAX_LINE_STYLING_DURATION {
+                info!("line {} took {:?} to be styled", line.number, duration);
+                return;
+            }
+            self.lines[self.highlighted_count].regions = regions;
+            self.highlighted_count += 1;
+        }
+        self.highlighter = Some(highlighter);
+    }
+
+    /// Read the content of a line, in the decoded text or in the
+    /// file. Return None if the file was truncated since parsing
+    fn line_content(
+        &self,
+        line: &Line,
+        op_mmap: &mut Option<Mmap>,
+    ) -> io::Result<Option<String>> {
+        let end = line.start + line.len;
+        if let Some(text) = &self.decoded {
+            return Ok(text.get(line.start..end).map(str::to_string));
+        }
+        if op_mmap.is_none() {
+            let file = File::open(&self.path)?;
+            *op_mmap = Some(unsafe { Mmap::map(&file)? });
+        }
+        let mmap = op_mmap.as_ref().unwrap();
+        if mmap.len() < end {
+            return Ok(None);
+        }
+        // we copy the memmap slice, as it's not immutable
+        Ok(Some(String::from_utf8_lossy(&mmap[line.start..end]).to_string()))
     }
 
     pub fn get_selected_line_number(&self) -> Option<LineNumber> {
@@ -311,6 +413,7 @@ impl SyntacticView {
             self.page_height = area.height as usize;
             self.ensure_selection_is_visible();
         }
+        self.highlight_up_to(self.scroll + area.height as usize);
         let max_number_len = self.lines.last().map_or(0, |l|l.number).to_string().len();
         let show_line_number = area.width > 55 || ( self.pattern.is_some() && area.width > 8 );
         let line_count = area.height as usize;
@@ -340,25 +443,14 @@ impl SyntacticView {
                 let mut regions = &line.regions;
                 let regions_ur;
                 if regions.is_empty() && line.len > 0 {
-                    if op_mmap.is_none() {
-                        let file = File::open(&self.path)?;
-                        let mmap = unsafe { Mmap::map(&file)? };
-                        op_mmap = Some(mmap);
-                    }
-                    if op_mmap.as_ref().unwrap().len() < line.start + line.len {
-                        warn!("file truncated since parsing");
-                    } else {
-                        // an UTF8 error can only happen if file modified during display
-                        let string = String::from_utf8(
-                            // we copy the memmap slice, as it's not immutable
-                            (op_mmap.unwrap()[line.start..line.start + line.len]).to_vec(),
-                        )
-                        .unwrap_or_else(|_| "Bad UTF8".to_string());
+                    if let Some(string) = self.line_content(line, &mut op_mmap)? {
                         regions_ur = vec![Region {
                             fg: normal_fg,
                             string,
                         }];
                         regions = &regions_ur;
+                    } else {
+                        warn!("file truncated since parsing");
                     }
                 }
                 cw.w.queue(SetBackgroundColor(bg))?;
@@ -429,6 +521,9 @@ impl SyntacticView {
         let width = area.width as usize;
         let mut s = if self.pattern.is_some() {
             format!("{}/{}", self.lines.len(), self.total_lines_count)
+        } else if self.truncated {
+            // the count is the one of the lines read
+            format!("{}+", self.total_lines_count)
         } else {
             format!("{}", self.total_lines_count)
         };
@@ -1,6 +1,6 @@
 use {
     crossbeam::channel::{self, bounded, select, Receiver},
-    std::thread,
+    std::{thread, time::Duration},
     termimad::TimedEvent,
 };
 
@@ -166,6 +166,39 @@ impl Dam {
             }
         }
     }
+
+    /// like `next` but return None when nothing comes before the timeout
+    pub fn next_before<T>(
+        &mut self,
+        other: &Receiver<T>,
+        timeout: Duration,
+    ) -> Option<Either<Option<TimedEvent>, Option<T>>> {
+        if self.in_dam.is_some() {
+            return Some(Either::First(self.in_dam.take()));
+        }
+        select! {
+            recv(self.receiver) -> event => Some(Either::First(event.ok())),
+            recv(other) -> o => Some(Either::Second(o.ok())),
+            default(timeout) => None,
+        }
+    }
+
+    /// like `next` but return None when a message comes first
+    /// on the signal receiver
+    pub fn next_unless<T, S>(
+        &mut self,
+        other: &Receiver<T>,
+        signal: &Receiver<S>,
+    ) -> Option<Either<Option<TimedEvent>, Option<T>>> {
+        if self.in_dam.is_some() {
+            return Some(Either::First(self.in_dam.take()));
+        }
+        select! {
+            recv(self.receiver) -> event => Some(Either::First(event.ok())),
+            recv(other) -> o => Some(Either::Second(o.ok())),
+            recv(signal) -> _ => None,
+        }
+    }
 }
 
 pub struct DamObserver {
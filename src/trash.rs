@@ -0,0 +1,167 @@
+//! Move files to the trash instead of deleting them.
+//!
+//! The home trash of the freedesktop.org specification
+//! (https://specifications.freedesktop.org/trash-spec/trashspec-latest.html)
+//! is used on unix systems, apart macOS. Files which are on another
+//! device than the trash are copied to it, then removed.
+//! There's no trash support on other systems.
+
+use std::{
+    io,
+    path::Path,
+};
+
+/// Tell whether files can be moved to a trash on this system
+pub fn is_supported() -> bool {
+    cfg!(all(unix, not(any(target_os = "macos", target_os = "ios", target_os = "android"))))
+}
+
+/// Move the file or directory (with its content) to the trash
+pub fn trash(path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    if is_supported() {
+        let trash_dir = directories::BaseDirs::new()
+            .map(|dirs| dirs.data_dir().join("Trash"))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+        return freedesktop::move_to_trash(path, &trash_dir);
+    }
+    Err(io::Error::new(io::ErrorKind::Other, "no trash on this system"))
+}
+
+#[cfg(unix)]
+mod freedesktop {
+
+    use {
+        crate::verb::internal_cp::move_path,
+        chrono::{DateTime, Local},
+        std::{
+            ffi::{OsStr, OsString},
+            fs::{self, DirBuilder, File, OpenOptions},
+            io::{self, Write},
+            os::unix::{
+                ffi::OsStrExt,
+                fs::DirBuilderExt,
+            },
+            path::Path,
+        },
+    };
+
+    /// Move the file or directory into the trash directory, with
+    /// the trashinfo file allowing its restoration
+    pub fn move_to_trash(path: &Path, trash_dir: &Path) -> io::Result<()> {
+        if !path.is_absolute() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "path isn't absolute"));
+        }
+        let name = path.file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path has no name"))?;
+        let files_dir = trash_dir.join("files");
+        let info_dir = trash_dir.join("info");
+        for dir in [&files_dir, &info_dir] {
+            DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+        }
+        let (trashed_name, mut info_file) = reserve_name(name, &files_dir, &info_dir)?;
+        let res = info_file
+            .write_all(trash_info(path, Local::now()).as_bytes())
+            .and_then(|_| move_path(path, &files_dir.join(&trashed_name)));
+        if res.is_err() {
+            let _ = fs::remove_file(info_dir.join(info_file_name(&trashed_name)));
+        }
+        res
+    }
+
+    /// Find a name which is free in the files and info directories
+    /// of the trash, and reserve it by creating the trashinfo file
+    fn reserve_name(
+        name: &OsStr,
+        files_dir: &Path,
+        info_dir: &Path,
+    ) -> io::Result<(OsString, File)> {
+        for i in 1..10_000 {
+            let candidate = numbered_name(name, i);
+            if files_dir.join(&candidate).symlink_metadata().is_ok() {
+                continue;
+            }
+            let info_path = info_dir.join(info_file_name(&candidate));
+            match OpenOptions::new().write(true).create_new(true).open(info_path) {
+                Ok(file) => return Ok((candidate, file)),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(io::Error::new(io::ErrorKind::Other, "too many trashed files with this name"))
+    }
+
+    fn info_file_name(trashed_name: &OsStr) -> OsString {
+        let mut info_name = trashed_name.to_owned();
+        info_name.push(".trashinfo");
+        info_name
+    }
+
+    /// `notes.md`, then `notes.2.md`, `notes.3.md`, etc.
+    fn numbered_name(name: &OsStr, i: usize) -> OsString {
+        if i == 1 {
+            return name.to_owned();
+        }
+        let path = Path::new(name);
+        match (path.file_stem(), path.extension()) {
+            (Some(stem), Some(ext)) => {
+                let mut numbered = stem.to_owned();
+                numbered.push(format!(".{i}."));
+                numbered.push(ext);
+                numbered
+            }
+            _ => {
+                let mut numbered = name.to_owned();
+                numbered.push(format!(".{i}"));
+                numbered
+            }
+        }
+    }
+
+    fn trash_info(path: &Path, date: DateTime<Local>) -> String {
+        format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            url_escape(path),
+            date.format("%Y-%m-%dT%H:%M:%S"),
+        )
+    }
+
+    /// Percent-encode the bytes of the path, apart the
+    /// unreserved chars and the slashes
+    fn url_escape(path: &Path) -> String {
+        let mut escaped = String::new();
+        for &b in path.as_os_str().as_bytes() {
+            if b.is_ascii_alphanumeric() || b"-_.~/".contains(&b) {
+                escaped.push(b as char);
+            } else {
+                escaped.push_str(&format!("%{b:02X}"));
+            }
+        }
+        escaped
+    }
+
+    #[test]
+    fn test_move_to_trash() {
+        let dir = tempfile::tempdir().unwrap();
+        let trash_dir = dir.path().join("Trash");
+        let file = dir.path().join("some notes.md");
+        for content in ["first", "second"] {
+            fs::write(&file, content).unwrap();
+            move_to_trash(&file, &trash_dir).unwrap();
+            assert!(!file.exists());
+        }
+        let files_dir = trash_dir.join("files");
+        assert_eq!(fs::read_to_string(files_dir.join("some notes.md")).unwrap(), "first");
+        assert_eq!(fs::read_to_string(files_dir.join("some notes.2.md")).unwrap(), "second");
+        let info = fs::read_to_string(trash_dir.join("info/some notes.2.md.trashinfo")).unwrap();
+        let expected_path = format!("Path={}/some%20notes.md\n", url_escape(dir.path()));
+        assert!(info.starts_with("[Trash Info]\n"));
+        assert!(info.contains(&expected_path));
+        assert!(info.contains("DeletionDate="));
+    }
+
+    #[test]
+    fn test_url_escape() {
+        assert_eq!(url_escape(Path::new("/home/dys/a b/été#1.txt")), "/home/dys/a%20b/%C3%A9t%C3%A9%231.txt");
+    }
+}
@@ -0,0 +1,29 @@
+use {
+    fnv::FnvHashMap,
+    std::path::{Path, PathBuf},
+};
+
+/// The directories the user explicitly expanded or collapsed,
+/// overriding the choice of the tree builder which opens
+/// directories only while there's room on screen
+#[derive(Debug, Clone, Default)]
+pub struct Expansions {
+    dirs: FnvHashMap<PathBuf, bool>, // true when expanded, false when collapsed
+}
+
+impl Expansions {
+    pub fn is_empty(&self) -> bool {
+        self.dirs.is_empty()
+    }
+    /// return Some(true) when the directory must have all its children
+    /// listed, Some(false) when none must be, None when it's the builder's call
+    pub fn get(&self, path: &Path) -> Option<bool> {
+        self.dirs.get(path).copied()
+    }
+    pub fn is_expanded(&self, path: &Path) -> bool {
+        self.get(path) == Some(true)
+    }
+    pub fn set(&mut self, path: PathBuf, expanded: bool) {
+        self.dirs.insert(path, expanded);
+    }
+}
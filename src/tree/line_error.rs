@@ -0,0 +1,100 @@
+use std::{
+    io,
+    path::Path,
+};
+
+/// What went wrong when reading the entry of a tree line.
+///
+/// Lines with an error are still displayed, this is only
+/// used to tell the user why they can't be entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineError {
+    BrokenLink,
+    LinkLoop, // a link to a directory which is already an ancestor
+    PermissionDenied,
+    UnreadableArchive, // an archive whose content couldn't be listed
+    Other,
+}
+
+impl LineError {
+    pub fn from_io(err: &io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            _ => Self::Other,
+        }
+    }
+    /// check that the current user may read the file, or list the
+    /// directory, and return the error when they can't
+    #[cfg(unix)]
+    pub fn check_access(path: &Path, is_dir: bool) -> Option<Self> {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+        let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mode = if is_dir { libc::R_OK | libc::X_OK } else { libc::R_OK };
+        if unsafe { libc::access(c_path.as_ptr(), mode) } == 0 {
+            None
+        } else {
+            Some(Self::from_io(&io::Error::last_os_error()))
+        }
+    }
+    #[cfg(not(unix))]
+    pub fn check_access(_path: &Path, _is_dir: bool) -> Option<Self> {
+        None
+    }
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::BrokenLink => "broken link",
+            Self::LinkLoop => "link to an ancestor, not followed",
+            Self::PermissionDenied => "permission denied",
+            Self::UnreadableArchive => "unreadable or unsupported archive",
+            Self::Other => "unreadable",
+        }
+    }
+}
+
+#[test]
+fn test_line_error_from_io() {
+    let err = io::Error::from(io::ErrorKind::PermissionDenied);
+    assert_eq!(LineError::from_io(&err), LineError::PermissionDenied);
+    let err = io::Error::from(io::ErrorKind::NotFound);
+    assert_eq!(LineError::from_io(&err), LineError::Other);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dangling_link_is_broken() {
+    use super::TreeLineType;
+    let dir = tempfile::tempdir().unwrap();
+    let link = dir.path().join("link");
+    std::os::unix::fs::symlink(dir.path().join("missing"), &link).unwrap();
+    let ft = std::fs::symlink_metadata(&link).unwrap().file_type();
+    assert!(matches!(TreeLineType::new(&link, &ft), TreeLineType::BrokenSymLink(_)));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unreadable_file_and_dir() {
+    use {
+        super::{test_util::build_tree, TreeOptions},
+        std::{fs, os::unix::fs::PermissionsExt},
+    };
+    if unsafe { libc::geteuid() } == 0 {
+        return; // root can read everything
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("file"), "").unwrap();
+    fs::create_dir(root.join("dir")).unwrap();
+    fs::write(root.join("dir/child"), "").unwrap();
+    fs::set_permissions(root.join("file"), fs::Permissions::from_mode(0o000)).unwrap();
+    fs::set_permissions(root.join("dir"), fs::Permissions::from_mode(0o000)).unwrap();
+    let tree = build_tree(root, TreeOptions::default(), 50);
+    // the dir must be made readable again for the tempdir to be removed
+    fs::set_permissions(root.join("dir"), fs::Permissions::from_mode(0o755)).unwrap();
+    let error = |name: &str| {
+        tree.lines.iter()
+            .find(|line| line.path == root.join(name))
+            .and_then(|line| line.error)
+    };
+    assert_eq!(error("file"), Some(LineError::PermissionDenied));
+    assert_eq!(error("dir"), Some(LineError::PermissionDenied));
+}
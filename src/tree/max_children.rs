@@ -0,0 +1,94 @@
+use {
+    crate::errors::ConfError,
+    serde::{Deserialize, Deserializer},
+    std::str::FromStr,
+};
+
+/// Whatever the setting, no more than this number of children are
+/// kept in memory for a directory. The other ones are only counted.
+pub const HARD_MAX_CHILDREN: usize = 100_000;
+
+/// The smallest limit in auto mode, so that tiny panels
+/// still show a few children of every directory
+const MIN_AUTO_MAX_CHILDREN: usize = 5;
+
+/// How many children of a directory may be listed before the
+/// other ones are summarized in a "N unlisted" line.
+///
+/// This doesn't apply when there's a search pattern, nor to
+/// directories explicitly expanded by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaxChildren {
+    /// as many as fit in the panel
+    #[default]
+    Unlimited,
+    /// a limit depending on the height of the panel
+    Auto,
+    Count(usize),
+}
+
+impl MaxChildren {
+    /// return the max number of children of a directory in a tree
+    /// targeting the given number of lines, None when there's no limit
+    pub fn limit(self, targeted_size: usize) -> Option<usize> {
+        match self {
+            Self::Unlimited => None,
+            Self::Auto => Some((targeted_size / 2).max(MIN_AUTO_MAX_CHILDREN)),
+            Self::Count(n) => Some(n),
+        }
+    }
+}
+
+impl From<usize> for MaxChildren {
+    fn from(n: usize) -> Self {
+        if n == 0 {
+            Self::Unlimited
+        } else {
+            Self::Count(n)
+        }
+    }
+}
+
+impl FromStr for MaxChildren {
+    type Err = ConfError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "auto" => Ok(Self::Auto),
+            "unlimited" => Ok(Self::Unlimited),
+            s => s.parse::<usize>()
+                .map(Self::from)
+                .map_err(|_| ConfError::InvalidMaxChildren { raw: s.to_string() }),
+        }
+    }
+}
+
+/// in the configuration, the value can be a number or a string
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MaxChildrenConf {
+    Count(usize),
+    Name(String),
+}
+
+impl<'de> Deserialize<'de> for MaxChildren {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        match MaxChildrenConf::deserialize(deserializer)? {
+            MaxChildrenConf::Count(n) => Ok(Self::from(n)),
+            MaxChildrenConf::Name(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[test]
+fn test_parse_max_children() {
+    assert_eq!("auto".parse::<MaxChildren>().unwrap(), MaxChildren::Auto);
+    assert_eq!("unlimited".parse::<MaxChildren>().unwrap(), MaxChildren::Unlimited);
+    assert_eq!("0".parse::<MaxChildren>().unwrap(), MaxChildren::Unlimited);
+    assert_eq!(" 200".parse::<MaxChildren>().unwrap(), MaxChildren::Count(200));
+    assert!("many".parse::<MaxChildren>().is_err());
+    assert_eq!(MaxChildren::Auto.limit(40), Some(20));
+    assert_eq!(MaxChildren::Auto.limit(6), Some(5));
+    assert_eq!(MaxChildren::Unlimited.limit(40), None);
+}
@@ -1,14 +1,28 @@
 
+mod expansions;
+mod line_error;
+mod max_children;
+mod natural_order;
 mod sort;
+#[cfg(test)]
+pub mod test_util;
 mod tree;
+mod tree_json;
 mod tree_line;
 mod tree_line_type;
 mod tree_options;
+mod tree_stats;
 
 pub use {
+    expansions::Expansions,
+    line_error::LineError,
+    max_children::{HARD_MAX_CHILDREN, MaxChildren},
+    natural_order::natural_cmp,
     sort::Sort,
     tree::Tree,
+    tree_json::JsonNode,
     tree_line::TreeLine,
     tree_line_type::TreeLineType,
-    tree_options::TreeOptions,
+    tree_options::{COMPACT_DATE_TIME_FORMAT, TreeOptions},
+    tree_stats::TreeStats,
 };
@@ -0,0 +1,78 @@
+use std::{
+    cmp::Ordering,
+    iter::Peekable,
+    str::Chars,
+};
+
+/// Compare two names the way a human would expect them to be sorted:
+/// case insensitively, and with digit runs compared as numbers
+/// (so that "file2" comes before "file10").
+///
+/// When two numbers have the same value ("2" and "002"), the one
+/// with less leading zeros comes first, unless the rest of the
+/// names differ.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    let mut zeros_ordering = Ordering::Equal;
+    loop {
+        let (ca, cb) = match (a.peek(), b.peek()) {
+            (None, None) => {
+                return zeros_ordering;
+            }
+            (None, Some(_)) => {
+                return Ordering::Less;
+            }
+            (Some(_), None) => {
+                return Ordering::Greater;
+            }
+            (Some(&ca), Some(&cb)) => (ca, cb),
+        };
+        if ca.is_ascii_digit() && cb.is_ascii_digit() {
+            let da = take_digits(&mut a);
+            let db = take_digits(&mut b);
+            let na = da.trim_start_matches('0');
+            let nb = db.trim_start_matches('0');
+            let ordering = na.len().cmp(&nb.len()).then_with(|| na.cmp(nb));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            if zeros_ordering == Ordering::Equal {
+                zeros_ordering = da.len().cmp(&db.len());
+            }
+        } else {
+            let ordering = ca.to_lowercase().cmp(cb.to_lowercase());
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            a.next();
+            b.next();
+        }
+    }
+}
+
+fn take_digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        chars.next();
+    }
+    digits
+}
+
+#[test]
+fn test_natural_cmp() {
+    let mut names = vec![
+        "file11", "File2", "file10", "file1", "file002", "file2", "b", "a10b", "a2c", "a2b",
+    ];
+    names.sort_by(|a, b| natural_cmp(a, b));
+    assert_eq!(
+        names,
+        vec![
+            "a2b", "a2c", "a10b", "b", "file1", "File2", "file2", "file002", "file10", "file11",
+        ],
+    );
+}
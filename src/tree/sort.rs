@@ -1,14 +1,16 @@
 
 /// A sort key.
-/// A non None sort mode implies only one level of the tree
-/// is displayed.
+/// Most non None sort modes imply only one level of the tree
+/// is displayed (see `prevent_deep_display`).
 /// When in None mode, paths are alpha sorted
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Sort {
     None,
     Count,
     Date,
+    DateTree, // children of every directory sorted by date
     Size,
+    SizeTree, // children of every directory sorted by size
     TypeDirsFirst,
     TypeDirsLast,
 }
@@ -19,7 +21,9 @@ impl Sort {
             Self::None => false,
             Self::Count => true,
             Self::Date => true,
+            Self::DateTree => false,
             Self::Size => true,
+            Self::SizeTree => false,
             Self::TypeDirsFirst => false,
             Self::TypeDirsLast => false,
         }
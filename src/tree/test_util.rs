@@ -0,0 +1,45 @@
+//! Helpers for the tests needing an application context
+//! or a tree built on a temporary directory
+
+use {
+    super::*,
+    crate::{
+        app::AppContext,
+        cli::Args,
+        conf::Conf,
+        task_sync::Dam,
+        tree_build::TreeBuilder,
+        verb::VerbStore,
+    },
+    clap::Parser,
+    std::path::Path,
+};
+
+/// make an application context for a launch on the given root,
+/// with the default configuration
+pub fn app_context(root: &Path) -> AppContext {
+    let mut conf = Conf::default();
+    let verb_store = VerbStore::new(&mut conf).unwrap();
+    let args = Args::parse_from(["broot", root.to_str().unwrap()]);
+    AppContext::from(args, verb_store, &conf).unwrap()
+}
+
+/// build the tree of the given root, targeting the given height
+pub fn build_tree(root: &Path, options: TreeOptions, height: usize) -> Tree {
+    build_tree_with(&app_context(root), root, options, height, false)
+}
+
+/// build the tree of the given root with an existing context,
+/// optionally looking at all the children (as a total search)
+pub fn build_tree_with(
+    con: &AppContext,
+    root: &Path,
+    options: TreeOptions,
+    height: usize,
+    total_search: bool,
+) -> Tree {
+    TreeBuilder::from(root.to_path_buf(), options, height, con)
+        .unwrap()
+        .build_tree(total_search, &Dam::unlimited())
+        .unwrap()
+}
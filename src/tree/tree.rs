@@ -5,18 +5,31 @@ use {
         errors,
         file_sum::FileSum,
         git::TreeGitStatus,
+        path::normalize_path,
         task_sync::ComputationResult,
         task_sync::Dam,
-        tree_build::{BId, BuildReport, TreeBuilder},
+        tree_build::{BId, BuildReport, SearchContinuation, TreeBuilder},
     },
-    fnv::FnvHashMap,
+    fnv::{FnvHashMap, FnvHashSet},
     std::{
-        cmp::Ord,
+        cmp::{Ord, Ordering, Reverse},
         mem,
         path::{Path, PathBuf},
     },
 };
 
+/// number of lines kept visible before and after the selection
+/// when the tree is scrolled to show it
+const SCROLL_CONTEXT: usize = 2;
+
+/// The part of a line's path used to sort it among its siblings
+struct SortKey {
+    group: u8, // 0 for the directories when they're listed first
+    prefix: String, // depends on the sort mode
+    name: String,
+    index: usize,
+}
+
 /// The tree which may be displayed, with onle line per visible line of the panel.
 ///
 /// In the tree structure, every "node" is just a line, there's
@@ -30,6 +43,9 @@ pub struct Tree {
     pub total_search: bool, // whether the search was made on all children
     pub git_status: ComputationResult<TreeGitStatus>,
     pub build_report: BuildReport,
+    pub gone_count: usize, // number of paths removed since the last refresh
+    pub stats: TreeStats, // updated when lines are added or removed
+    pub search_continuation: Option<Box<SearchContinuation>>, // when the search wasn't total
 }
 
 impl Tree {
@@ -55,22 +71,79 @@ impl Tree {
                        // we save the old selection to try restore it
         let selected_path = self.selected_line().path.to_path_buf();
         mem::swap(&mut self.lines, &mut tree.lines);
-        self.scroll = 0;
-        if !self.try_select_path(&selected_path) && self.selection >= self.lines.len() {
+        self.stats = tree.stats;
+        self.search_continuation = tree.search_continuation.take();
+        self.diff_lines(&tree.lines);
+        // the scroll is kept, unless the selection isn't visible anymore
+        if !self.try_select_path_or_ancestor(&selected_path) {
             self.selection = 0;
         }
         self.make_selection_visible(page_height);
         Ok(())
     }
 
+    /// flag the lines which weren't in the previous ones and
+    /// count the paths which don't exist anymore.
+    ///
+    /// To be flagged, a line must be in a directory whose children
+    /// were all read and listed, so that lines which are only newly
+    /// visible (eg because the panel grew) aren't flagged.
+    fn diff_lines(&mut self, previous_lines: &[TreeLine]) {
+        let previous_paths: FnvHashSet<&Path> = previous_lines
+            .iter()
+            .map(|line| line.path.as_path())
+            .collect();
+        let complete_dirs: FnvHashSet<&Path> = previous_lines
+            .iter()
+            .filter(|line| line.children_read && line.unlisted == 0)
+            .map(|line| line.path.as_path())
+            .collect();
+        for line in self.lines.iter_mut() {
+            line.is_new = line.is_selectable()
+                && !previous_paths.contains(line.path.as_path())
+                && line.path.parent().map_or(false, |dir| complete_dirs.contains(dir));
+        }
+        let paths: FnvHashSet<&Path> = self.lines
+            .iter()
+            .map(|line| line.path.as_path())
+            .collect();
+        self.gone_count = previous_lines
+            .iter()
+            .filter(|line| line.is_selectable())
+            .filter(|line| !paths.contains(line.path.as_path()) && !line.path.exists())
+            .count();
+    }
+    /// return the number of lines flagged as new by the last refresh
+    pub fn new_count(&self) -> usize {
+        self.lines.iter().filter(|line| line.is_new).count()
+    }
+    /// forget the changes found by the last refresh
+    pub fn clear_changes(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.is_new = false;
+        }
+        self.gone_count = 0;
+    }
+
     /// do what must be done after line additions or removals:
     /// - sort the lines
     /// - compute left branches
+    /// - update the stats
     pub fn after_lines_changed(&mut self) {
+        self.sort_lines_and_draw_branches();
+        self.stats = TreeStats::from_lines(&self.lines);
+        if self.options.needs_sum() {
+            time!("fetch_file_sum", self.fetch_regular_file_sums()); // not the dirs, only simple files
+            self.sort_siblings(); // does nothing when sort mode is None
+        }
+    }
 
+    /// order the lines according to the tree structure and the sort
+    /// mode, then compute the branches
+    fn sort_lines_and_draw_branches(&mut self) {
         // we need to order the lines to build the tree.
         // It's a little complicated because
-        //  - we want a case insensitive sort
+        //  - we want a case insensitive (and usually natural) sort
         //  - we still don't want to confuse the children of AA and Aa
         //  - a node can come from a not parent node, when we followed a link
         let mut bid_parents: FnvHashMap<BId, BId> = FnvHashMap::default();
@@ -81,14 +154,15 @@ impl Tree {
             }
             bid_lines.insert(line.bid, line);
         }
-        let mut sort_paths: FnvHashMap<BId, String> = FnvHashMap::default();
+        // for every line, the sort keys of its ancestors and itself, from the root
+        let mut sort_paths: FnvHashMap<BId, Vec<SortKey>> = FnvHashMap::default();
         for line in self.lines[1..].iter() {
-            let mut sort_path = String::new();
+            let mut sort_path = Vec::new();
             let mut bid = line.bid;
             while let Some(l) = bid_lines.get(&bid) {
-                let lower_name = l.path.file_name().map_or(
+                let name = l.path.file_name().map_or(
                     "".to_string(),
-                    |name| name.to_string_lossy().to_lowercase(),
+                    |name| name.to_string_lossy().to_string(),
                 );
                 let sort_prefix = match self.options.sort {
                     Sort::TypeDirsFirst => {
@@ -96,33 +170,83 @@ impl Tree {
                             "              "
                         } else {
                             l.path.extension().and_then(|s| s.to_str()).unwrap_or("")
-                        }
+                        }.to_string()
                     }
                     Sort::TypeDirsLast => {
                         if l.is_dir() {
                             "~~~~~~~~~~~~~~"
                         } else {
                             l.path.extension().and_then(|s| s.to_str()).unwrap_or("")
+                        }.to_string()
+                    }
+                    Sort::DateTree => {
+                        // most recent first, then the ones whose date is unknown
+                        // (they're sorted by name), and the pruning line last
+                        if l.line_type == TreeLineType::Pruning {
+                            "\u{FFFF}".to_string()
+                        } else if let Some(seconds) = l.sum
+                            .and_then(|sum| sum.to_valid_seconds())
+                            .or_else(|| l.modified_seconds())
+                        {
+                            format!("{:020}", i64::MAX - seconds)
+                        } else {
+                            "~".to_string()
                         }
                     }
-                    _ => { "" }
+                    Sort::SizeTree => {
+                        // biggest first, then not yet measured, and the
+                        // pruning line must stay the last one
+                        if l.line_type == TreeLineType::Pruning {
+                            "\u{FFFF}".to_string()
+                        } else if let Some(sum) = l.sum {
+                            format!("{:020}", u64::MAX - sum.to_size())
+                        } else {
+                            "~".to_string()
+                        }
+                    }
+                    _ => { String::new() }
                 };
-                sort_path = format!(
-                    "{}{}-{}/{}",
-                    sort_prefix,
-                    lower_name,
-                    bid.index(), // to be sure to separate paths having the same lowercase
-                    sort_path,
-                );
+                // when grouping directories first, the pruning line
+                // must still be the last one
+                let group = if !self.options.dirs_first {
+                    0
+                } else if l.line_type == TreeLineType::Pruning {
+                    2
+                } else if l.is_dir() {
+                    0
+                } else {
+                    1
+                };
+                sort_path.push(SortKey {
+                    group,
+                    prefix: sort_prefix,
+                    name,
+                    index: bid.index(), // to be sure to separate paths having the same lowercase
+                });
                 if let Some(&parent_bid) = bid_parents.get(&bid) {
                     bid = parent_bid;
                 } else {
                     break;
                 }
             }
+            sort_path.reverse();
             sort_paths.insert(line.bid, sort_path);
         }
-        self.lines[1..].sort_by_key(|line| sort_paths.get(&line.bid).unwrap());
+        let options = &self.options;
+        self.lines[1..].sort_by(|a, b| {
+            let a = sort_paths.get(&a.bid).unwrap();
+            let b = sort_paths.get(&b.bid).unwrap();
+            for (ka, kb) in a.iter().zip(b.iter()) {
+                let ordering = ka.group.cmp(&kb.group)
+                    .then_with(|| ka.prefix.cmp(&kb.prefix))
+                    .then_with(|| options.cmp_names(&ka.name, &kb.name))
+                    .then(ka.index.cmp(&kb.index));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            a.len().cmp(&b.len())
+        });
 
         let mut best_index = 0; // index of the line with the best score
         for i in 1..self.lines.len() {
@@ -135,26 +259,20 @@ impl Tree {
         }
         // then we discover the branches (for the drawing)
         // and we mark the last children as pruning, if they have unlisted brothers
+        let bid_indexes: FnvHashMap<BId, usize> = self.lines
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| (line.bid, idx))
+            .collect();
         let mut last_parent_index: usize = self.lines.len() + 1;
         for end_index in (1..self.lines.len()).rev() {
             let depth = (self.lines[end_index].depth - 1) as usize;
             let start_index = {
                 let parent_index = match self.lines[end_index].parent_bid {
-                    Some(parent_bid) => {
-                        let mut index = end_index;
-                        loop {
-                            index -= 1;
-                            if self.lines[index].bid == parent_bid {
-                                break;
-                            }
-                            if index == 0 {
-                                break;
-                            }
-                        }
-                        index
-                    }
+                    Some(parent_bid) => bid_indexes.get(&parent_bid).copied().unwrap_or(0),
                     None => end_index, // Should not happen
                 };
+                self.lines[end_index].parent_index = Some(parent_index);
                 if parent_index != last_parent_index {
                     // the line at end_index is the last listed child of the line at parent_index
                     let unlisted = self.lines[parent_index].unlisted;
@@ -173,14 +291,15 @@ impl Tree {
                 }
                 parent_index + 1
             };
-            for i in start_index..=end_index {
+            // when a later brother already drew the branch, it covers
+            // this line and all the lines above, up to the parent
+            for i in (start_index..=end_index).rev() {
+                if self.lines[i].left_branches[depth] {
+                    break;
+                }
                 self.lines[i].left_branches[depth] = true;
             }
         }
-        if self.options.needs_sum() {
-            time!("fetch_file_sum", self.fetch_regular_file_sums()); // not the dirs, only simple files
-            self.sort_siblings(); // does nothing when sort mode is None
-        }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -200,6 +319,7 @@ impl Tree {
     /// For example the following one if dy is 1.
     pub fn move_selection(&mut self, dy: i32, page_height: usize, cycle: bool) {
         let l = self.lines.len();
+        let initial_selection = self.selection;
         // we find the new line to select
         loop {
             if dy < 0 {
@@ -219,22 +339,17 @@ impl Tree {
                 break;
             }
         }
-        // we adjust the scroll
-        if l > page_height {
-            if self.selection < 3 {
-                self.scroll = 0;
-            } else if self.selection < self.scroll + 3 {
-                self.scroll = self.selection - 3;
-            } else if self.selection + 3 > l {
-                self.scroll = l - page_height;
-            } else if self.selection + 3 > self.scroll + page_height {
-                self.scroll = self.selection + 3 - page_height;
-            }
+        if !self.lines[self.selection].is_selectable() {
+            // without cycling, we may have stopped on a pruning line at an end
+            self.selection = initial_selection;
         }
+        self.make_selection_visible(page_height);
     }
 
     /// Scroll the desired amount and return true, or return false if it's
-    /// already at end or the tree fits the page
+    /// already at end or the tree fits the page.
+    ///
+    /// The selection isn't moved, it may end out of the view.
     pub fn try_scroll(&mut self, dy: i32, page_height: usize) -> bool {
         if self.lines.len() <= page_height {
             return false;
@@ -257,44 +372,84 @@ impl Tree {
             }
             self.scroll = (self.scroll + dy as usize).min(max);
         }
-        self.select_visible_line(page_height);
         true
     }
 
+    /// return the index of the line displayed at the given row of
+    /// the panel (the root line isn't scrolled)
+    pub fn line_index_at_y(&self, y: usize) -> usize {
+        if y == 0 {
+            0
+        } else {
+            y + self.scroll
+        }
+    }
     /// try to select a line by index of visible line
     /// (works if y+scroll falls on a selectable line)
     pub fn try_select_y(&mut self, y: usize) -> bool {
-        let y = y + self.scroll;
+        let y = self.line_index_at_y(y);
         if y < self.lines.len() && self.lines[y].is_selectable() {
             self.selection = y;
             return true;
         }
         false
     }
-    /// fix the selection so that it's a selectable visible line
-    fn select_visible_line(&mut self, page_height: usize) {
-        if self.selection < self.scroll || self.selection >= self.scroll + page_height {
-            self.selection = self.scroll;
-            let l = self.lines.len();
-            loop {
-                self.selection = (self.selection + l + 1) % l;
-                if self.lines[self.selection].is_selectable() {
-                    break;
-                }
+    /// try to select a line by its index in the tree (the number
+    /// displayed when line numbers are shown, the root being 0).
+    ///
+    /// An index past the end selects the last line. When the line
+    /// isn't selectable (a pruning line), the nearest selectable
+    /// one is selected, the previous one in case of tie.
+    pub fn try_select_index(&mut self, idx: usize) -> bool {
+        let idx = idx.min(self.lines.len() - 1);
+        let nearest = (0..self.lines.len())
+            .filter(|&i| self.lines[i].is_selectable())
+            .min_by_key(|&i| (i.abs_diff(idx), i > idx));
+        if let Some(i) = nearest {
+            self.selection = i;
+            true
+        } else {
+            false
+        }
+    }
+    /// fix the selection so that it's a selectable visible line,
+    /// the nearest one from the previous selection
+    pub fn select_visible_line(&mut self, page_height: usize) {
+        if self.selection == 0 {
+            return; // the root is always visible
+        }
+        // the visible lines after the root
+        let first = self.scroll + 1;
+        let end = (self.scroll + page_height).min(self.lines.len());
+        if self.selection < first {
+            if let Some(idx) = (first..end).find(|&i| self.lines[i].is_selectable()) {
+                self.selection = idx;
+            }
+        } else if self.selection >= end {
+            if let Some(idx) = (first..end).rev().find(|&i| self.lines[i].is_selectable()) {
+                self.selection = idx;
             }
         }
     }
 
+    /// change the scroll, if needed, so that the selection is
+    /// visible with some lines of context around it.
+    ///
+    /// The root line is always displayed, the scrolled lines are
+    /// the ones after it, from `scroll+1` to `scroll+page_height-1`.
     pub fn make_selection_visible(&mut self, page_height: usize) {
-        if page_height >= self.lines.len() || self.selection < 3 {
+        let l = self.lines.len();
+        if page_height >= l {
             self.scroll = 0;
-        } else if self.selection <= self.scroll {
-            self.scroll = self.selection - 2;
-        } else if self.selection > self.lines.len() - 2 {
-            self.scroll = self.lines.len() - page_height;
-        } else if self.selection >= self.scroll + page_height {
-            self.scroll = self.selection + 2 - page_height;
+            return;
         }
+        let context = SCROLL_CONTEXT.min(page_height.saturating_sub(2) / 2);
+        if self.selection < self.scroll + 1 + context {
+            self.scroll = self.selection.saturating_sub(1 + context);
+        } else if self.selection + context + 1 > self.scroll + page_height {
+            self.scroll = self.selection + context + 1 - page_height;
+        }
+        self.scroll = self.scroll.min(l - page_height);
     }
     pub fn selected_line(&self) -> &TreeLine {
         &self.lines[self.selection]
@@ -302,28 +457,57 @@ impl Tree {
     pub fn root(&self) -> &PathBuf {
         &self.lines[0].path
     }
-    /// select the line with the best matching score
+    /// select the line with the best matching score.
+    ///
+    /// In case of equal scores, we prefer the shallowest line, then
+    /// the shortest name, then the line nearest to the current selection.
     pub fn try_select_best_match(&mut self) {
-        let mut best_score = 0;
+        let current = self.selection;
+        let best = self.lines.iter()
+            .enumerate()
+            .filter(|(_, line)| line.is_selectable() && line.score > 0)
+            .min_by_key(|(idx, line)| (
+                Reverse(line.score),
+                line.depth,
+                line.name.chars().count(),
+                idx.abs_diff(current),
+            ));
+        if let Some((idx, _)) = best {
+            self.selection = idx;
+        }
+    }
+    /// return the number of lines directly matching the pattern and,
+    /// if the selection is one of them, its rank (starting at 1)
+    pub fn count_matches(&self) -> (usize, Option<usize>) {
+        let mut count = 0;
+        let mut rank = None;
         for (idx, line) in self.lines.iter().enumerate() {
-            if !line.is_selectable() {
-                continue;
-            }
-            if best_score > line.score {
-                continue;
-            }
-            if line.score == best_score {
-                // in case of equal scores, we prefer the shortest path
-                if self.lines[idx].depth >= self.lines[self.selection].depth {
-                    continue;
+            if line.direct_match && line.is_selectable() {
+                count += 1;
+                if idx == self.selection {
+                    rank = Some(count);
                 }
             }
-            best_score = line.score;
-            self.selection = idx;
         }
+        (count, rank)
+    }
+    /// tell whether some matches may be missing from the lines, because
+    /// the search was stopped early or the lines were trimmed to fit
+    pub fn may_miss_matches(&self) -> bool {
+        !self.total_search
+            || self.lines.iter().any(|line| line.line_type == TreeLineType::Pruning)
+    }
+    /// tell whether some children of the listed directories aren't
+    /// in the lines, so that a bigger page could show more
+    pub fn has_unlisted(&self) -> bool {
+        self.lines.iter().any(|line| line.unlisted > 0)
     }
-    /// return true when we could select the given path
+    /// return true when we could select the given path.
+    ///
+    /// Paths are compared component-wise after normalization, so
+    /// `a/./b/`, `a/c/../b` and `a/b` are the same path.
     pub fn try_select_path(&mut self, path: &Path) -> bool {
+        let path = normalize_path(path);
         for (idx, line) in self.lines.iter().enumerate() {
             if !line.is_selectable() {
                 continue;
@@ -335,6 +519,101 @@ impl Tree {
         }
         false
     }
+    /// select the given path or, if it's not in the tree, its
+    /// deepest listed ancestor. Return false if none could be selected.
+    pub fn try_select_path_or_ancestor(&mut self, path: &Path) -> bool {
+        let path = normalize_path(path);
+        path.ancestors().any(|path| self.try_select_path(path))
+    }
+    /// select the parent of the selected line, if any
+    pub fn select_parent(&mut self) {
+        if let Some(parent_index) = self.selected_line().parent_index {
+            self.selection = parent_index;
+        }
+    }
+    /// select the next line having the same parent than the selected
+    /// one. Return false, without changing the selection, if there's none.
+    pub fn select_next_sibling(&mut self) -> bool {
+        let line = self.selected_line();
+        let (Some(parent_index), depth) = (line.parent_index, line.depth) else {
+            return false;
+        };
+        for idx in self.selection + 1..self.lines.len() {
+            let line = &self.lines[idx];
+            if line.depth < depth {
+                break; // we're out of the children of the parent
+            }
+            if line.parent_index == Some(parent_index) && line.is_selectable() {
+                self.selection = idx;
+                return true;
+            }
+        }
+        false
+    }
+    /// select the previous line having the same parent than the selected
+    /// one. Return false, without changing the selection, if there's none.
+    pub fn select_previous_sibling(&mut self) -> bool {
+        let Some(parent_index) = self.selected_line().parent_index else {
+            return false;
+        };
+        for idx in (parent_index + 1..self.selection).rev() {
+            let line = &self.lines[idx];
+            if line.parent_index == Some(parent_index) && line.is_selectable() {
+                self.selection = idx;
+                return true;
+            }
+        }
+        false
+    }
+    /// update the lines of a path which was renamed, and the ones
+    /// of its descendants, keeping their type and other properties.
+    /// Return false if the path isn't in the tree.
+    pub fn rename_path(&mut self, old_path: &Path, new_path: &Path) -> bool {
+        let Some(line) = self.lines.iter().find(|line| line.path == old_path) else {
+            return false;
+        };
+        let Some(new_name) = new_path.file_name() else {
+            return false;
+        };
+        let new_name = new_name.to_string_lossy().replace('\n', "");
+        let old_subpath = line.subpath.clone();
+        let new_subpath = match old_subpath.rfind('/') {
+            Some(idx) => format!("{}/{}", &old_subpath[..idx], new_name),
+            None => new_name.clone(),
+        };
+        for line in self.lines.iter_mut() {
+            let Ok(relative) = line.path.strip_prefix(old_path) else {
+                continue;
+            };
+            if relative.as_os_str().is_empty() {
+                line.path = new_path.to_path_buf();
+                line.name = new_name.clone();
+                line.subpath = new_subpath.clone();
+            } else {
+                line.path = new_path.join(relative);
+                if let Some(end) = line.subpath.strip_prefix(&old_subpath) {
+                    line.subpath = format!("{new_subpath}{end}");
+                }
+            }
+        }
+        true
+    }
+    /// read again the metadata of the line of the given path and,
+    /// when `with_descendants` is true, of the lines below it
+    pub fn reload_metadata(&mut self, path: &Path, with_descendants: bool) {
+        for line in self.lines.iter_mut() {
+            let concerned = if with_descendants {
+                line.path.starts_with(path)
+            } else {
+                line.path == path
+            };
+            if concerned {
+                if let Ok(metadata) = std::fs::symlink_metadata(&line.path) {
+                    line.metadata = metadata;
+                }
+            }
+        }
+    }
     pub fn try_select_first(&mut self) -> bool {
         for idx in 0..self.lines.len() {
             let line = &self.lines[idx];
@@ -448,18 +727,39 @@ impl Tree {
 
     /// fetch the file_sums of regular files (thus avoiding the
     /// long computation which is needed for directories)
+    ///
+    /// This doesn't touch the disk: the metadata read when building
+    /// the lines are used.
     pub fn fetch_regular_file_sums(&mut self) {
-        for i in 1..self.lines.len() {
-            match self.lines[i].line_type {
+        for line in self.lines[1..].iter_mut() {
+            match line.line_type {
                 TreeLineType::Dir | TreeLineType::Pruning => {}
+                // the sums of archive entries come from the archive listing
+                _ if line.in_archive => {}
                 _ => {
-                    self.lines[i].sum = Some(FileSum::from_file(&self.lines[i].path));
+                    line.sum = Some(FileSum::from_metadata(&line.metadata));
                 }
             }
         }
         self.sort_siblings();
     }
 
+    /// the device sums must be restricted to, when the tree
+    /// doesn't cross filesystem boundaries
+    #[cfg(unix)]
+    fn sum_device(&self) -> Option<u64> {
+        use std::os::unix::fs::MetadataExt;
+        if self.options.one_file_system {
+            Some(self.lines[0].metadata.dev())
+        } else {
+            None
+        }
+    }
+    #[cfg(not(unix))]
+    fn sum_device(&self) -> Option<u64> {
+        None
+    }
+
     /// compute the file_sum of one directory
     ///
     /// To compute the size of all of them, this should be called until
@@ -469,7 +769,8 @@ impl Tree {
         // is faster when its first level children are already computed
         for i in (0..self.lines.len()).rev() {
             if self.lines[i].sum.is_none() && self.lines[i].line_type == TreeLineType::Dir {
-                self.lines[i].sum = FileSum::from_dir(&self.lines[i].path, dam, con);
+                let dev = self.sum_device();
+                self.lines[i].sum = FileSum::from_dir(&self.lines[i].path, dev, dam, con);
                 self.sort_siblings();
                 return;
             }
@@ -509,6 +810,13 @@ impl Tree {
                 });
                 self.try_select_path(&selected_path);
             }
+            Sort::DateTree | Sort::SizeTree => {
+                // dates and sizes are taken into account when building the sort
+                // paths, so the tree structure is kept
+                let selected_path = self.selected_line().path.to_path_buf();
+                self.sort_lines_and_draw_branches();
+                self.try_select_path(&selected_path);
+            }
             _ => {}
         }
     }
@@ -532,5 +840,338 @@ impl Tree {
             sum
         }
     }
+
+    /// compute, for every parent, the greatest sum among its
+    /// displayed children, so that sizes can be compared between
+    /// siblings
+    pub fn max_sums_by_parent(&self) -> FnvHashMap<BId, FileSum> {
+        let mut max_sums: FnvHashMap<BId, FileSum> = FnvHashMap::default();
+        for line in self.lines[1..].iter() {
+            if let (Some(parent_bid), Some(sum)) = (line.parent_bid, line.sum) {
+                max_sums
+                    .entry(parent_bid)
+                    .and_modify(|max| {
+                        if sum.to_size() > max.to_size() {
+                            *max = sum;
+                        }
+                    })
+                    .or_insert(sum);
+            }
+        }
+        max_sums
+    }
 }
 
+#[test]
+fn test_try_select_path() {
+    use {
+        super::test_util::build_tree,
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::write(root.join("a/b/c"), "").unwrap();
+    fs::write(root.join("a/f"), "").unwrap();
+    let mut tree = build_tree(&root, TreeOptions::default(), 50);
+    let selected = |tree: &Tree| tree.selected_line().path.clone();
+    assert!(tree.try_select_path(&root.join("a/b/c")));
+    assert_eq!(selected(&tree), root.join("a/b/c"));
+    assert!(tree.try_select_path(&root.join("a/./b/")));
+    assert_eq!(selected(&tree), root.join("a/b"));
+    assert!(tree.try_select_path(&root.join("a/b/../b/c")));
+    assert_eq!(selected(&tree), root.join("a/b/c"));
+    assert!(!tree.try_select_path(&root.join("a/b/d")));
+    assert_eq!(selected(&tree), root.join("a/b/c"));
+    assert!(tree.try_select_path_or_ancestor(&root.join("a/d/e")));
+    assert_eq!(selected(&tree), root.join("a"));
+    tree.try_select_path(&root.join("a/b/c"));
+    tree.select_parent();
+    assert_eq!(selected(&tree), root.join("a/b"));
+    tree.select_parent();
+    tree.select_parent();
+    assert_eq!(tree.selection, 0);
+    tree.select_parent(); // no-op on the root
+    assert_eq!(tree.selection, 0);
+    assert!(!tree.select_next_sibling());
+    tree.try_select_path(&root.join("a/b"));
+    assert!(tree.select_next_sibling());
+    assert_eq!(selected(&tree), root.join("a/f"));
+    assert!(!tree.select_next_sibling());
+    assert!(tree.select_previous_sibling());
+    assert_eq!(selected(&tree), root.join("a/b"));
+    assert!(!tree.select_previous_sibling());
+    assert_eq!(selected(&tree), root.join("a/b"));
+    // renaming a directory updates its descendants, types are kept
+    assert!(tree.rename_path(&root.join("a/b"), &root.join("a/renamed")));
+    assert_eq!(selected(&tree), root.join("a/renamed"));
+    assert_eq!(tree.selected_line().name, "renamed");
+    assert_eq!(tree.selected_line().line_type, TreeLineType::Dir);
+    assert!(tree.try_select_path(&root.join("a/renamed/c")));
+    assert_eq!(tree.selected_line().subpath, "a/renamed/c");
+    assert_eq!(tree.selected_line().line_type, TreeLineType::File);
+    assert!(!tree.rename_path(&root.join("a/b"), &root.join("a/other")));
+}
+
+#[test]
+fn test_move_selection_without_cycling() {
+    use {
+        super::test_util::build_tree,
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    for i in 0..20 {
+        fs::write(root.join(format!("f{i:02}")), "").unwrap();
+    }
+    let mut options = TreeOptions::default();
+    options.trim_root = true;
+    let mut tree = build_tree(&root, options, 6);
+    let last = tree.lines.len() - 1;
+    assert_eq!(tree.lines[last].line_type, TreeLineType::Pruning);
+    assert_eq!(tree.stats.lines_count, last - 1);
+    assert_eq!(tree.stats.truncated_dirs_count, 1);
+    tree.selection = last - 1;
+    tree.move_selection(1, 10, false);
+    assert_eq!(tree.selection, last - 1);
+    tree.move_selection(1, 10, true);
+    assert_eq!(tree.selection, 0);
+    tree.move_selection(-1, 10, false);
+    assert_eq!(tree.selection, 0);
+}
+
+#[test]
+fn test_try_select_index() {
+    use {
+        super::test_util::build_tree,
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    for i in 0..20 {
+        fs::write(root.join(format!("f{i:02}")), "").unwrap();
+    }
+    let mut options = TreeOptions::default();
+    options.trim_root = true;
+    let mut tree = build_tree(&root, options, 6);
+    let last = tree.lines.len() - 1;
+    assert!(tree.try_select_index(2));
+    assert_eq!(tree.selection, 2);
+    assert!(tree.try_select_index(0));
+    assert_eq!(tree.selection, 0);
+    // the pruning line can't be selected, the line before is
+    assert!(tree.try_select_index(last));
+    assert_eq!(tree.selection, last - 1);
+    assert!(tree.try_select_index(1000));
+    assert_eq!(tree.selection, last - 1);
+}
+
+#[test]
+fn test_selection_kept_on_rebuild() {
+    use {
+        super::test_util::{app_context, build_tree_with},
+        crate::{
+            command::CommandParts,
+            pattern::InputPattern,
+        },
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::write(root.join("a/b/apple"), "").unwrap();
+    fs::write(root.join("a/b/banana"), "").unwrap();
+    fs::write(root.join("a/cherry"), "").unwrap();
+    let con = app_context(&root);
+    let pattern = |raw: &str| {
+        let parts = CommandParts::from(raw);
+        InputPattern::new(raw.to_string(), &parts.pattern, &con).unwrap()
+    };
+    let mut options = TreeOptions::default();
+    options.pattern = pattern("an");
+    let mut tree = build_tree_with(&con, &root, options, 50, false);
+    assert!(tree.try_select_path(&root.join("a/b/banana")));
+    // the selected file still matches
+    tree.options.pattern = pattern("ba");
+    tree.refresh(50, &con).unwrap();
+    assert_eq!(tree.selected_line().path, root.join("a/b/banana"));
+    // the selected file is filtered out, its deepest remaining ancestor is selected
+    tree.options.pattern = pattern("app");
+    tree.refresh(50, &con).unwrap();
+    assert_eq!(tree.selected_line().path, root.join("a/b"));
+    tree.options.pattern = pattern("cherry");
+    tree.refresh(50, &con).unwrap();
+    assert_eq!(tree.selected_line().path, root.join("a"));
+}
+
+#[test]
+fn test_count_matches() {
+    use {
+        super::test_util::{app_context, build_tree_with},
+        crate::{
+            command::CommandParts,
+            pattern::InputPattern,
+        },
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), "").unwrap();
+    fs::write(root.join("src/mod.rs"), "").unwrap();
+    fs::write(root.join("README.md"), "").unwrap();
+    let con = app_context(&root);
+    let build = |raw: &str| {
+        let parts = CommandParts::from(raw);
+        let mut options = TreeOptions::default();
+        options.pattern = InputPattern::new(raw.to_string(), &parts.pattern, &con).unwrap();
+        build_tree_with(&con, &root, options, 50, true)
+    };
+    let mut tree = build("rs");
+    // the src directory is displayed but doesn't match
+    assert_eq!(tree.count_matches(), (2, None));
+    assert!(!tree.may_miss_matches());
+    assert!(tree.try_select_path(&root.join("src/mod.rs")));
+    assert_eq!(tree.count_matches(), (2, Some(2)));
+    assert_eq!(build("nothing_here").count_matches(), (0, None));
+}
+
+#[test]
+fn test_best_match_tie_breaking() {
+    use {
+        super::test_util::build_tree,
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    for path in ["a/b/x", "a/bb", "a/c", "a/dd", "a/e", "a/f"] {
+        fs::write(root.join(path), "").unwrap();
+    }
+    let mut tree = build_tree(&root, TreeOptions::default(), 50);
+    // scores are set by hand to get ties
+    let mut select_best = |scored: &[(&str, i32)], current: &str| {
+        for line in tree.lines.iter_mut() {
+            line.score = scored.iter()
+                .find(|(path, _)| line.path == root.join(path))
+                .map_or(0, |(_, score)| *score);
+        }
+        assert!(tree.try_select_path(&root.join(current)));
+        tree.try_select_best_match();
+        tree.selected_line().path.strip_prefix(&root).unwrap().to_path_buf()
+    };
+    // the best score wins, even deeper
+    assert_eq!(select_best(&[("a/b/x", 5), ("a/c", 4)], "a"), PathBuf::from("a/b/x"));
+    // equal scores: the shallowest line, even when it comes after
+    assert_eq!(select_best(&[("a/b/x", 5), ("a/c", 5)], "a"), PathBuf::from("a/c"));
+    // same depth: the shortest name
+    assert_eq!(select_best(&[("a/bb", 5), ("a/c", 5)], "a"), PathBuf::from("a/c"));
+    // same name length: the nearest to the selection
+    assert_eq!(select_best(&[("a/c", 5), ("a/e", 5)], "a/f"), PathBuf::from("a/e"));
+    assert_eq!(select_best(&[("a/c", 5), ("a/e", 5)], "a/bb"), PathBuf::from("a/c"));
+    // no match: the selection doesn't move
+    assert_eq!(select_best(&[], "a/dd"), PathBuf::from("a/dd"));
+}
+
+#[test]
+fn test_refresh_changes() {
+    use {
+        super::test_util::{app_context, build_tree_with},
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::write(root.join("a/f"), "").unwrap();
+    fs::write(root.join("g"), "").unwrap();
+    let con = app_context(&root);
+    let mut tree = build_tree_with(&con, &root, TreeOptions::default(), 50, false);
+    assert_eq!(tree.new_count(), 0);
+    fs::write(root.join("a/h"), "").unwrap();
+    fs::remove_file(root.join("g")).unwrap();
+    tree.refresh(50, &con).unwrap();
+    let new_paths: Vec<&Path> = tree.lines.iter()
+        .filter(|line| line.is_new)
+        .map(|line| line.path.as_path())
+        .collect();
+    assert_eq!(new_paths, vec![root.join("a/h").as_path()]);
+    assert_eq!(tree.gone_count, 1);
+    tree.clear_changes();
+    assert_eq!(tree.new_count(), 0);
+    assert_eq!(tree.gone_count, 0);
+    // a refresh without change flags nothing
+    tree.refresh(50, &con).unwrap();
+    assert_eq!(tree.new_count(), 0);
+    assert_eq!(tree.gone_count, 0);
+    // nor the children of a directory which wasn't read
+    for line in tree.lines.iter_mut().filter(|line| line.path == root.join("a")) {
+        line.children_read = false;
+    }
+    fs::write(root.join("a/k"), "").unwrap();
+    tree.refresh(50, &con).unwrap();
+    assert!(tree.lines.iter().any(|line| line.path == root.join("a/k")));
+    assert_eq!(tree.new_count(), 0);
+}
+
+#[test]
+fn test_scroll() {
+    use {
+        super::test_util::{app_context, build_tree_with},
+        crate::{
+            command::CommandParts,
+            pattern::InputPattern,
+        },
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a")).unwrap();
+    for i in 0..30 {
+        fs::write(root.join(format!("a/f{i:02}")), "").unwrap();
+    }
+    let con = app_context(&root);
+    let pattern = InputPattern::new("f".to_string(), &CommandParts::from("f").pattern, &con).unwrap();
+    let mut options = TreeOptions::default();
+    options.pattern = pattern;
+    // the tree holds more lines than the page
+    let page_height = 10;
+    let mut tree = build_tree_with(&con, &root, options, page_height, true);
+    assert_eq!(tree.lines.len(), 32);
+    tree.try_select_first();
+    // moving the selection keeps 2 lines of context after it
+    tree.move_selection(9, page_height, false);
+    assert_eq!(tree.selection, 9);
+    assert_eq!(tree.scroll, 2);
+    // plain scrolling doesn't move the selection
+    assert!(tree.try_scroll(10, page_height));
+    assert_eq!(tree.scroll, 12);
+    assert_eq!(tree.selection, 9);
+    // but moving it brings it back in view, with context
+    tree.move_selection(-1, page_height, false);
+    assert_eq!(tree.selection, 8);
+    assert_eq!(tree.scroll, 5);
+    // page moves keep the selection on a visible line
+    assert!(tree.try_scroll(page_height as i32, page_height));
+    tree.select_visible_line(page_height);
+    assert_eq!(tree.selection, 16);
+    assert!(tree.try_scroll(-(page_height as i32), page_height));
+    tree.select_visible_line(page_height);
+    assert_eq!(tree.selection, 14);
+    assert!(tree.try_scroll(100, page_height));
+    assert_eq!(tree.scroll, 32 - page_height);
+    assert!(!tree.try_scroll(1, page_height));
+    // clicks are mapped through the scroll, except on the root
+    assert_eq!(tree.line_index_at_y(0), 0);
+    assert!(tree.try_select_y(3));
+    assert_eq!(tree.selection, tree.scroll + 3);
+    tree.try_select_last(page_height);
+    assert_eq!(tree.scroll, 32 - page_height);
+    // branches of a subtree starting above the view are still drawn
+    assert!(tree.has_branch(tree.scroll + 1, 1));
+    tree.try_select_first();
+    assert_eq!(tree.scroll, 0);
+    // on a tiny page there's still room for the selected line
+    assert!(!tree.has_unlisted());
+    tree.move_selection(20, 2, false);
+    assert_eq!(tree.scroll, tree.selection - 1);
+}
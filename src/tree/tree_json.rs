@@ -0,0 +1,127 @@
+use {
+    super::*,
+    serde::{Deserialize, Serialize},
+};
+
+/// A node of the JSON representation of a tree, with its children
+/// (the tree lines have no link to their children, they're rebuilt
+/// from the depths)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonNode {
+    pub name: String,
+    pub path: String,
+    /// true when the name or path couldn't be written as UTF-8
+    /// without replacing some chars
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossy: bool,
+    #[serde(rename = "type")]
+    pub node_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// last modification, in seconds since the epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<u32>,
+    /// number of children which aren't listed
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub unlisted: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<JsonNode>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+impl JsonNode {
+    fn from_line(line: &TreeLine, options: &TreeOptions) -> Self {
+        let lossy = line.path.to_str().is_none();
+        let path = line.path.to_string_lossy().to_string();
+        let name = line.path.file_name()
+            .map_or_else(|| path.clone(), |name| name.to_string_lossy().to_string());
+        let node_type = match line.line_type {
+            TreeLineType::Dir => "dir",
+            TreeLineType::SymLink { .. } | TreeLineType::BrokenSymLink(_) => "link",
+            TreeLineType::File => "file",
+            _ => "other",
+        };
+        let size = line.sum
+            .filter(|_| options.show_sizes)
+            .map(|sum| sum.to_size());
+        let mtime = line.sum
+            .filter(|_| options.show_dates)
+            .map(|sum| sum.to_seconds());
+        Self {
+            name,
+            path,
+            lossy,
+            node_type: node_type.to_string(),
+            size,
+            mtime,
+            unlisted: line.unlisted,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl Tree {
+    /// build the JSON representation of the tree, nesting the lines
+    /// according to their depth.
+    ///
+    /// Pruning lines aren't nodes: their count is added to the
+    /// unlisted count of their parent.
+    pub fn to_json_node(&self) -> JsonNode {
+        // the stack of the nodes being filled, one per depth
+        let mut stack: Vec<JsonNode> = vec![JsonNode::from_line(&self.lines[0], &self.options)];
+        for line in self.lines.iter().skip(1) {
+            let depth = line.depth as usize;
+            while stack.len() > depth {
+                let node = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(node);
+            }
+            if line.line_type == TreeLineType::Pruning {
+                stack.last_mut().unwrap().unlisted += line.unlisted;
+            } else {
+                stack.push(JsonNode::from_line(line, &self.options));
+            }
+        }
+        while stack.len() > 1 {
+            let node = stack.pop().unwrap();
+            stack.last_mut().unwrap().children.push(node);
+        }
+        stack.pop().unwrap()
+    }
+}
+
+#[test]
+fn test_json_round_trip() {
+    use {
+        super::test_util::build_tree,
+        std::fs,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/b")).unwrap();
+    fs::create_dir_all(root.join("c")).unwrap();
+    fs::write(root.join("a/b/d"), "").unwrap();
+    fs::write(root.join("a/e"), "").unwrap();
+    fs::write(root.join("f"), "").unwrap();
+    let tree = build_tree(&root, TreeOptions::default(), 50);
+    let json = serde_json::to_string(&tree.to_json_node()).unwrap();
+    let node: JsonNode = serde_json::from_str(&json).unwrap();
+    // flattening the nodes must give back the lines, with their depths
+    fn flatten(node: &JsonNode, depth: u16, out: &mut Vec<(String, u16)>) {
+        out.push((node.path.clone(), depth));
+        for child in &node.children {
+            flatten(child, depth + 1, out);
+        }
+    }
+    let mut flattened = Vec::new();
+    flatten(&node, 0, &mut flattened);
+    let lines: Vec<(String, u16)> = tree.lines.iter()
+        .map(|line| (line.path.to_string_lossy().to_string(), line.depth))
+        .collect();
+    assert_eq!(flattened, lines);
+    assert_eq!(node.children[0].name, "a");
+    assert_eq!(node.children[0].node_type, "dir");
+    assert_eq!(node.children[2].node_type, "file");
+}
@@ -2,6 +2,7 @@ use {
     super::*,
     crate::{
         app::{Selection, SelectionType},
+        content_search::ContentType,
         file_sum::FileSum,
         git::LineGitStatus,
         tree_build::BId,
@@ -24,6 +25,7 @@ use is_executable::IsExecutable;
 pub struct TreeLine {
     pub bid: BId,
     pub parent_bid: Option<BId>,
+    pub parent_index: Option<usize>, // index of the parent in the tree lines, None for the root
     pub left_branches: Box<[bool]>, // a depth-sized array telling whether a branch pass
     pub depth: u16,
     pub path: PathBuf,
@@ -32,13 +34,19 @@ pub struct TreeLine {
     pub name: String, // a displayable name - some chars may have been stripped
     pub line_type: TreeLineType,
     pub has_error: bool,
+    pub error: Option<LineError>, // why the entry couldn't be read, if it couldn't
     pub nb_kept_children: usize,
     pub unlisted: usize, // number of not listed children (Dir) or brothers (Pruning)
+    pub hidden: usize, // number of hidden children (Dir), they're not in unlisted
+    pub children_read: bool, // whether the children of the directory were read
     pub score: i32,      // 0 if there's no pattern
     pub direct_match: bool,
     pub sum: Option<FileSum>, // None when not measured
     pub metadata: fs::Metadata,
     pub git_status: Option<LineGitStatus>,
+    pub git_ignored: bool, // only true when git ignored files are shown
+    pub is_new: bool, // appeared in the last refresh
+    pub in_archive: bool, // an entry of an archive, not a real file
 }
 
 impl TreeLine {
@@ -53,6 +61,15 @@ impl TreeLine {
             .map(|(_, ext)| ext)
     }
 
+    /// the path relative to the root, as displayed in flat mode
+    /// (for a pruning line, it's the path of the "n unlisted" name)
+    pub fn flat_label(&self) -> String {
+        match self.subpath.rfind('/') {
+            Some(idx) => format!("{}/{}", &self.subpath[..idx], &self.name),
+            None => self.name.clone(),
+        }
+    }
+
     pub fn is_selectable(&self) -> bool {
         !matches!(&self.line_type, TreeLineType::Pruning)
     }
@@ -66,6 +83,16 @@ impl TreeLine {
     pub fn is_file(&self) -> bool {
         matches!(&self.line_type, TreeLineType::File)
     }
+    /// guess whether the file is text or binary, from its first bytes.
+    ///
+    /// None for directories and files which can't be read.
+    pub fn content_type(&self) -> Option<ContentType> {
+        if self.is_file() {
+            ContentType::of_file(&self.path).ok()
+        } else {
+            None
+        }
+    }
     pub fn is_of(&self, selection_type: SelectionType) -> bool {
         match selection_type {
             SelectionType::Any => true,
@@ -79,7 +106,7 @@ impl TreeLine {
     pub fn selection_type(&self) -> SelectionType {
         use TreeLineType::*;
         match &self.line_type {
-            File => SelectionType::File,
+            File | Fifo | Socket | BlockDevice | CharDevice => SelectionType::File,
             Dir | BrokenSymLink(_) => SelectionType::Directory,
             SymLink { final_is_dir, .. } => {
                 if *final_is_dir {
@@ -107,6 +134,11 @@ impl TreeLine {
     pub fn device_id(&self) -> lfs_core::DeviceId {
         self.metadata.dev().into()
     }
+    /// number of hard links to the entry
+    #[cfg(unix)]
+    pub fn nlink(&self) -> u64 {
+        self.metadata.nlink()
+    }
     #[cfg(unix)]
     pub fn mount(&self) -> Option<lfs_core::Mount> {
         use crate::filesystems::*;
@@ -126,6 +158,15 @@ impl TreeLine {
         #[cfg(windows)]
         return self.path.is_executable();
     }
+    /// return the number of seconds from Epoch to the last
+    /// modification of the entry, if it can be known
+    pub fn modified_seconds(&self) -> Option<i64> {
+        self.metadata
+            .modified()
+            .ok()
+            .and_then(|st| st.duration_since(std::time::UNIX_EPOCH).ok())
+            .and_then(|d| d.as_secs().try_into().ok())
+    }
     /// build and return the absolute targeted path: either self.path or the
     ///  solved canonicalized symlink
     pub fn target(&self) -> &Path {
@@ -23,6 +23,10 @@ pub enum TreeLineType {
         final_target: PathBuf,
     },
     Pruning, // a "xxx unlisted" line
+    Fifo, // a named pipe (unix only)
+    Socket, // unix only
+    BlockDevice, // unix only
+    CharDevice, // unix only
 }
 
 pub fn read_link(path: &Path) -> io::Result<PathBuf> {
@@ -87,7 +91,52 @@ impl TreeLineType {
                 Self::BrokenSymLink("???".to_string())
             }
         } else {
-            Self::File
+            Self::special(ft).unwrap_or(Self::File)
         }
     }
+
+    #[cfg(unix)]
+    fn special(ft: &fs::FileType) -> Option<Self> {
+        use std::os::unix::fs::FileTypeExt;
+        if ft.is_fifo() {
+            Some(Self::Fifo)
+        } else if ft.is_socket() {
+            Some(Self::Socket)
+        } else if ft.is_block_device() {
+            Some(Self::BlockDevice)
+        } else if ft.is_char_device() {
+            Some(Self::CharDevice)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn special(_ft: &fs::FileType) -> Option<Self> {
+        None
+    }
+
+    /// a short description of the type of special files, displayed
+    /// after their name
+    pub fn special_description(&self) -> Option<&'static str> {
+        match self {
+            Self::Fifo => Some("fifo"),
+            Self::Socket => Some("socket"),
+            Self::BlockDevice => Some("block device"),
+            Self::CharDevice => Some("char device"),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_socket_line_type() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sock");
+    let _listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    let ft = fs::symlink_metadata(&path).unwrap().file_type();
+    let line_type = TreeLineType::new(&path, &ft);
+    assert_eq!(line_type, TreeLineType::Socket);
+    assert_eq!(line_type.special_description(), Some("socket"));
 }
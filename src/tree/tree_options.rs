@@ -1,27 +1,41 @@
 use {
-    super::Sort,
+    super::{Expansions, MaxChildren, Sort},
     crate::{
         cli::Args,
         conf::Conf,
-        display::{Cols, DEFAULT_COLS},
+        display::{BranchChars, Cols, DEFAULT_COLS},
         errors::ConfError,
         pattern::*,
     },
     clap::Parser,
-    std::convert::TryFrom,
+    std::{
+        cmp::Ordering,
+        convert::TryFrom,
+    },
 };
 
+/// A special value of the date_time_format, for a display adapted
+/// to the age of the dates: time for today, day for this year,
+/// full date otherwise
+pub const COMPACT_DATE_TIME_FORMAT: &str = "compact";
+
 /// Options defining how the tree should be build and|or displayed
 #[derive(Debug, Clone)]
 pub struct TreeOptions {
     pub show_selection_mark: bool, // whether to have a triangle left of selected line
     pub show_hidden: bool, // whether files whose name starts with a dot should be shown
     pub only_folders: bool, // whether to hide normal files and links
+    pub follow_links: bool, // whether to list the content of links to directories
+    pub dirs_first: bool, // whether directories are listed before files, at every level
+    pub one_file_system: bool, // whether to stay on the filesystem of the root
+    pub max_depth: Option<u16>, // depth past which directories aren't entered
+    pub max_children: MaxChildren, // how many children of a directory may be listed
     pub show_counts: bool, // whether to show the number of files (> 1 only for dirs)
     pub show_dates: bool,  // whether to show the last modified date
     pub show_sizes: bool,  // whether to show sizes of files and dirs
     pub show_git_file_info: bool,
     pub show_device_id: bool,
+    pub show_hard_links: bool, // show the number of hard links of files (only on unix)
     pub show_root_fs: bool, // show information relative to the fs of the root
     pub trim_root: bool,    // whether to cut out direct children of root
     pub show_permissions: bool, // show classic rwx unix permissions (only on unix)
@@ -32,6 +46,12 @@ pub struct TreeOptions {
     pub sort: Sort,
     pub cols_order: Cols, // order of columns
     pub show_matching_characters_on_path_searches: bool,
+    pub natural_sort: bool, // whether numbers in names are compared as numbers
+    pub icon_width: usize, // number of cells taken by icons in the terminal (1 or 2)
+    pub branch_chars: BranchChars,
+    pub flat: bool, // whether lines are shown as paths, without branches
+    pub show_line_numbers: bool, // whether lines are prefixed with their index
+    pub expansions: Expansions, // directories expanded or collapsed by the user
 }
 
 impl TreeOptions {
@@ -41,6 +61,11 @@ impl TreeOptions {
             show_selection_mark: self.show_selection_mark,
             show_hidden: self.show_hidden,
             only_folders: self.only_folders,
+            follow_links: self.follow_links,
+            dirs_first: self.dirs_first,
+            one_file_system: self.one_file_system,
+            max_depth: self.max_depth,
+            max_children: self.max_children,
             show_counts: self.show_counts,
             show_dates: self.show_dates,
             show_sizes: self.show_sizes,
@@ -49,6 +74,7 @@ impl TreeOptions {
             filter_by_git_status: self.filter_by_git_status,
             show_git_file_info: self.show_git_file_info,
             show_device_id: self.show_device_id,
+            show_hard_links: self.show_hard_links,
             show_root_fs: self.show_root_fs,
             trim_root: self.trim_root,
             pattern: InputPattern::none(),
@@ -56,6 +82,12 @@ impl TreeOptions {
             sort: self.sort,
             cols_order: self.cols_order,
             show_matching_characters_on_path_searches: self.show_matching_characters_on_path_searches,
+            natural_sort: self.natural_sort,
+            icon_width: self.icon_width,
+            branch_chars: self.branch_chars,
+            flat: self.flat,
+            show_line_numbers: self.show_line_numbers,
+            expansions: self.expansions.clone(),
         }
     }
     /// counts must be computed, either for sorting or just for display
@@ -64,11 +96,19 @@ impl TreeOptions {
     }
     /// dates must be computed, either for sorting or just for display
     pub fn needs_dates(&self) -> bool {
-        self.show_dates || self.sort == Sort::Date
+        self.show_dates || self.sort == Sort::Date || self.sort == Sort::DateTree
     }
     /// sizes must be computed, either for sorting or just for display
     pub fn needs_sizes(&self) -> bool {
-        self.show_sizes || self.sort == Sort::Size
+        self.show_sizes || self.sort == Sort::Size || self.sort == Sort::SizeTree
+    }
+    /// compare two file names, case insensitively
+    pub fn cmp_names(&self, a: &str, b: &str) -> Ordering {
+        if self.natural_sort {
+            super::natural_cmp(a, b)
+        } else {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        }
     }
     pub fn needs_sum(&self) -> bool {
         self.needs_counts() || self.needs_dates() || self.needs_sizes()
@@ -97,6 +137,18 @@ impl TreeOptions {
         if let Some(b) = config.show_matching_characters_on_path_searches {
             self.show_matching_characters_on_path_searches = b;
         }
+        if let Some(b) = config.natural_sort {
+            self.natural_sort = b;
+        }
+        if let Some(w) = config.icon_width {
+            self.icon_width = w.clamp(1, 2) as usize;
+        }
+        if let Some(raw) = &config.branch_chars {
+            self.branch_chars = raw.parse()?;
+        }
+        if let Some(max_children) = config.max_children {
+            self.max_children = max_children;
+        }
         self.cols_order = config
             .cols_order
             .as_ref()
@@ -125,6 +177,25 @@ impl TreeOptions {
         } else if cli_args.no_only_folders {
             self.only_folders = false;
         }
+        if cli_args.follow_links {
+            self.follow_links = true;
+        } else if cli_args.no_follow_links {
+            self.follow_links = false;
+        }
+        if cli_args.one_file_system {
+            self.one_file_system = true;
+        } else if cli_args.no_one_file_system {
+            self.one_file_system = false;
+        }
+        if let Some(max_depth) = cli_args.max_depth {
+            // as for :set_max_depth, 0 means there's no limit
+            self.max_depth = (max_depth > 0).then_some(max_depth);
+        }
+        if cli_args.dirs_first {
+            self.dirs_first = true;
+        } else if cli_args.no_dirs_first {
+            self.dirs_first = false;
+        }
         if cli_args.git_status {
             self.filter_by_git_status = true;
             self.show_hidden = true;
@@ -165,10 +236,18 @@ impl TreeOptions {
             self.sort = Sort::Date;
             self.show_dates = true;
         }
+        if cli_args.sort_by_date_in_tree {
+            self.sort = Sort::DateTree;
+            self.show_dates = true;
+        }
         if cli_args.sort_by_size {
             self.sort = Sort::Size;
             self.show_sizes = true;
         }
+        if cli_args.sort_by_size_in_tree {
+            self.sort = Sort::SizeTree;
+            self.show_sizes = true;
+        }
         if cli_args.sort_by_type_dirs_first || cli_args.sort_by_type {
             self.sort = Sort::TypeDirsFirst;
         }
@@ -192,11 +271,17 @@ impl Default for TreeOptions {
             show_selection_mark: false,
             show_hidden: false,
             only_folders: false,
+            follow_links: false,
+            dirs_first: false,
+            one_file_system: false,
+            max_depth: None,
+            max_children: MaxChildren::default(),
             show_counts: false,
             show_dates: false,
             show_sizes: false,
             show_git_file_info: false,
             show_device_id: false,
+            show_hard_links: false,
             show_root_fs: false,
             trim_root: false,
             show_permissions: false,
@@ -207,6 +292,12 @@ impl Default for TreeOptions {
             sort: Sort::None,
             cols_order: DEFAULT_COLS,
             show_matching_characters_on_path_searches: true,
+            natural_sort: true,
+            icon_width: 1,
+            branch_chars: BranchChars::default(),
+            flat: false,
+            show_line_numbers: false,
+            expansions: Expansions::default(),
         }
     }
 }
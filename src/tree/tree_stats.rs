@@ -0,0 +1,33 @@
+use super::*;
+
+/// Counts describing a tree, computed when its lines change
+/// so that they don't have to be on every display
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TreeStats {
+    /// number of listed paths, root excluded
+    pub lines_count: usize,
+    /// number of directories whose content isn't fully listed
+    pub truncated_dirs_count: usize,
+    /// number of paths which couldn't be read
+    pub errors_count: usize,
+}
+
+impl TreeStats {
+    pub fn from_lines(lines: &[TreeLine]) -> Self {
+        let mut stats = Self::default();
+        for line in lines.iter().skip(1) {
+            if line.line_type != TreeLineType::Pruning {
+                stats.lines_count += 1;
+            }
+        }
+        for line in lines {
+            if line.unlisted > 0 {
+                stats.truncated_dirs_count += 1;
+            }
+            if line.has_error {
+                stats.errors_count += 1;
+            }
+        }
+        stats
+    }
+}
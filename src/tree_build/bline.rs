@@ -2,7 +2,9 @@ use {
     super::bid::BId,
     crate::{
         app::AppContext,
+        archive::{self, Archive},
         errors::TreeBuildError,
+        file_sum::FileSum,
         git::GitIgnoreChain,
         path::{normalize_path, SpecialHandling},
         tree::*,
@@ -13,11 +15,13 @@ use {
         io,
         path::PathBuf,
         result::Result,
+        sync::Arc,
     },
 };
 
 /// like a tree line, but with the info needed during the build
 /// This structure isn't usable independently from the tree builder
+#[derive(Clone)]
 pub struct BLine {
     pub parent_id: Option<BId>,
     pub path: PathBuf,
@@ -27,13 +31,19 @@ pub struct BLine {
     pub file_type: fs::FileType,
     pub children: Option<Vec<BId>>, // sorted and filtered
     pub next_child_idx: usize,      // index for iteration, among the children
+    pub unread_children: usize,     // children counted but not read, past the hard limit
+    pub hidden_children: usize,     // children not listed because hidden, not in unlisted
     pub has_error: bool,
+    pub error: Option<LineError>,
     pub has_match: bool,
     pub direct_match: bool,
     pub score: i32,
     pub nb_kept_children: i32, // used during the trimming step
     pub git_ignore_chain: GitIgnoreChain,
+    pub git_ignored: bool, // only true when git ignored files are shown
     pub special_handling: SpecialHandling,
+    pub archive: Option<Arc<Archive>>, // when the line is an archive or is in one
+    pub archive_entry: Option<usize>,  // index of the entry, when the line is in an archive
 }
 
 impl BLine {
@@ -48,7 +58,36 @@ impl BLine {
             Some(name) => name.to_string_lossy().to_string(),
             None => String::from("???"), // should not happen
         };
-        if let Ok(md) = fs::metadata(&path) {
+        let mut error = None;
+        let mut archive = None;
+        let mut archive_entry = None;
+        let md = if let Some((archive_path, path_in_archive)) = archive::split_virtual_path(&path) {
+            // the root is a directory in an archive
+            let not_found = || TreeBuildError::FileNotFound {
+                path: format!("{path:?}"),
+            };
+            let read_archive = Archive::open(archive_path).map_err(|e| {
+                warn!("failed to read archive {:?}: {}", archive_path, e);
+                not_found()
+            })?;
+            archive_entry = Some(read_archive.entry_idx(&path_in_archive).ok_or_else(not_found)?);
+            archive = Some(read_archive);
+            fs::metadata(archive_path)
+        } else {
+            if archive::is_archive(&path) {
+                match Archive::open(&path) {
+                    Ok(read_archive) => {
+                        archive = Some(read_archive);
+                    }
+                    Err(e) => {
+                        warn!("failed to read archive {:?}: {}", &path, e);
+                        error = Some(LineError::UnreadableArchive);
+                    }
+                }
+            }
+            fs::metadata(&path)
+        };
+        if let Ok(md) = md {
             let file_type = md.file_type();
             Ok(blines.alloc(BLine {
                 parent_id: None,
@@ -58,14 +97,20 @@ impl BLine {
                 subpath: String::new(),
                 children: None,
                 next_child_idx: 0,
+                unread_children: 0,
+                hidden_children: 0,
                 file_type,
-                has_error: false,
+                has_error: error.is_some(),
+                error,
                 has_match: true,
                 direct_match: false,
                 score: 0,
                 nb_kept_children: 0,
                 git_ignore_chain,
+                git_ignored: false,
                 special_handling: SpecialHandling::None,
+                archive,
+                archive_entry,
             }))
         } else {
             Err(TreeBuildError::FileNotFound {
@@ -90,8 +135,36 @@ impl BLine {
         }
         fs::read_dir(&self.path)
     }
+    /// tell whether the line is a directory, be it a real one or one
+    /// in an archive. An archive whose content is listed counts as one.
+    pub fn is_dir(&self) -> bool {
+        match (&self.archive, self.archive_entry) {
+            (Some(archive), Some(idx)) => archive.entries[idx].is_dir,
+            (Some(_), None) => true,
+            (None, _) => self.file_type.is_dir(),
+        }
+    }
+    /// tell whether the line is a directory or a link to a directory
+    pub fn is_dir_or_link_to_dir(&self) -> bool {
+        if self.archive.is_none() && self.file_type.is_symlink() {
+            matches!(
+                TreeLineType::new(&self.path, &self.file_type),
+                TreeLineType::SymLink { final_is_dir: true, .. },
+            )
+        } else {
+            self.is_dir()
+        }
+    }
+    /// tell whether the line is an entry of an archive, thus with
+    /// a path which doesn't exist on disk
+    pub fn is_in_archive(&self) -> bool {
+        self.archive_entry.is_some()
+    }
     /// tell whether we should list the children of the present line
     pub fn can_enter(&self) -> bool {
+        if self.archive.is_some() {
+            return self.is_dir();
+        }
         if self.file_type.is_dir() && self.special_handling != SpecialHandling::NoEnter {
             return true;
         }
@@ -119,15 +192,34 @@ impl BLine {
         false
     }
     pub fn to_tree_line(&self, bid: BId, con: &AppContext) -> std::io::Result<TreeLine> {
-        let has_error = self.has_error;
-        let line_type = TreeLineType::new(&self.path, &self.file_type);
+        let line_type = if self.archive.is_some() {
+            if self.is_dir() { TreeLineType::Dir } else { TreeLineType::File }
+        } else {
+            TreeLineType::new(&self.path, &self.file_type)
+        };
+        let error = match line_type {
+            TreeLineType::BrokenSymLink(_) => Some(LineError::BrokenLink),
+            TreeLineType::File | TreeLineType::Dir if self.error.is_none() && self.archive.is_none() => {
+                LineError::check_access(&self.path, line_type == TreeLineType::Dir)
+            }
+            _ => self.error,
+        };
+        let has_error = self.has_error || error.is_some();
         let unlisted = if let Some(children) = &self.children {
             // number of not listed children
-            children.len() - self.next_child_idx
+            children.len() - self.next_child_idx + self.unread_children
         } else {
             0
         };
-        let metadata = fs::symlink_metadata(&self.path)?;
+        // the lines in an archive get the metadata of the archive, and
+        // their sizes and dates from the archive listing
+        let (metadata, sum) = match (&self.archive, self.archive_entry) {
+            (Some(archive), Some(idx)) => {
+                (fs::symlink_metadata(&archive.path)?, Some(archive.entries[idx].sum))
+            }
+            (Some(archive), None) => (fs::symlink_metadata(&self.path)?, Some(archive.sum)),
+            (None, _) => (fs::symlink_metadata(&self.path)?, None::<FileSum>),
+        };
         let subpath = self.subpath.replace('\n', "");
         let name = self.name.replace('\n', "");
         let icon = con.icons.as_ref()
@@ -146,6 +238,7 @@ impl BLine {
         Ok(TreeLine {
             bid,
             parent_bid: self.parent_id,
+            parent_index: None, // set when the tree lines are sorted
             left_branches: vec![false; self.depth as usize].into_boxed_slice(),
             depth: self.depth,
             icon,
@@ -154,13 +247,19 @@ impl BLine {
             path: self.path.clone(),
             line_type,
             has_error,
+            error,
             nb_kept_children: self.nb_kept_children as usize,
             unlisted,
+            hidden: self.hidden_children,
+            children_read: self.children.is_some(),
             score: self.score,
             direct_match: self.direct_match,
-            sum: None,
+            sum,
             metadata,
             git_status: None,
+            git_ignored: self.git_ignored,
+            is_new: false,
+            in_archive: self.is_in_archive(),
         })
     }
 }
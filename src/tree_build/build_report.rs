@@ -14,7 +14,25 @@ pub struct BuildReport {
     /// (this count stays at zero if hidden files are displayed)
     pub hidden_count: usize,
 
+    /// number of times an ignore glob (from conf or launch
+    /// arguments) excluded a file
+    pub ignored_count: usize,
+
     /// number of errors excluding a file
     pub error_count: usize,
 
+    /// number of directories with too many entries to be
+    /// totally read (see HARD_MAX_CHILDREN)
+    pub capped_dir_count: usize,
+
+}
+
+impl std::ops::AddAssign for BuildReport {
+    fn add_assign(&mut self, other: Self) {
+        self.gitignored_count += other.gitignored_count;
+        self.hidden_count += other.hidden_count;
+        self.ignored_count += other.ignored_count;
+        self.error_count += other.error_count;
+        self.capped_dir_count += other.capped_dir_count;
+    }
 }
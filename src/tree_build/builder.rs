@@ -6,22 +6,28 @@ use {
     },
     crate::{
         app::AppContext,
+        archive::Archive,
         errors::TreeBuildError,
         git::{GitIgnoreChain, GitIgnorer, LineStatusComputer},
         pattern::Candidate,
-        path::{SpecialHandling, SpecialPathList},
+        path::{IgnoreGlobList, SpecialHandling, SpecialPathList},
         task_sync::ComputationResult,
         task_sync::Dam,
         tree::*,
     },
+    fnv::FnvHashSet,
     git2::Repository,
     id_arena::Arena,
+    rayon::prelude::*,
     std::{
         collections::{BinaryHeap, VecDeque},
+        fmt,
         fs,
-        path::PathBuf,
+        io,
+        path::{Path, PathBuf},
         result::Result,
-        time::{Duration, Instant},
+        sync::Arc,
+        time::Instant,
     },
 };
 
@@ -43,10 +49,52 @@ impl OsStrWin for OsStr {
         self.to_str().map(|s| s.as_bytes()).unwrap_or(INVALID_UTF8)
     }
 }
-/// If a search found enough results to fill the screen but didn't scan
-/// everything, we search a little more in case we find better matches
-/// but not after the NOT_LONG duration.
-static NOT_LONG: Duration = Duration::from_millis(900);
+/// A search which isn't total stops after this duration (unless changed
+/// in configuration), with the matches it found so far
+pub const DEFAULT_SEARCH_TIME_BUDGET_MS: u64 = 900;
+
+/// Number of directories read in parallel between two checks
+/// of the dam
+const PARALLEL_READ_CHUNK: usize = 64;
+
+/// The kept entries of a directory, not yet added to the tree
+struct ChildrenReading {
+    lines: io::Result<Vec<BLine>>,
+    unread_count: usize, // lines not kept because past HARD_MAX_CHILDREN
+    hidden_count: usize, // entries not kept because hidden
+    report: BuildReport,
+}
+
+/// Where the walk of the directories is: the lines gathered so
+/// far, and the directories whose children are still to be listed
+#[derive(Clone)]
+struct Walk {
+    out_blines: Vec<BId>, // the blines we want to display
+    nb_lines_ok: usize,   // in out_blines
+    open_dirs: VecDeque<BId>,
+    next_level_dirs: Vec<BId>,
+}
+
+/// The state of a search which wasn't total, taken before the
+/// trimming of its lines, so that a total search can go on from
+/// where it stopped instead of walking again from the root
+#[derive(Clone)]
+pub struct SearchContinuation {
+    blines: Arena<BLine>,
+    root_id: BId,
+    git_ignorer: GitIgnorer,
+    report: BuildReport,
+    walk: Walk,
+}
+
+impl fmt::Debug for SearchContinuation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchContinuation")
+            .field("lines", &self.walk.out_blines.len())
+            .field("pending_dirs", &(self.walk.open_dirs.len() + self.walk.next_level_dirs.len()))
+            .finish()
+    }
+}
 
 /// The TreeBuilder builds a Tree according to options (including an optional search pattern)
 /// Instead of the final TreeLine, the builder uses an internal structure: BLine.
@@ -66,6 +114,9 @@ pub struct TreeBuilder<'c> {
     trim_root: bool,
     pub deep: bool,
     report: BuildReport,
+    root_dev: Option<u64>, // set only when we must stay on the root's filesystem
+    resumed_walk: Option<Walk>,
+    continuation: Option<SearchContinuation>, // set when a search is stopped
 }
 impl<'c> TreeBuilder<'c> {
 
@@ -88,6 +139,15 @@ impl<'c> TreeBuilder<'c> {
         } else {
             None
         };
+        #[cfg(unix)]
+        let root_dev = if options.one_file_system {
+            use std::os::unix::fs::MetadataExt;
+            fs::metadata(&path).ok().map(|md| md.dev())
+        } else {
+            None
+        };
+        #[cfg(not(unix))]
+        let root_dev = None;
         let root_id = BLine::from_root(&mut blines, path, root_ignore_chain, &options)?;
         let trim_root = match (options.trim_root, options.pattern.is_some(), options.sort.prevent_deep_display()) {
             // we never want to trim the root if there's a sort
@@ -112,19 +172,33 @@ impl<'c> TreeBuilder<'c> {
             matches_max: None,
             deep: true,
             report: BuildReport::default(),
+            root_dev,
+            resumed_walk: None,
+            continuation: None,
         })
     }
 
+    /// go on with the search from where a previous build, with the same
+    /// root and options, was stopped
+    pub fn resume(&mut self, continuation: SearchContinuation) {
+        self.blines = continuation.blines;
+        self.root_id = continuation.root_id;
+        self.git_ignorer = continuation.git_ignorer;
+        self.report = continuation.report;
+        self.resumed_walk = Some(continuation.walk);
+    }
+
     /// Return a bline if the dir_entry directly matches the options and there's no error
     fn make_line(
-        &mut self,
+        &self,
         parent_id: BId,
         e: &fs::DirEntry,
         depth: u16,
+        report: &mut BuildReport,
     ) -> Option<BLine> {
         let name = e.file_name();
         if name.is_empty() {
-            self.report.error_count += 1;
+            report.error_count += 1;
             return None;
         }
         let path = e.path();
@@ -136,7 +210,7 @@ impl<'c> TreeBuilder<'c> {
             .filter(|sp| sp.handling == SpecialHandling::NoHide)
             .any(|sp| sp.pattern.matches_path(&path))
         {
-            self.report.hidden_count += 1;
+            report.hidden_count += 1;
             return None;
         }
         let name = name.to_string_lossy();
@@ -145,7 +219,7 @@ impl<'c> TreeBuilder<'c> {
         let file_type = match e.file_type() {
             Ok(ft) => ft,
             Err(_) => {
-                self.report.error_count += 1;
+                report.error_count += 1;
                 return None;
             }
         };
@@ -155,6 +229,10 @@ impl<'c> TreeBuilder<'c> {
         } else {
             name.to_string()
         };
+        if self.con.ignore_globs.excludes(&name, Path::new(&subpath)) {
+            report.ignored_count += 1;
+            return None;
+        }
         let candidate = Candidate {
             name: &name,
             subpath: &subpath,
@@ -197,15 +275,16 @@ impl<'c> TreeBuilder<'c> {
         if special_handling == SpecialHandling::Hide {
             return None;
         }
-        if self.options.respect_git_ignore {
-            let parent_chain = &self.blines[parent_id].git_ignore_chain;
-            if !self
-                .git_ignorer
-                .accepts(parent_chain, &path, &name, file_type.is_dir())
-            {
-                return None;
-            }
-        };
+        // the content of an ignored directory is ignored too
+        let git_ignored = self.blines[parent_id].git_ignored || !self.git_ignorer.accepts(
+            &self.blines[parent_id].git_ignore_chain,
+            &path,
+            &name,
+            file_type.is_dir(),
+        );
+        if git_ignored && self.options.respect_git_ignore {
+            return None;
+        }
         Some(BLine {
             parent_id: Some(parent_id),
             path,
@@ -215,38 +294,162 @@ impl<'c> TreeBuilder<'c> {
             file_type,
             children: None,
             next_child_idx: 0,
+            unread_children: 0,
+            hidden_children: 0,
             has_error: false,
+            error: None,
             has_match,
             direct_match,
             score,
             nb_kept_children: 0,
             git_ignore_chain: GitIgnoreChain::default(),
+            git_ignored,
             special_handling,
+            archive: None,
+            archive_entry: None,
+        })
+    }
+
+    /// Return a bline for an entry of an archive, if it matches the options
+    fn make_archive_line(
+        &self,
+        parent_id: BId,
+        archive: &Arc<Archive>,
+        idx: usize,
+        depth: u16,
+        report: &mut BuildReport,
+    ) -> Option<BLine> {
+        let entry = &archive.entries[idx];
+        let name = entry.name();
+        if !self.options.show_hidden && name.starts_with('.') {
+            report.hidden_count += 1;
+            return None;
+        }
+        let parent = &self.blines[parent_id];
+        let subpath = if !parent.subpath.is_empty() {
+            format!("{}/{}", parent.subpath, name)
+        } else {
+            name.to_string()
+        };
+        if self.con.ignore_globs.excludes(name, Path::new(&subpath)) {
+            report.ignored_count += 1;
+            return None;
+        }
+        if self.options.only_folders && !entry.is_dir {
+            return None;
+        }
+        let path = archive.entry_path(idx);
+        let mut score = 10000 - i32::from(depth);
+        let candidate = Candidate {
+            name,
+            subpath: &subpath,
+            path: &path,
+            regular_file: !entry.is_dir,
+        };
+        let direct_match = if let Some(pattern_score) = self.options.pattern.pattern.score_of(candidate) {
+            score += pattern_score + 10;
+            true
+        } else {
+            false
+        };
+        if !entry.is_dir && !direct_match {
+            return None;
+        }
+        Some(BLine {
+            parent_id: Some(parent_id),
+            path,
+            depth,
+            subpath,
+            name: name.to_string(),
+            file_type: parent.file_type, // not used for lines in archives
+            children: None,
+            next_child_idx: 0,
+            unread_children: 0,
+            hidden_children: 0,
+            has_error: false,
+            error: None,
+            has_match: direct_match,
+            direct_match,
+            score,
+            nb_kept_children: 0,
+            git_ignore_chain: GitIgnoreChain::default(),
+            git_ignored: false,
+            special_handling: SpecialHandling::None,
+            archive: Some(Arc::clone(archive)),
+            archive_entry: Some(idx),
         })
     }
 
     /// Return true when there are direct matches among children
     fn load_children(&mut self, bid: BId) -> bool {
-        let mut has_child_match = false;
-        match self.blines[bid].read_dir() {
-            Ok(entries) => {
-                let mut children: Vec<BId> = Vec::new();
-                let child_depth = self.blines[bid].depth + 1;
-                let mut lines = Vec::new();
-                for e in entries.flatten() {
-                    if let Some(line) = self.make_line(bid, &e, child_depth) {
+        let reading = self.read_children(bid);
+        self.store_children(bid, reading)
+    }
+
+    /// Read the entries of a directory and make the lines which should
+    /// be kept. The builder isn't modified, so that several directories
+    /// can be read in parallel.
+    fn read_children(&self, bid: BId) -> ChildrenReading {
+        let mut report = BuildReport::default();
+        let child_depth = self.blines[bid].depth + 1;
+        if let Some(archive) = &self.blines[bid].archive {
+            // the children are read in the listing of the archive
+            let lines = archive
+                .children(self.blines[bid].archive_entry)
+                .iter()
+                .filter_map(|&idx| {
+                    self.make_archive_line(bid, archive, idx, child_depth, &mut report)
+                })
+                .collect();
+            let hidden_count = report.hidden_count;
+            return ChildrenReading { lines: Ok(lines), unread_count: 0, hidden_count, report };
+        }
+        // past the hard limit, lines are made (so that they're filtered
+        // as the other ones) but only counted
+        let mut unread_count = 0;
+        let lines = self.blines[bid].read_dir().map(|entries| {
+            let mut lines = Vec::new();
+            for e in entries.flatten() {
+                if let Some(line) = self.make_line(bid, &e, child_depth, &mut report) {
+                    if lines.len() < HARD_MAX_CHILDREN {
                         lines.push(line);
+                    } else {
+                        unread_count += 1;
                     }
                 }
+            }
+            lines
+        });
+        if unread_count > 0 {
+            warn!(
+                "only {} children of {:?} read, {} not read",
+                HARD_MAX_CHILDREN,
+                &self.blines[bid].path,
+                unread_count,
+            );
+            report.capped_dir_count += 1;
+        }
+        let hidden_count = report.hidden_count;
+        ChildrenReading { lines, unread_count, hidden_count, report }
+    }
+
+    /// Add the lines read in a directory to the tree.
+    /// Return true when there are direct matches among them
+    fn store_children(&mut self, bid: BId, reading: ChildrenReading) -> bool {
+        let mut has_child_match = false;
+        self.report += reading.report;
+        match reading.lines {
+            Ok(lines) => {
+                let mut children: Vec<BId> = Vec::new();
                 for mut bl in lines {
-                    if self.options.respect_git_ignore {
-                        let parent_chain = &self.blines[bid].git_ignore_chain;
-                        bl.git_ignore_chain = if bl.file_type.is_dir() {
-                            self.git_ignorer.deeper_chain(parent_chain, &bl.path)
-                        } else {
-                            parent_chain.clone()
-                        };
-                    }
+                    // the chain is needed even when ignored files are shown,
+                    // to know which ones must be flagged
+                    let parent_chain = &self.blines[bid].git_ignore_chain;
+                    bl.git_ignore_chain = if bl.file_type.is_dir() && bl.archive.is_none() {
+                        self.git_ignorer.deeper_chain(parent_chain, &bl.path)
+                    } else {
+                        parent_chain.clone()
+                    };
                     if bl.has_match {
                         self.blines[bid].has_match = true;
                         has_child_match = true;
@@ -254,28 +457,129 @@ impl<'c> TreeBuilder<'c> {
                     let child_id = self.blines.alloc(bl);
                     children.push(child_id);
                 }
-                children.sort_by(|&a, &b| {
-                    self.blines[a]
-                        .name
-                        .to_lowercase()
-                        .cmp(&self.blines[b].name.to_lowercase())
+                // links are resolved once, not at every comparison
+                let dirs: FnvHashSet<BId> = if self.options.dirs_first {
+                    children.iter()
+                        .filter(|&&id| self.blines[id].is_dir_or_link_to_dir())
+                        .copied()
+                        .collect()
+                } else {
+                    FnvHashSet::default()
+                };
+                children.sort_by(|a, b| {
+                    let group = dirs.contains(b).cmp(&dirs.contains(a));
+                    let (a, b) = (&self.blines[*a], &self.blines[*b]);
+                    group.then_with(|| self.options.cmp_names(&a.name, &b.name))
                 });
                 self.blines[bid].children = Some(children);
+                self.blines[bid].unread_children = reading.unread_count;
+                self.blines[bid].hidden_children = reading.hidden_count;
             }
-            Err(_err) => {
+            Err(err) => {
                 self.blines[bid].has_error = true;
+                // a more precise error may already be known (eg for an archive)
+                self.blines[bid].error.get_or_insert(LineError::from_io(&err));
                 self.blines[bid].children = Some(Vec::new());
             }
         }
         has_child_match
     }
 
-    /// return the next child.
+    /// tell whether we should list the children of the line.
+    ///
+    /// When links are followed, a link to a directory which is already
+    /// on the ancestor chain isn't entered (it's marked instead) to
+    /// prevent infinite loops.
+    /// When asked to stay on one filesystem, mount points aren't entered.
+    /// Directories at the max depth are listed but their children aren't.
+    /// The choice of the user, when they expanded or collapsed the
+    /// directory, prevails over the max depth.
+    fn can_enter(&mut self, bid: BId) -> bool {
+        match self.options.expansions.get(&self.blines[bid].path) {
+            Some(false) => false,
+            Some(true) => self.can_list(bid),
+            None => !self.is_at_max_depth(bid) && self.can_list(bid),
+        }
+    }
+
+    /// tell whether the children of the line can be read, be it only
+    /// to count them
+    fn can_list(&mut self, bid: BId) -> bool {
+        self.can_enter_dir(bid) && !self.is_on_other_file_system(bid)
+    }
+
+    fn is_at_max_depth(&self, bid: BId) -> bool {
+        self.options.max_depth
+            .map_or(false, |max_depth| self.blines[bid].depth >= max_depth)
+    }
+
+    /// tell whether the line is on another filesystem than the root, while the
+    /// user asked to stay on the root's one (it's then a mount point)
+    fn is_on_other_file_system(&self, bid: BId) -> bool {
+        #[cfg(unix)]
+        if let Some(root_dev) = self.root_dev {
+            use std::os::unix::fs::MetadataExt;
+            let path = &self.blines[bid].path;
+            if fs::metadata(path).map_or(false, |md| md.dev() != root_dev) {
+                debug!("not entering {:?}: it's on another filesystem", path);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn can_enter_dir(&mut self, bid: BId) -> bool {
+        let bline = &self.blines[bid];
+        if bline.can_enter() {
+            return true;
+        }
+        if !self.options.follow_links
+            || !bline.file_type.is_symlink()
+            || bline.special_handling == SpecialHandling::NoEnter
+        {
+            return false;
+        }
+        let Ok(target) = fs::canonicalize(&bline.path) else {
+            return false;
+        };
+        if !target.is_dir() {
+            return false;
+        }
+        let mut parent_id = bline.parent_id;
+        while let Some(id) = parent_id {
+            let ancestor = &self.blines[id];
+            if fs::canonicalize(&ancestor.path).map_or(false, |path| path == target) {
+                debug!("not following {:?}: it's a link to an ancestor", &bline.path);
+                self.blines[bid].error = Some(LineError::LinkLoop);
+                return false;
+            }
+            parent_id = ancestor.parent_id;
+        }
+        true
+    }
+
+    /// return the max number of children which may be listed for
+    /// the directory, None if there's no limit.
+    ///
+    /// There's no limit when searching, as the best matches may be
+    /// anywhere, nor in directories expanded by the user.
+    fn max_children(&self, bid: BId) -> Option<usize> {
+        if self.options.pattern.is_some()
+            || self.options.expansions.is_expanded(&self.blines[bid].path)
+        {
+            return None;
+        }
+        self.options.max_children.limit(self.targeted_size)
+    }
+
+    /// return the next child, unless the max number of
+    /// children of the directory is reached.
     /// load_children must have been called before on parent_id
     fn next_child(&mut self, parent_id: BId) -> Option<BId> {
+        let max_children = self.max_children(parent_id).unwrap_or(usize::MAX);
         let bline = &mut self.blines[parent_id];
         if let Some(children) = &bline.children {
-            if bline.next_child_idx < children.len() {
+            if bline.next_child_idx < children.len().min(max_children) {
                 let next_child = children[bline.next_child_idx];
                 bline.next_child_idx += 1;
                 Some(next_child)
@@ -292,85 +596,97 @@ impl<'c> TreeBuilder<'c> {
     /// If there's a pattern, we try to gather more lines that will be sorted afterwards.
     fn gather_lines(&mut self, total_search: bool, dam: &Dam) -> Result<Vec<BId>, TreeBuildError> {
         let start = Instant::now();
-        let mut out_blines: Vec<BId> = Vec::new(); // the blines we want to display
         let optimal_size = if self.options.pattern.pattern.has_real_scores() {
             10 * self.targeted_size
         } else {
             self.targeted_size
         };
-        out_blines.push(self.root_id);
-        let mut nb_lines_ok = 1; // in out_blines
-        let mut open_dirs: VecDeque<BId> = VecDeque::new();
-        let mut next_level_dirs: Vec<BId> = Vec::new();
-        self.load_children(self.root_id);
-        open_dirs.push_back(self.root_id);
+        let mut walk = match self.resumed_walk.take() {
+            Some(walk) => walk,
+            None => {
+                self.load_children(self.root_id);
+                Walk {
+                    out_blines: vec![self.root_id],
+                    nb_lines_ok: 1,
+                    open_dirs: VecDeque::from([self.root_id]),
+                    next_level_dirs: Vec::new(),
+                }
+            }
+        };
         let deep = self.deep && !self.options.sort.prevent_deep_display();
         loop {
             if !total_search && (
-                (nb_lines_ok > optimal_size)
-                || (nb_lines_ok >= self.targeted_size && start.elapsed() > NOT_LONG)
+                (walk.nb_lines_ok > optimal_size)
+                || (self.options.pattern.is_some() && start.elapsed() > self.con.search_time_budget)
             ) {
                 self.total_search = false;
+                if self.options.pattern.is_some() {
+                    // the lines are kept before they're trimmed, and before
+                    // the end of the walk below, so that a total search
+                    // can go on from here
+                    self.continuation = Some(SearchContinuation {
+                        blines: self.blines.clone(),
+                        root_id: self.root_id,
+                        git_ignorer: self.git_ignorer.clone(),
+                        report: self.report,
+                        walk: walk.clone(),
+                    });
+                }
                 break;
             }
             if let Some(max) = self.matches_max {
-                if nb_lines_ok > max {
+                if walk.nb_lines_ok > max {
                     return Err(TreeBuildError::TooManyMatches{max});
                 }
             }
-            if let Some(open_dir_id) = open_dirs.pop_front() {
+            if let Some(open_dir_id) = walk.open_dirs.pop_front() {
                 if let Some(child_id) = self.next_child(open_dir_id) {
-                    open_dirs.push_back(open_dir_id);
-                    let child = &self.blines[child_id];
-                    if child.has_match {
-                        nb_lines_ok += 1;
+                    walk.open_dirs.push_back(open_dir_id);
+                    if self.blines[child_id].has_match {
+                        walk.nb_lines_ok += 1;
                     }
-                    if child.can_enter() {
-                        next_level_dirs.push(child_id);
+                    if self.can_enter(child_id) {
+                        walk.next_level_dirs.push(child_id);
                     }
-                    out_blines.push(child_id);
+                    walk.out_blines.push(child_id);
                 }
             } else {
                 // this depth is finished, we must go deeper
                 if !deep {
                     break;
                 }
-                if next_level_dirs.is_empty() {
+                if walk.next_level_dirs.is_empty() {
                     // except there's nothing deeper
                     break;
                 }
-                for next_level_dir_id in &next_level_dirs {
+                // directories are read in parallel, but their lines are
+                // added in order so that the tree doesn't depend on timings
+                for chunk in walk.next_level_dirs.chunks(PARALLEL_READ_CHUNK) {
                     if dam.has_event() {
                         info!("task expired (core build - inner loop)");
                         return Err(TreeBuildError::Interrupted);
                     }
-                    let has_child_match = self.load_children(*next_level_dir_id);
-                    if has_child_match {
-                        // we must ensure the ancestors are made Ok
-                        let mut id = *next_level_dir_id;
-                        loop {
-                            let bline = &mut self.blines[id];
-                            if !bline.has_match {
-                                bline.has_match = true;
-                                nb_lines_ok += 1;
-                            }
-                            if let Some(pid) = bline.parent_id {
-                                id = pid;
-                            } else {
-                                break;
-                            }
+                    let readings: Vec<ChildrenReading> = chunk
+                        .par_iter()
+                        .map(|&id| self.read_children(id))
+                        .collect();
+                    for (&next_level_dir_id, reading) in chunk.iter().zip(readings) {
+                        let has_child_match = self.store_children(next_level_dir_id, reading);
+                        if has_child_match {
+                            walk.nb_lines_ok += self.mark_ancestors_matching(next_level_dir_id);
                         }
+                        walk.open_dirs.push_back(next_level_dir_id);
                     }
-                    open_dirs.push_back(*next_level_dir_id);
                 }
-                next_level_dirs.clear();
+                walk.next_level_dirs.clear();
             }
         }
         if let Some(max) = self.matches_max {
-            if nb_lines_ok > max {
+            if walk.nb_lines_ok > max {
                 return Err(TreeBuildError::TooManyMatches{max});
             }
         }
+        let mut out_blines = walk.out_blines;
         if !self.trim_root {
             // if the root directory isn't totally read, we finished it even
             // it it goes past the bottom of the screen
@@ -378,20 +694,79 @@ impl<'c> TreeBuilder<'c> {
                 out_blines.push(child_id);
             }
         }
+        if deep && !self.options.expansions.is_empty() {
+            self.add_expanded_children(&mut out_blines);
+        }
         Ok(out_blines)
     }
 
+    /// make the line and its ancestors matching, so that they're kept,
+    /// and return the number of lines which weren't
+    fn mark_ancestors_matching(&mut self, bid: BId) -> usize {
+        let mut count = 0;
+        let mut id = bid;
+        loop {
+            let bline = &mut self.blines[id];
+            if !bline.has_match {
+                bline.has_match = true;
+                count += 1;
+            }
+            if let Some(pid) = bline.parent_id {
+                id = pid;
+            } else {
+                break;
+            }
+        }
+        count
+    }
+
+    /// add all the children of the directories expanded by the user, even
+    /// when they don't fit the screen. Children are themselves handled
+    /// when they're expanded directories.
+    fn add_expanded_children(&mut self, out_blines: &mut Vec<BId>) {
+        let mut i = 0;
+        while i < out_blines.len() {
+            let id = out_blines[i];
+            i += 1;
+            if !self.options.expansions.is_expanded(&self.blines[id].path) || !self.can_list(id) {
+                continue;
+            }
+            if self.blines[id].children.is_none() && self.load_children(id) {
+                self.mark_ancestors_matching(id);
+            }
+            while let Some(child_id) = self.next_child(id) {
+                out_blines.push(child_id);
+            }
+        }
+    }
+
+    /// tell whether the line must not be removed at trimming, because
+    /// it's an expanded directory or the child of one
+    fn is_pinned(&self, bid: BId) -> bool {
+        let expansions = &self.options.expansions;
+        if expansions.is_empty() {
+            return false;
+        }
+        let bline = &self.blines[bid];
+        expansions.is_expanded(&bline.path)
+            || bline.parent_id.map_or(false, |pid| expansions.is_expanded(&self.blines[pid].path))
+    }
+
     /// Post search trimming
     /// When there's a pattern, gathering normally brings many more lines than
     ///  strictly necessary to fill the screen.
     /// This function keeps only the best ones while taking care of not
     ///  removing a parent before its children.
+    /// Lines pinned by an expansion don't count in the size to fit: they
+    ///  push down the other ones, which are trimmed as if they weren't there.
     fn trim_excess(&mut self, out_blines: &[BId]) {
         let mut count = 1;
         for id in out_blines[1..].iter() {
             if self.blines[*id].has_match {
                 //debug!("bline before trimming: {:?}", &self.blines[*idx].path);
-                count += 1;
+                if !self.is_pinned(*id) {
+                    count += 1;
+                }
                 let parent_id = self.blines[*id].parent_id.unwrap();
                 // (we can unwrap because only the root can have a None parent)
                 self.blines[parent_id].nb_kept_children += 1;
@@ -400,7 +775,10 @@ impl<'c> TreeBuilder<'c> {
         let mut remove_queue: BinaryHeap<SortableBId> = BinaryHeap::new();
         for id in out_blines[1..].iter() {
             let bline = &self.blines[*id];
-            if bline.has_match && bline.nb_kept_children == 0 && (bline.depth > 1 || self.trim_root)
+            if bline.has_match
+                && bline.nb_kept_children == 0
+                && (bline.depth > 1 || self.trim_root)
+                && !self.is_pinned(*id)
             {
                 //debug!("in list: {:?} score: {}",  &bline.path, bline.score);
                 remove_queue.push(SortableBId {
@@ -416,10 +794,10 @@ impl<'c> TreeBuilder<'c> {
                 let parent = &mut self.blines[parent_id];
                 parent.nb_kept_children -= 1;
                 parent.next_child_idx -= 1; // to fix the number of "unlisted"
-                if parent.nb_kept_children == 0 {
+                if parent.nb_kept_children == 0 && !self.is_pinned(parent_id) {
                     remove_queue.push(SortableBId {
                         id: parent_id,
-                        score: parent.score,
+                        score: self.blines[parent_id].score,
                     });
                 }
                 count -= 1;
@@ -436,7 +814,7 @@ impl<'c> TreeBuilder<'c> {
         for id in out_blines.iter() {
             if self.blines[*id].has_match {
                 // we need to count the children, so we load them
-                if self.blines[*id].can_enter() && self.blines[*id].children.is_none() {
+                if self.blines[*id].children.is_none() && self.can_list(*id) {
                     self.load_children(*id);
                 }
                 if let Ok(tree_line) = self.blines[*id].to_tree_line(*id, self.con) {
@@ -458,6 +836,9 @@ impl<'c> TreeBuilder<'c> {
             total_search: self.total_search,
             git_status: ComputationResult::None,
             build_report: self.report,
+            gone_count: 0,
+            stats: TreeStats::default(),
+            search_continuation: self.continuation.map(Box::new),
         };
         tree.after_lines_changed();
         if let Some(computer) = self.line_status_computer {
@@ -507,3 +888,273 @@ impl<'c> TreeBuilder<'c> {
             })
     }
 }
+
+#[test]
+fn test_parallel_build_is_sequential_build() {
+    use {
+        crate::tree::test_util::{app_context, build_tree_with},
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    for i in 0..150 {
+        let sub = root.join(format!("d{}/e{}", i % 7, i));
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(sub.join(format!("f{i}.txt")), "").unwrap();
+    }
+    let con = app_context(&root);
+    let paths = || {
+        build_tree_with(&con, &root, TreeOptions::default(), 100, true)
+            .lines
+            .iter()
+            .map(|line| line.path.clone())
+            .collect::<Vec<PathBuf>>()
+    };
+    let sequential = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap()
+        .install(paths);
+    assert_eq!(sequential.len(), 1 + 7 + 150 + 150);
+    assert_eq!(paths(), sequential);
+}
+
+#[test]
+fn test_expansions() {
+    use crate::tree::test_util::build_tree;
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    for d in ["a", "b"] {
+        for i in 0..30 {
+            fs::create_dir_all(root.join(format!("{d}/s{i}"))).unwrap();
+            fs::write(root.join(format!("{d}/s{i}/f.txt")), "").unwrap();
+        }
+    }
+    let build = |options: &TreeOptions| {
+        build_tree(&root, options.clone(), 10)
+    };
+    let mut options = TreeOptions::default();
+    let tree = build(&options);
+    assert!(tree.lines.len() <= 10);
+    // all the children of an expanded directory are listed
+    options.expansions.set(root.join("a"), true);
+    let tree = build(&options);
+    for i in 0..30 {
+        assert!(tree.lines.iter().any(|line| line.path == root.join(format!("a/s{i}"))));
+    }
+    assert!(tree.lines.iter().any(|line| line.path == root.join("b")));
+    // even the ones of a directory deeper in an expanded one
+    options.expansions.set(root.join("a/s3"), true);
+    let tree = build(&options);
+    assert!(tree.lines.iter().any(|line| line.path == root.join("a/s3/f.txt")));
+    // a collapsed directory has no listed child
+    options.expansions.set(root.join("a"), false);
+    let tree = build(&options);
+    let a = tree.lines.iter().position(|line| line.path == root.join("a")).unwrap();
+    assert!(tree.lines.iter().all(|line| line.parent_index != Some(a)));
+    assert_eq!(tree.lines[a].unlisted, 30);
+}
+
+#[test]
+fn test_max_children() {
+    use crate::tree::test_util::build_tree;
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::create_dir_all(root.join("b")).unwrap();
+    for i in 0..30 {
+        fs::write(root.join(format!("a/f{i}.txt")), "").unwrap();
+    }
+    for i in 0..3 {
+        fs::write(root.join(format!("b/f{i}.txt")), "").unwrap();
+    }
+    let build = |options: &TreeOptions| {
+        build_tree(&root, options.clone(), 100)
+    };
+    let children = |tree: &Tree, path: &str| {
+        let idx = tree.lines.iter().position(|line| line.path == root.join(path)).unwrap();
+        tree.lines.iter().filter(|line| line.parent_index == Some(idx)).count()
+    };
+    let mut options = TreeOptions::default();
+    let tree = build(&options);
+    assert_eq!(children(&tree, "a"), 30);
+    // with a limit, the last listed line tells exactly how many are missing
+    options.max_children = MaxChildren::Count(5);
+    let tree = build(&options);
+    assert_eq!(children(&tree, "a"), 5);
+    assert_eq!(children(&tree, "b"), 3);
+    let pruning = tree.lines.iter().find(|line| line.line_type == TreeLineType::Pruning).unwrap();
+    assert_eq!(pruning.unlisted, 26);
+    // an expanded directory isn't limited
+    options.expansions.set(root.join("a"), true);
+    let tree = build(&options);
+    assert_eq!(children(&tree, "a"), 30);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_link_loops_are_not_entered() {
+    use {
+        crate::tree::test_util::app_context,
+        std::os::unix::fs::symlink,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::create_dir_all(root.join("c")).unwrap();
+    symlink(root.join("a"), root.join("a/loop")).unwrap();
+    symlink(root.join("c"), root.join("a/to_c")).unwrap();
+    let con = app_context(&root);
+    let mut options = TreeOptions::default();
+    options.follow_links = true;
+    let mut builder = TreeBuilder::from(root.clone(), options, 50, &con).unwrap();
+    let child = |builder: &TreeBuilder, parent_id: BId, name: &str| {
+        *builder.blines[parent_id].children.as_ref().unwrap()
+            .iter()
+            .find(|&&id| builder.blines[id].name == name)
+            .unwrap()
+    };
+    let root_id = builder.root_id;
+    builder.load_children(root_id);
+    let a = child(&builder, root_id, "a");
+    assert!(builder.can_enter(a));
+    builder.load_children(a);
+    // a link to an ancestor isn't entered, and is flagged
+    let loop_id = child(&builder, a, "loop");
+    assert!(!builder.can_enter(loop_id));
+    assert_eq!(builder.blines[loop_id].error, Some(LineError::LinkLoop));
+    // a link to another directory is followed
+    let to_c = child(&builder, a, "to_c");
+    assert!(builder.can_enter(to_c));
+    assert_eq!(builder.blines[to_c].error, None);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_dirs_first_includes_links_to_dirs() {
+    use crate::tree::test_util::build_tree;
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    for name in ["a.txt", "c.txt", "d.txt"] {
+        fs::write(root.join(name), "").unwrap();
+    }
+    fs::create_dir_all(root.join("y")).unwrap();
+    fs::create_dir_all(root.join("z")).unwrap();
+    std::os::unix::fs::symlink(root.join("z"), root.join("b")).unwrap();
+    let options = TreeOptions {
+        dirs_first: true,
+        // only the first children are kept, the last one
+        // being replaced with the "unlisted" line
+        max_children: MaxChildren::Count(3),
+        ..TreeOptions::default()
+    };
+    let tree = build_tree(&root, options, 100);
+    let names: Vec<&str> = tree.lines.iter()
+        .filter(|line| line.depth == 1 && line.line_type != TreeLineType::Pruning)
+        .map(|line| line.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["b", "y"]);
+}
+
+#[test]
+fn test_search_time_budget() {
+    use crate::{
+        command::CommandParts,
+        pattern::InputPattern,
+        tree::test_util::{app_context, build_tree_with},
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    for i in 0..20 {
+        fs::create_dir_all(root.join(format!("d{i}"))).unwrap();
+        fs::write(root.join(format!("d{i}/f.txt")), "").unwrap();
+    }
+    let mut con = app_context(&root);
+    let mut options = TreeOptions::default();
+    options.pattern = InputPattern::new("txt".to_string(), &CommandParts::from("txt").pattern, &con).unwrap();
+    // the budget applies even when too few matches were found
+    con.search_time_budget = std::time::Duration::ZERO;
+    let tree = build_tree_with(&con, &root, options.clone(), 100, false);
+    assert!(!tree.total_search);
+    assert!(tree.lines.len() < 21);
+    // a total search ignores it
+    let tree = build_tree_with(&con, &root, options, 100, true);
+    assert!(tree.total_search);
+    assert_eq!(tree.lines.iter().filter(|line| line.is_file()).count(), 20);
+}
+
+#[test]
+fn test_resumed_search_is_total_search() {
+    use crate::{
+        command::CommandParts,
+        pattern::InputPattern,
+        tree::test_util::app_context,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    for i in 0..40 {
+        for j in 0..5 {
+            let sub = root.join(format!("d{i}/e{j}"));
+            fs::create_dir_all(&sub).unwrap();
+            fs::write(sub.join(format!("f{i}_{j}.txt")), "").unwrap();
+        }
+    }
+    let mut con = app_context(&root);
+    let mut options = TreeOptions::default();
+    options.pattern = InputPattern::new("txt".to_string(), &CommandParts::from("txt").pattern, &con).unwrap();
+    let paths = |tree: &Tree| -> Vec<PathBuf> {
+        tree.lines.iter().map(|line| line.path.clone()).collect()
+    };
+    let build = |con: &AppContext, continuation: Option<SearchContinuation>, total_search: bool| {
+        let mut builder = TreeBuilder::from(root.clone(), options.clone(), 10, con).unwrap();
+        if let Some(continuation) = continuation {
+            builder.resume(continuation);
+        }
+        builder.build_tree(total_search, &Dam::unlimited()).unwrap()
+    };
+    let total = paths(&build(&con, None, true));
+    assert_eq!(total.iter().filter(|path| path.is_file()).count(), 200);
+    // the search is stopped either because there are enough matches,
+    // or because there's no time left
+    for budget in [std::time::Duration::from_secs(100), std::time::Duration::ZERO] {
+        con.search_time_budget = budget;
+        let mut partial = build(&con, None, false);
+        assert!(!partial.total_search);
+        let continuation = partial.search_continuation.take().unwrap();
+        assert!(continuation.walk.out_blines.len() < total.len());
+        if !budget.is_zero() {
+            // stopped in the middle of the walk
+            assert!(continuation.walk.out_blines.len() > 1);
+        }
+        let resumed = build(&con, Some(*continuation), true);
+        assert!(resumed.total_search);
+        assert!(resumed.search_continuation.is_none());
+        assert_eq!(paths(&resumed), total);
+    }
+}
+
+#[test]
+fn test_hidden_children_count() {
+    use crate::tree::test_util::build_tree;
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_path_buf();
+    fs::create_dir_all(root.join("a/.git")).unwrap();
+    for name in [".env", "b.txt", "a/.hidden", "a/c.txt"] {
+        fs::write(root.join(name), "").unwrap();
+    }
+    let hidden = |tree: &Tree, path: &str| {
+        tree.lines.iter().find(|line| line.path == root.join(path)).unwrap().hidden
+    };
+    let tree = build_tree(&root, TreeOptions::default(), 50);
+    assert_eq!(hidden(&tree, ""), 1);
+    assert_eq!(hidden(&tree, "a"), 2);
+    // hidden entries aren't unlisted ones, there's no pruning line
+    assert!(!tree.has_unlisted());
+    assert!(tree.lines.iter().all(|line| line.line_type != TreeLineType::Pruning));
+    let options = TreeOptions {
+        show_hidden: true,
+        ..TreeOptions::default()
+    };
+    let tree = build_tree(&root, options, 50);
+    assert_eq!(hidden(&tree, ""), 0);
+    assert_eq!(hidden(&tree, "a"), 0);
+}
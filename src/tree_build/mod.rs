@@ -5,6 +5,6 @@ mod builder;
 
 pub use {
     bid::BId,
-    builder::TreeBuilder,
+    builder::{DEFAULT_SEARCH_TIME_BUDGET_MS, SearchContinuation, TreeBuilder},
     build_report::BuildReport,
 };
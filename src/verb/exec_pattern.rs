@@ -4,6 +4,7 @@ use {
     },
     serde::Deserialize,
     std::{
+        env,
         path::Path,
         fmt,
     },
@@ -30,6 +31,14 @@ impl ExecPattern {
             Self::Array(v) => v.iter().any(|s| str_has_selection_group(s)),
         }
     }
+    /// tell whether some token contains `{files}`, to be expanded
+    /// into one token per selected path
+    pub fn has_files_group(&self) -> bool {
+        match self {
+            Self::String(s) => s.contains("{files}"),
+            Self::Array(v) => v.iter().any(|token| token.contains("{files}")),
+        }
+    }
     pub fn has_other_panel_group(&self) -> bool {
         match self {
             Self::String(s) => str_has_other_panel_group(s),
@@ -68,18 +77,19 @@ impl ExecPattern {
     pub fn tokenize(self) -> Self {
         Self::Array(self.into_array())
     }
-    pub fn apply(&self, f: &dyn Fn(&str) -> String) -> Self {
+    /// replace every token with the ones returned by the function
+    pub fn apply(&self, f: &dyn Fn(&str) -> Vec<String>) -> Self {
         Self::Array(
             match self {
                 Self::String(s) => {
                     splitty::split_unquoted_whitespace(s)
                         .unwrap_quotes(true)
-                        .map(|s| f(s))
+                        .flat_map(f)
                         .collect()
                 }
                 Self::Array(v) => {
                     v.iter()
-                        .map(|s| f(s))
+                        .flat_map(|s| f(s))
                         .collect()
                 }
             }
@@ -102,6 +112,45 @@ impl ExecPattern {
     }
 }
 
+/// tell whether the string can be written in a shell command
+/// without quotes
+fn is_shell_safe(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || "/._-+,:@%=".contains(c))
+}
+
+/// The shell which will read the commands written for the br function,
+/// as told by this function in the `BR_SHELL` environment variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetShell {
+    Posix, // bash, zsh, fish
+    Nushell,
+    PowerShell,
+}
+
+impl TargetShell {
+    fn detect() -> Self {
+        match env::var("BR_SHELL").as_deref() {
+            Ok("nushell") => Self::Nushell,
+            Ok("powershell") => Self::PowerShell,
+            Ok(_) => Self::Posix,
+            Err(_) if cfg!(windows) => Self::PowerShell,
+            Err(_) => Self::Posix,
+        }
+    }
+    /// quote the string so that no char is interpreted by the shell
+    fn quote(self, s: &str) -> String {
+        match self {
+            Self::Posix => format!("'{}'", s.replace('\'', r"'\''")),
+            Self::PowerShell => format!("'{}'", s.replace('\'', "''")),
+            // the nushell function doesn't evaluate the command but takes
+            // the path verbatim, between the quotes if any
+            Self::Nushell if !s.contains('\'') => format!("'{s}'"),
+            Self::Nushell if !s.contains('"') => format!("\"{s}\""),
+            Self::Nushell => s.to_string(),
+        }
+    }
+}
+
 fn fix_token_path<T: Into<String> + AsRef<str>>(token: T) -> String {
     let path = Path::new(token.as_ref());
     if path.exists() {
@@ -122,7 +171,12 @@ impl fmt::Display for ExecPattern {
                     if idx > 0 {
                         write!(f, " ")?;
                     }
-                    if s.contains(' ') {
+                    if Path::new(s).exists() && !is_shell_safe(s) {
+                        // a path, which may come from a selection: it must not
+                        // be expanded or executed when the string is evaluated
+                        // by the shell (eg by the br function)
+                        write!(f, "{}", TargetShell::detect().quote(s))?;
+                    } else if s.contains(' ') {
                         write!(f, "\"{s}\"")?;
                     } else {
                         write!(f, "{s}")?;
@@ -133,3 +187,28 @@ impl fmt::Display for ExecPattern {
         }
     }
 }
+
+#[cfg(not(windows))]
+#[test]
+fn test_paths_quoting() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("it's $(here)");
+    std::fs::create_dir(&path).unwrap();
+    let path = path.to_string_lossy().to_string();
+    let pattern = ExecPattern::Array(vec!["cd".to_string(), path.clone()]);
+    let expected = format!("cd '{}'", path.replace('\'', r"'\''"));
+    assert_eq!(pattern.to_string(), expected);
+    // tokens which aren't existing paths are written as before
+    let pattern = ExecPattern::Array(vec!["cd".to_string(), "$HOME/a b".to_string()]);
+    assert_eq!(pattern.to_string(), r#"cd "$HOME/a b""#);
+}
+
+#[test]
+fn test_quoting_per_shell() {
+    let path = r#"/a b/it's "$(here)""#;
+    assert_eq!(TargetShell::Posix.quote(path), r#"'/a b/it'\''s "$(here)"'"#);
+    assert_eq!(TargetShell::PowerShell.quote(path), r#"'/a b/it''s "$(here)"'"#);
+    assert_eq!(TargetShell::Nushell.quote(path), path);
+    assert_eq!(TargetShell::Nushell.quote("/a b/$(here)"), "'/a b/$(here)'");
+    assert_eq!(TargetShell::Nushell.quote("/it's"), r#""/it's""#);
+}
@@ -212,7 +212,7 @@ impl<'b> ExecutionStringBuilder<'b> {
                             .map(|default_name| default_name.as_str())
                             .and_then(|default_name|
                                 self.get_raw_replacement(|sel|
-                                    self.get_raw_sel_name_standard_replacement(default_name, sel)
+                                    self.get_raw_sel_name_default(default_name, sel)
                                 )
                             )
                             .unwrap_or_default()
@@ -223,6 +223,58 @@ impl<'b> ExecutionStringBuilder<'b> {
         }
     }
 
+    /// the value prefilled in the input for an argument whose
+    /// default is given by name: the standard replacement or, for
+    /// paths typed from a directory, this directory
+    fn get_raw_sel_name_default(
+        &self,
+        name: &str,
+        sel: Option<Selection<'_>>,
+    ) -> Option<String> {
+        let dir = match name {
+            "path-from-parent" => sel.and_then(|s| s.path.parent()).map(Path::to_path_buf),
+            "path-from-directory" => sel.map(|s| path::closest_dir(s.path)),
+            _ => {
+                return self.get_raw_sel_name_standard_replacement(name, sel);
+            }
+        };
+        dir.map(|dir| {
+            let mut dir = path_to_string(dir);
+            if !dir.ends_with(std::path::MAIN_SEPARATOR) {
+                dir.push(std::path::MAIN_SEPARATOR);
+            }
+            dir
+        })
+    }
+
+    /// all the selected paths: the ones of the staging area
+    /// when it's the selection
+    fn selected_paths(&self) -> Vec<String> {
+        match self.sel_info {
+            SelInfo::None => Vec::new(),
+            SelInfo::One(sel) => vec![path_to_string(sel.path)],
+            SelInfo::More(stage) => stage.paths().iter().map(path_to_string).collect(),
+        }
+    }
+
+    /// replace the groups of the token. A token containing `{files}`
+    /// is repeated for each selected path.
+    fn expand_token(
+        &self,
+        token: &str,
+        replace: &dyn Fn(&Captures<'_>) -> String,
+    ) -> Vec<String> {
+        let token = GROUP.replace_all(token, replace);
+        if token.contains("{files}") {
+            self.selected_paths()
+                .iter()
+                .map(|path| token.replace("{files}", path))
+                .collect()
+        } else {
+            vec![token.to_string()]
+        }
+    }
+
     fn base_dir(&self) -> &Path {
         self.sel_info
             .one_sel()
@@ -249,12 +301,7 @@ impl<'b> ExecutionStringBuilder<'b> {
         exec_pattern: &ExecPattern,
     ) -> String {
         exec_pattern
-            .apply(&|s| {
-                GROUP.replace_all(
-                    s,
-                    |ec: &Captures<'_>| self.get_capture_replacement(ec),
-                ).to_string()
-            })
+            .apply(&|s| self.expand_token(s, &|ec| self.get_capture_replacement(ec)))
             .fix_paths()
             .to_string()
     }
@@ -267,12 +314,7 @@ impl<'b> ExecutionStringBuilder<'b> {
         sel: Option<Selection<'_>>,
     ) -> String {
         exec_pattern
-            .apply(&|s| {
-                GROUP.replace_all(
-                    s,
-                    |ec: &Captures<'_>| self.get_sel_capture_replacement(ec, sel),
-                ).to_string()
-            })
+            .apply(&|s| self.expand_token(s, &|ec| self.get_sel_capture_replacement(ec, sel)))
             .fix_paths()
             .to_string()
     }
@@ -283,12 +325,7 @@ impl<'b> ExecutionStringBuilder<'b> {
         exec_pattern: &ExecPattern,
     ) -> Vec<String> {
         exec_pattern
-            .apply(&|s| {
-                GROUP.replace_all(
-                    s,
-                    |ec: &Captures<'_>| self.get_capture_replacement(ec),
-                ).to_string()
-            })
+            .apply(&|s| self.expand_token(s, &|ec| self.get_capture_replacement(ec)))
             .fix_paths()
             .into_array()
     }
@@ -300,12 +337,7 @@ impl<'b> ExecutionStringBuilder<'b> {
         sel: Option<Selection<'_>>,
     ) -> Vec<String> {
         exec_pattern
-            .apply(&|s| {
-                GROUP.replace_all(
-                    s,
-                    |ec: &Captures<'_>| self.get_sel_capture_replacement(ec, sel),
-                ).to_string()
-            })
+            .apply(&|s| self.expand_token(s, &|ec| self.get_sel_capture_replacement(ec, sel)))
             .fix_paths()
             .into_array()
     }
@@ -350,6 +382,8 @@ mod execution_builder_test {
             stage: Stage::default(),
             root: PathBuf::from("/".to_owned()),
             other_panel_path: None,
+            marks: Marks::default(),
+            refresh_other_panels: false,
         };
         let mut builder = ExecutionStringBuilder::without_invocation(
             SelInfo::One(sel),
@@ -392,6 +426,56 @@ mod execution_builder_test {
             vec![],
             vec!["xterm", "-e", "kak /path/to/file"],
         );
+        check_build_execution_from_sel(
+            vec![
+                ExecPattern::from_string("cp {file} {parent}/{new_name}"),
+                ExecPattern::from_array(vo!["cp", "{file}", "{parent}/{new_name}"]),
+            ],
+            "/home/dys/it's a \"test\"/notes.md",
+            vec![("new_name", "l'été.md")],
+            vec!["cp", "/home/dys/it's a \"test\"/notes.md", "/home/dys/it's a \"test\"/l'été.md"],
+        );
+        check_build_execution_from_sel(
+            vec![ExecPattern::from_string("ls {directory}")],
+            "/tmp/$HOME; rm -rf ~/file",
+            vec![],
+            vec!["ls", "/tmp"],
+        );
+    }
+
+    #[test]
+    fn test_files_expansion() {
+        let mut stage = Stage::default();
+        stage.add(PathBuf::from("/a/b c"));
+        stage.add(PathBuf::from("/d"));
+        let app_state = AppState {
+            stage,
+            root: PathBuf::from("/".to_owned()),
+            other_panel_path: None,
+            marks: Marks::default(),
+            refresh_other_panels: false,
+        };
+        let builder = ExecutionStringBuilder::without_invocation(
+            SelInfo::More(&app_state.stage),
+            &app_state,
+        );
+        for exec_pattern in [
+            ExecPattern::from_string("tar czf out.tgz {files}"),
+            ExecPattern::from_array(vo!["tar", "czf", "out.tgz", "{files}"]),
+        ] {
+            assert!(exec_pattern.has_files_group());
+            assert_eq!(
+                builder.exec_token(&exec_pattern),
+                vec!["tar", "czf", "out.tgz", "/a/b c", "/d"],
+            );
+        }
+        // a token containing {files} is repeated for each path
+        let exec_pattern = ExecPattern::from_string("cmd --file={files} {root}");
+        assert!(exec_pattern.has_files_group());
+        assert_eq!(
+            builder.exec_token(&exec_pattern),
+            vec!["cmd", "--file=/a/b c", "--file=/d", "/"],
+        );
     }
 }
 
@@ -23,6 +23,7 @@ pub struct ExternalExecution {
     /// pattern (like {my-arg}) and special names automatically filled by
     /// broot from the selection and application state:
     /// * {file}
+    /// * {files}
     /// * {directory}
     /// * {parent}
     /// * {other-panel-file}
@@ -40,6 +41,10 @@ pub struct ExternalExecution {
     /// whether we need to switch to the normal terminal for
     /// the duration of the execution of the process
     pub switch_terminal: bool,
+
+    /// whether the tree must be refreshed after an execution
+    /// which doesn't leave broot
+    pub refresh_after: bool,
 }
 
 impl ExternalExecution {
@@ -52,6 +57,7 @@ impl ExternalExecution {
             exec_mode,
             working_dir: None,
             switch_terminal: true, // by default we switch
+            refresh_after: true,
         }
     }
 
@@ -110,7 +116,7 @@ impl ExternalExecution {
         builder: ExecutionStringBuilder<'_>,
         con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
-        if builder.sel_info.count_paths() > 1 {
+        if builder.sel_info.count_paths() > 1 && !self.exec_pattern.has_files_group() {
             return Ok(CmdResult::error(
                 "only verbs returning to broot on end can be executed on a multi-selection"
             ));
@@ -135,7 +141,7 @@ impl ExternalExecution {
         builder: ExecutionStringBuilder<'_>,
         con: &AppContext,
     ) -> Result<CmdResult, ProgramError> {
-        if builder.sel_info.count_paths() > 1 {
+        if builder.sel_info.count_paths() > 1 && !self.exec_pattern.has_files_group() {
             return Ok(CmdResult::error(
                 "only verbs returning to broot on end can be executed on a multi-selection"
             ));
@@ -159,21 +165,7 @@ impl ExternalExecution {
     ) -> Result<CmdResult, ProgramError> {
         let working_dir_path = self.working_dir_path(&builder);
         match &builder.sel_info {
-            SelInfo::None | SelInfo::One(_) => {
-                // zero or one selection -> only one execution
-                let launchable = Launchable::program(
-                    builder.exec_token(&self.exec_pattern),
-                    working_dir_path,
-                    self.switch_terminal,
-                    con,
-                )?;
-                info!("Executing not leaving, launchable {:?}", launchable);
-                if let Err(e) = launchable.execute(Some(w)) {
-                    warn!("launchable failed : {:?}", e);
-                    return Ok(CmdResult::error(e.to_string()));
-                }
-            }
-            SelInfo::More(stage) => {
+            SelInfo::More(stage) if !self.exec_pattern.has_files_group() => {
                 // multiselection -> we must execute on all paths
                 let sels = stage.paths().iter()
                     .map(|path| Selection {
@@ -195,7 +187,26 @@ impl ExternalExecution {
                     }
                 }
             }
+            _ => {
+                // zero or one selection, or all paths given at once
+                // with {files} -> only one execution
+                let launchable = Launchable::program(
+                    builder.exec_token(&self.exec_pattern),
+                    working_dir_path,
+                    self.switch_terminal,
+                    con,
+                )?;
+                info!("Executing not leaving, launchable {:?}", launchable);
+                if let Err(e) = launchable.execute(Some(w)) {
+                    warn!("launchable failed : {:?}", e);
+                    return Ok(CmdResult::error(e.to_string()));
+                }
+            }
+        }
+        if self.refresh_after {
+            Ok(CmdResult::RefreshState { clear_cache: true })
+        } else {
+            Ok(CmdResult::Keep)
         }
-        Ok(CmdResult::RefreshState { clear_cache: true })
     }
 }
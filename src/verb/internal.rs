@@ -55,10 +55,17 @@ macro_rules! Internals {
 Internals! {
     back: "revert to the previous state (mapped to *esc*)" false,
     escape: "escape from edition, completion, page, etc." false,
+    expand_dir: "list all the children of the selected directory, or of the parent of the selected file" true,
+    chmod: "change the mode of the selection" true,
     close_panel_ok: "close the panel, validating the selected path" false,
     close_panel_cancel: "close the panel, not using the selected path" false,
     copy_line: "copy selected line (in tree or preview)" true,
     copy_path: "copy path to system clipboard" true,
+    copy_relative_path: "copy path relative to the tree root to system clipboard" true,
+    copy_file_name: "copy file name to system clipboard" true,
+    copy_to_panel: "copy the selection to the directory of the other panel" true,
+    cp: "copy the selection to a new path" true,
+    edit: "edit the selected file in $EDITOR" true,
     filesystems: "list mounted filesystems" false,
     focus: "display the directory (mapped to *enter*)" true,
     help: "display broot's help" false,
@@ -83,13 +90,19 @@ Internals! {
     open_stay: "open file or directory according to OS (stay in broot)" true,
     open_stay_filter: "display the directory, keeping the current pattern" true,
     open_leave: "open file or directory according to OS (quit broot)" true,
+    mark: "mark the selected path with a char" true,
+    go_to_mark: "select the path marked with a char" false,
+    mkdir: "create a directory in the selected one, or next to the selected file" true,
     mode_input: "enter the input mode" false,
     mode_command: "enter the command mode" false,
+    move_to_panel: "move the selection to the directory of the other panel" true,
+    mv: "move the selection to a new path" true,
     previous_dir: "select the previous directory" false,
     next_dir: "select the next directory" false,
     previous_match: "select the previous match" false,
     next_match: "select the next match" false,
     next_same_depth: "select the next file at the same depth" false,
+    next_sibling: "select the next file in the same directory" false,
     no_sort: "don't sort" false,
     page_down: "scroll one page down" false,
     page_up: "scroll one page up" false,
@@ -99,6 +112,7 @@ Internals! {
     panel_left_no_open: "focus panel on left" false,
     panel_right_no_open: "focus panel on right" false,
     previous_same_depth: "select the previous file at the same depth" false,
+    previous_sibling: "select the previous file in the same directory" false,
     open_preview: "open the preview panel" true,
     close_preview: "close the preview panel" false,
     toggle_preview: "open/close the preview panel" false,
@@ -111,16 +125,29 @@ Internals! {
     start_end_panel: "either open or close an additional panel" true,
     quit: "quit Broot" false,
     refresh: "refresh tree and clear size cache" false,
+    soft_refresh: "refresh tree, keeping the size cache" false,
+    rename: "rename the selection in its directory" true,
+    rm: "move the selection to the trash" true,
+    rm_permanently: "delete the selection (with a confirmation for directories with content)" true,
     root_up: "move tree root up" true,
     root_down: "move tree root down" true,
+    scroll_down: "scroll down, keeping the selection" false,
+    scroll_up: "scroll up, keeping the selection" false,
     //restore_pattern: "restore a pattern which was just removed" false,
     select_first: "select the first item" false,
     select_last: "select the last item" false,
+    select_parent: "select the parent directory" false,
+    selection_back: "select the previously selected path" false,
+    selection_forward: "select the next path in the selection history" false,
     select: "select a file by path" true,
+    select_line: "select a line by its number" false,
+    set_max_depth: "set the depth past which directories aren't entered" false,
     set_syntax_theme: "set the theme of code preview" false,
     sort_by_count: "sort by count" false,
     sort_by_date: "sort by date" false,
+    sort_by_date_in_tree: "sort the children of every directory by date" false,
     sort_by_size: "sort by size" false,
+    sort_by_size_in_tree: "sort the children of every directory by size" false,
     sort_by_type: "sort by type" false,
     sort_by_type_dirs_first: "sort by type, dirs first" false,
     sort_by_type_dirs_last: "sort by type, dirs last" false,
@@ -135,40 +162,73 @@ Internals! {
     toggle_counts: "toggle showing number of files in directories" false,
     toggle_dates: "toggle showing last modified dates" false,
     toggle_device_id: "toggle showing device id" false,
+    toggle_dirs_first: "toggle listing directories before files" false,
+    toggle_expansion: "expand or collapse the selected directory" true,
     toggle_files: "toggle showing files (or just folders)" false,
+    toggle_flat: "toggle displaying lines as paths, without branches" false,
     toggle_git_ignore: "toggle use of .gitignore" false,
     toggle_git_file_info: "toggle display of git file information" false,
     toggle_git_status: "toggle showing only files relevant for git status" false,
     toggle_root_fs: "toggle showing filesystem info on top" false,
     toggle_hidden: "toggle showing hidden files" false,
+    toggle_hard_links: "toggle showing the number of hard links of files" false,
+    toggle_line_numbers: "toggle showing line numbers" false,
+    toggle_one_file_system: "toggle staying on the filesystem of the root" false,
     toggle_perm: "toggle showing file permissions" false,
     toggle_sizes: "toggle showing sizes" false,
     toggle_trim_root: "toggle removing nodes at first level too" false,
     toggle_second_tree: "toggle display of a second tree panel" true,
+    toggle_mouse_capture: "toggle capturing the mouse, to allow the terminal's text selection" false,
+    toggle_watch: "toggle refreshing trees on changes of their directories" false,
     total_search: "search again but on all children" false,
+    touch: "create an empty file in the selected directory, or next to the selected file" true,
     up_tree: "focus the parent of the current root" true,
 }
 
 impl Internal {
     pub fn invocation_pattern(self) -> &'static str {
         match self {
+            Internal::chmod => r"chmod {mode}",
+            Internal::cp => r"cp {newpath:path-from-parent}",
             Internal::focus => r"focus (?P<path>.*)?",
             Internal::select => r"select (?P<path>.*)?",
+            Internal::select_line => r"select_line (?P<line>\d*)?",
             Internal::line_down => r"line_down (?P<count>\d*)?",
             Internal::line_up => r"line_up (?P<count>\d*)?",
             Internal::line_down_no_cycle => r"line_down_no_cycle (?P<count>\d*)?",
             Internal::line_up_no_cycle => r"line_up_no_cycle (?P<count>\d*)?",
+            Internal::scroll_down => r"scroll_down (?P<count>\d*)?",
+            Internal::scroll_up => r"scroll_up (?P<count>\d*)?",
+            Internal::rename => r"rename {new_filename:file-name}",
             Internal::set_syntax_theme => r"set_syntax_theme {theme:theme}",
+            Internal::set_max_depth => r"set_max_depth (?P<depth>\d*)?",
+            Internal::touch => r"touch {subpath:path-from-directory}",
+            Internal::mark => r"mark (?P<mark>\S)?",
+            Internal::mkdir => r"mkdir {subpath:path-from-directory}",
+            Internal::mv => r"mv {newpath:path-from-parent}",
+            Internal::go_to_mark => r"go_to_mark (?P<mark>\S)?",
             _ => self.name(),
         }
     }
     pub fn exec_pattern(self) -> &'static str {
         match self {
+            Internal::chmod => r"chmod {mode}",
+            Internal::cp => r"cp {newpath}",
             Internal::focus => r"focus {path}",
             Internal::line_down => r"line_down {count}",
+            Internal::select_line => r"select_line {line}",
             Internal::line_up => r"line_up {count}",
             Internal::line_down_no_cycle => r"line_down_no_cycle {count}",
             Internal::line_up_no_cycle => r"line_up_no_cycle {count}",
+            Internal::scroll_down => r"scroll_down {count}",
+            Internal::scroll_up => r"scroll_up {count}",
+            Internal::rename => r"rename {new_filename}",
+            Internal::set_max_depth => r"set_max_depth {depth}",
+            Internal::touch => r"touch {subpath}",
+            Internal::mark => r"mark {mark}",
+            Internal::mkdir => r"mkdir {subpath}",
+            Internal::mv => r"mv {newpath}",
+            Internal::go_to_mark => r"go_to_mark {mark}",
             _ => self.name(),
         }
     }
@@ -178,4 +238,29 @@ impl Internal {
             _ => self.need_path(),
         }
     }
+    /// whether the internal acts on the file system, and so can't be
+    /// applied to the synthetic path of a file in an archive
+    pub fn needs_real_path(self) -> bool {
+        matches!(
+            self,
+            Internal::chmod
+                | Internal::copy_to_panel
+                | Internal::cp
+                | Internal::edit
+                | Internal::mkdir
+                | Internal::move_to_panel
+                | Internal::mv
+                | Internal::open_leave
+                | Internal::rename
+                | Internal::rm
+                | Internal::rm_permanently
+                | Internal::stage
+                | Internal::toggle_stage
+                | Internal::touch
+        )
+    }
+    /// whether the internal writes in the directory of the other panel
+    pub fn needs_other_panel_dir(self) -> bool {
+        matches!(self, Internal::copy_to_panel | Internal::move_to_panel)
+    }
 }
@@ -0,0 +1,520 @@
+//! utility functions to help handle the `:cp` and `:mv` internals
+
+use {
+    super::internal_rm::remove_path,
+    crate::task_sync::Dam,
+    std::{
+        fs,
+        io,
+        path::{Path, PathBuf},
+        time::{Duration, Instant},
+    },
+};
+
+/// how long a step of a transfer may last before giving
+/// the application a chance to display the progress
+const STEP_DURATION: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferKind {
+    Copy,
+    Move,
+}
+
+impl TransferKind {
+    pub fn verb(self) -> &'static str {
+        match self {
+            Self::Copy => "copy",
+            Self::Move => "move",
+        }
+    }
+    /// the name of the internal, as typed by the user
+    pub fn internal_name(self) -> &'static str {
+        match self {
+            Self::Copy => "cp",
+            Self::Move => "mv",
+        }
+    }
+    pub fn progress_name(self) -> &'static str {
+        match self {
+            Self::Copy => "copying",
+            Self::Move => "moving",
+        }
+    }
+}
+
+/// Transfers which can't be done without a confirmation, because
+/// some destinations already exist and would be overwritten.
+///
+/// The confirmation is the same verb, which must be the very
+/// next one (typing it doesn't cancel the request).
+#[derive(Debug)]
+pub struct OverwriteRequest {
+    paths: Vec<(PathBuf, PathBuf)>, // (source, destination)
+    fresh: bool, // true until the state executes another verb
+}
+
+impl OverwriteRequest {
+    /// To be called before the execution of every verb: a request
+    /// which isn't confirmed by the next verb is dropped
+    pub fn age(request: &mut Option<Self>) {
+        *request = request
+            .take()
+            .filter(|r| r.fresh)
+            .map(|r| Self { fresh: false, ..r });
+    }
+}
+
+/// What happened when a copy or move was asked
+#[derive(Debug)]
+pub enum TransferStart {
+    /// the transfers started, some may even be already complete
+    Started(Vec<Transfer>),
+    /// nothing was done, the user must confirm the overwrite
+    Confirm(String),
+    /// nothing was done
+    Failed(String),
+}
+
+/// Check the paths then start copying or moving each source
+/// to its destination.
+///
+/// When a destination is an existing directory, the source goes inside
+/// it. When there are several sources, a missing destination is created
+/// as a directory receiving them.
+/// When final destinations already exist, they're only overwritten after
+/// a confirmation, which the request stores until the next verb.
+///
+/// The root of the displayed tree, and its ancestors, are never moved
+/// nor overwritten.
+///
+/// The verb name is the one to run again to confirm an overwrite.
+pub fn start(
+    kind: TransferKind,
+    paths: Vec<(PathBuf, PathBuf)>,
+    root: &Path,
+    verb_name: &str,
+    request: &mut Option<OverwriteRequest>,
+) -> TransferStart {
+    if paths.is_empty() {
+        return TransferStart::Failed(format!("nothing to {}", kind.verb()));
+    }
+    let into_dirs = paths.len() > 1;
+    let mut checked_paths = Vec::with_capacity(paths.len());
+    let mut existing_count = 0;
+    for (src, dst) in paths {
+        match check(kind, src, dst, root, into_dirs) {
+            Ok((src, dst, exists)) => {
+                if exists {
+                    existing_count += 1;
+                }
+                checked_paths.push((src, dst));
+            }
+            Err(message) => {
+                return TransferStart::Failed(message);
+            }
+        }
+    }
+    if existing_count > 0 {
+        let confirmed = request
+            .take()
+            .map_or(false, |r| r.paths == checked_paths);
+        if !confirmed {
+            let message = if checked_paths.len() == 1 {
+                format!(
+                    "{} already exists. Run :{verb_name} again to overwrite it",
+                    checked_paths[0].1.to_string_lossy(),
+                )
+            } else {
+                format!(
+                    "{existing_count} destinations already exist. Run :{verb_name} again to overwrite them",
+                )
+            };
+            *request = Some(OverwriteRequest { paths: checked_paths, fresh: true });
+            return TransferStart::Confirm(message);
+        }
+    }
+    let mut transfers = Vec::with_capacity(checked_paths.len());
+    for (src, dst) in checked_paths {
+        match Transfer::new(kind, src, dst) {
+            Ok(transfer) => transfers.push(transfer),
+            Err(e) => {
+                return TransferStart::Failed(format!("Failed to {}: {e}", kind.verb()));
+            }
+        }
+    }
+    TransferStart::Started(transfers)
+}
+
+/// Check the new name then rename the file or directory in its
+/// own directory. Overwriting an existing sibling needs a confirmation,
+/// as for other transfers.
+pub fn rename(
+    src: PathBuf,
+    new_name: &str,
+    root: &Path,
+    request: &mut Option<OverwriteRequest>,
+) -> TransferStart {
+    if new_name.trim().is_empty() {
+        return TransferStart::Failed("The new name can't be empty".to_string());
+    }
+    if new_name.contains('/') || new_name.contains(std::path::MAIN_SEPARATOR) {
+        return TransferStart::Failed(
+            "The new name can't contain a path separator (use :mv to move to another directory)".to_string()
+        );
+    }
+    if new_name == "." || new_name == ".." {
+        return TransferStart::Failed(format!("{new_name:?} isn't a valid name"));
+    }
+    if root.starts_with(&src) {
+        return TransferStart::Failed(format!(
+            "{} can't be renamed: it's the tree root or one of its ancestors",
+            src.to_string_lossy(),
+        ));
+    }
+    let Some(parent) = src.parent() else {
+        return TransferStart::Failed("Can't rename a path without parent".to_string());
+    };
+    let dst = parent.join(new_name);
+    if dst == src {
+        return TransferStart::Failed(format!("It's already named {new_name:?}"));
+    }
+    // on case insensitive file systems, a change of case makes dst
+    // look like an existing file while it's the source
+    let is_src = || fs::canonicalize(&src).ok() == fs::canonicalize(&dst).ok();
+    if fs::symlink_metadata(&dst).is_ok() && !is_src() {
+        let paths = vec![(src.clone(), dst.clone())];
+        let confirmed = request.take().map_or(false, |r| r.paths == paths);
+        if !confirmed {
+            *request = Some(OverwriteRequest { paths, fresh: true });
+            return TransferStart::Confirm(format!(
+                "{new_name:?} already exists. Run :rename again to overwrite it",
+            ));
+        }
+    }
+    match Transfer::new(TransferKind::Move, src, dst) {
+        Ok(transfer) => TransferStart::Started(vec![transfer]),
+        Err(e) => TransferStart::Failed(format!("Failed to rename: {e}")),
+    }
+}
+
+/// Check a transfer is possible, and return the source, the
+/// final destination and whether it already exists
+fn check(
+    kind: TransferKind,
+    src: PathBuf,
+    mut dst: PathBuf,
+    root: &Path,
+    into_dir: bool,
+) -> Result<(PathBuf, PathBuf, bool), String> {
+    let Ok(src_metadata) = fs::symlink_metadata(&src) else {
+        return Err(format!("{} not found", src.to_string_lossy()));
+    };
+    if (dst.is_dir() || into_dir) && dst != src {
+        if let Some(name) = src.file_name() {
+            dst = dst.join(name);
+        }
+    }
+    if dst == src {
+        return Err("The source and the destination are the same".to_string());
+    }
+    if src_metadata.is_dir() && dst.starts_with(&src) {
+        return Err(format!("Can't {} a directory into itself", kind.verb()));
+    }
+    if kind == TransferKind::Move && root.starts_with(&src) {
+        return Err(format!(
+            "{} can't be moved: it's the tree root or one of its ancestors",
+            src.to_string_lossy(),
+        ));
+    }
+    let exists = fs::symlink_metadata(&dst).is_ok();
+    if exists && root.starts_with(&dst) {
+        return Err(format!(
+            "{} can't be overwritten: it's the tree root or one of its ancestors",
+            dst.to_string_lossy(),
+        ));
+    }
+    Ok((src, dst, exists))
+}
+
+/// A copy, or a move, which may be done in several steps
+/// so that big directories don't freeze the application.
+///
+/// Moves are renames, unless the destination is on another device,
+/// in which case the source is copied, then removed.
+/// Links are copied as links.
+///
+/// An existing destination is only replaced once the transfer is
+/// complete: the source is first written to a temporary sibling.
+#[derive(Debug)]
+pub struct Transfer {
+    pub kind: TransferKind,
+    pub src: PathBuf,
+    pub dst: PathBuf,
+    /// where the source is written: the destination, or a temporary
+    /// path next to it when the destination already exists
+    target: PathBuf,
+    /// the (source, destination) pairs still to copy
+    todo: Vec<(PathBuf, PathBuf)>,
+    /// the created directories, whose permissions are set at the end
+    /// so that read-only directories can be filled
+    dirs: Vec<(PathBuf, fs::Permissions)>,
+    /// whether the source must be removed once copied
+    remove_src: bool,
+    /// the number of files and directories already copied
+    pub done_count: usize,
+}
+
+impl Transfer {
+    /// Prepare the transfer, creating the parent directories of the
+    /// destination. A move is immediately done when a rename is possible.
+    pub fn new(
+        kind: TransferKind,
+        src: PathBuf,
+        dst: PathBuf,
+    ) -> io::Result<Self> {
+        if let Some(parent) = dst.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let target = if fs::symlink_metadata(&dst).is_ok() {
+            temp_sibling(&dst)
+        } else {
+            dst.clone()
+        };
+        let mut todo = vec![(src.clone(), target.clone())];
+        let mut remove_src = false;
+        if kind == TransferKind::Move {
+            match fs::rename(&src, &target) {
+                Ok(()) => {
+                    todo.clear();
+                }
+                Err(e) if is_cross_device(&e) => {
+                    remove_src = true;
+                }
+                Err(e) => {
+                    return Err(e);
+                }
+            }
+        }
+        Ok(Self {
+            kind,
+            src,
+            dst,
+            target,
+            todo,
+            dirs: Vec::new(),
+            remove_src,
+            done_count: 0,
+        })
+    }
+
+    /// Go on with the transfer until it's complete, the dam has an
+    /// event, or the step lasted long enough. Return true when the
+    /// transfer is complete.
+    ///
+    /// If a copy fails, the partial copy is removed, and an existing
+    /// destination is left untouched.
+    pub fn step(&mut self, dam: &Dam) -> io::Result<bool> {
+        let start = Instant::now();
+        while let Some((src, dst)) = self.todo.pop() {
+            if let Err(e) = self.copy_entry(&src, &dst) {
+                self.todo.clear();
+                let _ = remove_path(&self.target);
+                return Err(io::Error::new(
+                    e.kind(),
+                    format!("{}: {e}", src.to_string_lossy()),
+                ));
+            }
+            self.done_count += 1;
+            if !self.todo.is_empty() && (dam.has_event() || start.elapsed() > STEP_DURATION) {
+                return Ok(false);
+            }
+        }
+        for (dir, permissions) in self.dirs.drain(..).rev() {
+            fs::set_permissions(dir, permissions)?;
+        }
+        let io_error = |(failed_path, e): (PathBuf, io::Error)| io::Error::new(
+            e.kind(),
+            format!("{}: {e}", failed_path.to_string_lossy()),
+        );
+        if self.remove_src {
+            self.remove_src = false;
+            remove_path(&self.src).map_err(io_error)?;
+        }
+        if self.target != self.dst {
+            // the overwritten destination may already be gone, eg when
+            // it was the source with another case
+            if fs::symlink_metadata(&self.dst).is_ok() {
+                remove_path(&self.dst).map_err(io_error)?;
+            }
+            fs::rename(&self.target, &self.dst)?;
+            self.target = self.dst.clone();
+        }
+        Ok(true)
+    }
+
+    /// Do the whole remaining transfer in one go
+    pub fn finish(&mut self) -> io::Result<()> {
+        let dam = Dam::unlimited();
+        while !self.step(&dam)? {}
+        Ok(())
+    }
+
+    fn copy_entry(&mut self, src: &Path, dst: &Path) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(src)?;
+        let file_type = metadata.file_type();
+        if file_type.is_symlink() {
+            copy_link(src, dst)
+        } else if file_type.is_dir() {
+            fs::create_dir(dst)?;
+            self.dirs.push((dst.to_path_buf(), metadata.permissions()));
+            for entry in fs::read_dir(src)? {
+                let entry = entry?;
+                self.todo.push((entry.path(), dst.join(entry.file_name())));
+            }
+            Ok(())
+        } else {
+            fs::copy(src, dst).map(|_| ())
+        }
+    }
+}
+
+/// return a path, in the same directory, where a transfer can be done
+/// before replacing the given destination
+fn temp_sibling(dst: &Path) -> PathBuf {
+    let name = dst.file_name().map_or_else(
+        || "broot".into(),
+        |name| name.to_string_lossy(),
+    );
+    let mut i = 0;
+    loop {
+        let path = dst.with_file_name(format!(".{name}.broot-tmp-{i}"));
+        if fs::symlink_metadata(&path).is_err() {
+            return path;
+        }
+        i += 1;
+    }
+}
+
+#[cfg(unix)]
+fn copy_link(src: &Path, dst: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dst)
+}
+
+#[cfg(not(unix))]
+fn copy_link(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::copy(src, dst).map(|_| ())
+}
+
+fn is_cross_device(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    let code = libc::EXDEV;
+    #[cfg(not(unix))]
+    let code = 17; // ERROR_NOT_SAME_DEVICE
+    e.raw_os_error() == Some(code)
+}
+
+/// Move a file or a directory, in one go
+pub fn move_path(src: &Path, dst: &Path) -> io::Result<()> {
+    let mut transfer = Transfer::new(TransferKind::Move, src.to_path_buf(), dst.to_path_buf())?;
+    transfer.finish()
+}
+
+#[test]
+fn test_copy_and_move_with_confirmation() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    let src = dir.join("src");
+    fs::create_dir_all(src.join("sub")).unwrap();
+    fs::write(src.join("sub/a.txt"), "a").unwrap();
+    fs::write(dir.join("b.txt"), "b").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("sub/a.txt", src.join("link")).unwrap();
+    let mut request = None;
+    let run = |start: TransferStart| match start {
+        TransferStart::Started(mut transfers) => {
+            for transfer in &mut transfers {
+                transfer.finish().unwrap();
+            }
+            transfers
+        }
+        other => panic!("unexpected {other:?}"),
+    };
+    // a directory can't be copied into itself
+    assert!(matches!(
+        start(TransferKind::Copy, vec![(src.clone(), src.join("sub/copy"))], dir, "cp", &mut request),
+        TransferStart::Failed(_),
+    ));
+    // a recursive copy, creating the missing parent
+    let transfers = run(start(TransferKind::Copy, vec![(src.clone(), dir.join("new/copy"))], dir, "cp", &mut request));
+    assert_eq!(fs::read_to_string(dir.join("new/copy/sub/a.txt")).unwrap(), "a");
+    #[cfg(unix)]
+    assert_eq!(fs::read_link(dir.join("new/copy/link")).unwrap(), Path::new("sub/a.txt"));
+    assert!(transfers[0].done_count >= 3);
+    // a move into an existing directory
+    let transfers = run(start(TransferKind::Move, vec![(dir.join("b.txt"), src.clone())], dir, "mv", &mut request));
+    assert_eq!(transfers[0].dst, src.join("b.txt"));
+    assert!(!dir.join("b.txt").exists());
+    // several sources go into a new directory
+    let paths = vec![
+        (src.join("b.txt"), dir.join("both")),
+        (src.join("sub"), dir.join("both")),
+    ];
+    run(start(TransferKind::Copy, paths, dir, "cp", &mut request));
+    assert!(dir.join("both/b.txt").is_file());
+    assert!(dir.join("both/sub/a.txt").is_file());
+    // overwriting needs a confirmation by the next verb
+    fs::write(dir.join("c.txt"), "c").unwrap();
+    let c_to_b = |request: &mut Option<OverwriteRequest>| {
+        start(TransferKind::Copy, vec![(dir.join("c.txt"), src.join("b.txt"))], dir, "cp", request)
+    };
+    assert!(matches!(c_to_b(&mut request), TransferStart::Confirm(_)));
+    OverwriteRequest::age(&mut request);
+    OverwriteRequest::age(&mut request); // another verb came in between
+    assert!(matches!(c_to_b(&mut request), TransferStart::Confirm(_)));
+    OverwriteRequest::age(&mut request);
+    run(c_to_b(&mut request));
+    assert_eq!(fs::read_to_string(src.join("b.txt")).unwrap(), "c");
+    // the destination is only replaced once the copy is done
+    let paths = vec![(src.clone(), dir.join("c.txt"))];
+    assert!(matches!(start(TransferKind::Copy, paths.clone(), dir, "cp", &mut request), TransferStart::Confirm(_)));
+    OverwriteRequest::age(&mut request);
+    let mut transfers = match start(TransferKind::Copy, paths, dir, "cp", &mut request) {
+        TransferStart::Started(transfers) => transfers,
+        other => panic!("unexpected {other:?}"),
+    };
+    assert_eq!(fs::read_to_string(dir.join("c.txt")).unwrap(), "c");
+    transfers[0].finish().unwrap();
+    assert!(dir.join("c.txt/sub/a.txt").is_file());
+    assert!(fs::read_dir(dir).unwrap().all(|e| !e.unwrap().file_name().to_string_lossy().contains("broot-tmp")));
+}
+
+#[test]
+fn test_rename() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    fs::write(dir.join("a.txt"), "a").unwrap();
+    fs::write(dir.join("b.txt"), "b").unwrap();
+    let mut request = None;
+    for bad_name in ["", " ", "sub/c.txt", "..", "a.txt"] {
+        assert!(
+            matches!(rename(dir.join("a.txt"), bad_name, dir, &mut request), TransferStart::Failed(_)),
+            "{bad_name:?} should be rejected",
+        );
+    }
+    assert!(matches!(rename(dir.to_path_buf(), "other", dir, &mut request), TransferStart::Failed(_)));
+    // an existing sibling is only overwritten after confirmation
+    assert!(matches!(rename(dir.join("a.txt"), "b.txt", dir, &mut request), TransferStart::Confirm(_)));
+    OverwriteRequest::age(&mut request);
+    match rename(dir.join("a.txt"), "b.txt", dir, &mut request) {
+        TransferStart::Started(mut transfers) => {
+            assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "b");
+            transfers[0].finish().unwrap();
+        }
+        other => panic!("unexpected {other:?}"),
+    }
+    assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "a");
+    assert!(!dir.join("a.txt").exists());
+    assert!(matches!(rename(dir.join("b.txt"), "c.txt", dir, &mut request), TransferStart::Started(_)));
+    assert!(dir.join("c.txt").is_file());
+}
@@ -4,6 +4,7 @@ use {
     super::*,
     crate::{
         app::*,
+        archive,
         browser::BrowserState,
         command::TriggerType,
         display::Screen,
@@ -42,7 +43,7 @@ pub fn new_state_on_path(
     tree_options: TreeOptions,
     con: &AppContext,
 ) -> CmdResult {
-    let path = path::closest_dir(&path);
+    let path = archive::closest_dir(&path);
     CmdResult::from_optional_state(
         BrowserState::new(path, tree_options, screen, con, &Dam::unlimited()),
         None,
@@ -66,7 +67,7 @@ pub fn new_panel_on_path(
             direction,
         }
     } else {
-        let path = path::closest_dir(&path);
+        let path = archive::closest_dir(&path);
         match BrowserState::new(path, tree_options, screen, con, &Dam::unlimited()) {
             Ok(os) => CmdResult::NewPanel {
                 state: Box::new(os),
@@ -0,0 +1,105 @@
+//! utility functions to help handle the `:mark` and `:go_to_mark` internals
+
+use {
+    super::*,
+    crate::{
+        app::*,
+        browser::BrowserState,
+        task_sync::Dam,
+        tree::{Tree, TreeOptions},
+    },
+};
+
+/// return the char of the mark, either given in input or in
+/// the verb's execution (eg `:go_to_mark a` bound to a key)
+pub fn mark_arg(
+    internal_exec: &InternalExecution,
+    input_invocation: Option<&VerbInvocation>,
+) -> Option<char> {
+    input_invocation
+        .and_then(|inv| inv.args.as_ref())
+        .or(internal_exec.arg.as_ref())
+        .and_then(|arg| arg.parse::<char>().ok())
+}
+
+pub fn on_mark(
+    internal_exec: &InternalExecution,
+    input_invocation: Option<&VerbInvocation>,
+    tree: &Tree,
+    app_state: &mut AppState,
+) -> CmdResult {
+    match mark_arg(internal_exec, input_invocation) {
+        Some(c) => {
+            app_state.marks.set(c, tree.selected_line().path.clone());
+            CmdResult::Keep
+        }
+        None => CmdResult::error("a mark is a char, eg `:mark a`"),
+    }
+}
+
+/// select the marked path if it's in the tree, or display a new
+/// tree containing it, rooted on its parent
+pub fn on_go_to_mark(
+    internal_exec: &InternalExecution,
+    input_invocation: Option<&VerbInvocation>,
+    tree: &mut Tree,
+    tree_options: TreeOptions,
+    app_state: &AppState,
+    cc: &CmdContext,
+) -> CmdResult {
+    let Some(c) = mark_arg(internal_exec, input_invocation) else {
+        return CmdResult::error(marks_markdown(&app_state.marks));
+    };
+    let Some(path) = app_state.marks.get(c) else {
+        return CmdResult::error(format!("no mark {c:?}"));
+    };
+    let page_height = BrowserState::page_height(cc.app.screen);
+    if tree.try_select_path(path) {
+        tree.make_selection_visible(page_height);
+        return CmdResult::Keep;
+    }
+    if !path.exists() {
+        return CmdResult::error(format!("{path:?} doesn't exist anymore"));
+    }
+    let root = path.parent().unwrap_or(path).to_path_buf();
+    let mut new_state = BrowserState::new(
+        root,
+        tree_options,
+        cc.app.screen,
+        cc.app.con,
+        &Dam::unlimited(),
+    );
+    if let Ok(bs) = &mut new_state {
+        if bs.tree.try_select_path(path) {
+            bs.tree.make_selection_visible(page_height);
+        }
+    }
+    let bang = input_invocation
+        .map(|inv| inv.bang)
+        .unwrap_or(internal_exec.bang);
+    CmdResult::from_optional_state(new_state, None, bang)
+}
+
+/// list the existing marks, to help the user choose one
+fn marks_markdown(marks: &Marks) -> String {
+    if marks.is_empty() {
+        return "No mark yet: use `:mark` with a char to mark the selection".to_string();
+    }
+    let list = marks.iter()
+        .map(|(c, path)| format!("*{}* `{}`", c, path.to_string_lossy()))
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("Marks: {list}")
+}
+
+pub fn get_status_markdown(
+    invocation: &VerbInvocation,
+    app_state: &AppState,
+) -> String {
+    let mark = invocation.args.as_ref()
+        .and_then(|arg| arg.parse::<char>().ok());
+    match mark.and_then(|c| app_state.marks.get(c)) {
+        Some(path) => format!("Hit *enter* to go to `{}`", path.to_string_lossy()),
+        None => marks_markdown(&app_state.marks),
+    }
+}
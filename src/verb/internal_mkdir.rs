@@ -0,0 +1,78 @@
+//! utility functions to help handle the `:mkdir` and `:touch` internals
+
+use std::{
+    fs::{self, OpenOptions},
+    path::Path,
+};
+
+/// Create the directory and its missing ancestors.
+///
+/// The error tells which path couldn't be created.
+pub fn create_dir(path: &Path) -> Result<(), String> {
+    if fs::symlink_metadata(path).is_ok() {
+        return Err(format!("{} already exists", path.to_string_lossy()));
+    }
+    fs::create_dir_all(path).map_err(|e| creation_error(path, e))
+}
+
+/// Create an empty file, never truncating an existing one.
+///
+/// Missing parent directories are created only when `create_dirs`
+/// is true.
+pub fn create_file(path: &Path, create_dirs: bool) -> Result<(), String> {
+    if fs::symlink_metadata(path).is_ok() {
+        return Err(format!("{} already exists", path.to_string_lossy()));
+    }
+    if let Some(parent) = path.parent() {
+        if !parent.is_dir() {
+            if !create_dirs {
+                return Err(format!(
+                    "Directory {} doesn't exist (you may set touch_creates_dirs in conf)",
+                    parent.to_string_lossy(),
+                ));
+            }
+            create_dir(parent)?;
+        }
+    }
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map(|_| ())
+        .map_err(|e| creation_error(path, e))
+}
+
+fn creation_error(path: &Path, e: std::io::Error) -> String {
+    // the failing path is the first one which doesn't exist
+    let failed_path = path
+        .ancestors()
+        .take_while(|p| fs::symlink_metadata(p).is_err())
+        .last()
+        .unwrap_or(path);
+    format!("Failed to create {}: {e}", failed_path.to_string_lossy())
+}
+
+#[test]
+fn test_create_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    assert!(create_dir(&dir.join("a/b/c")).is_ok());
+    assert!(dir.join("a/b/c").is_dir());
+    assert!(create_dir(&dir.join("a/b")).unwrap_err().contains("already exists"));
+    fs::write(dir.join("f"), "").unwrap();
+    let error = create_dir(&dir.join("f/g/h")).unwrap_err();
+    assert!(error.starts_with(&format!("Failed to create {}", dir.join("f/g").to_string_lossy())));
+}
+
+#[test]
+fn test_create_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    fs::write(dir.join("f"), "content").unwrap();
+    assert!(create_file(&dir.join("f"), false).unwrap_err().contains("already exists"));
+    assert_eq!(fs::read_to_string(dir.join("f")).unwrap(), "content");
+    assert!(create_file(&dir.join("a/b/new"), false).unwrap_err().contains("doesn't exist"));
+    assert!(!dir.join("a").exists());
+    assert!(create_file(&dir.join("a/b/new"), true).is_ok());
+    assert_eq!(fs::read_to_string(dir.join("a/b/new")).unwrap(), "");
+}
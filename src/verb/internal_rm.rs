@@ -0,0 +1,196 @@
+//! utility functions to help handle the `:rm` and `:rm_permanently` internals
+
+use {
+    crate::trash,
+    std::{
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+};
+
+/// A permanent removal which can't be done without a confirmation,
+/// because it would delete the content of directories, or because
+/// `:rm` was asked but there's no trash.
+///
+/// The confirmation is a new removal of the same paths, which must be
+/// the very next verb (typing it doesn't cancel the request).
+#[derive(Debug)]
+pub struct RemovalRequest {
+    paths: Vec<PathBuf>,
+    fresh: bool, // true until the state executes another verb
+}
+
+impl RemovalRequest {
+    /// To be called before the execution of every verb: a request
+    /// which isn't confirmed by the next verb is dropped
+    pub fn age(request: &mut Option<Self>) {
+        *request = request
+            .take()
+            .filter(|r| r.fresh)
+            .map(|r| Self { fresh: false, ..r });
+    }
+}
+
+/// What happened on a removal
+#[derive(Debug)]
+pub enum Removal {
+    /// all the paths were deleted
+    Done,
+    /// nothing was deleted, the user must confirm
+    Confirm(String),
+    /// nothing was deleted, or only a part (the error tells
+    /// what path couldn't be deleted)
+    Failed(String),
+}
+
+/// Move the given paths to the trash or, when `permanently` is true
+/// or there's no trash, delete them, directories with all their content.
+///
+/// A permanent deletion isn't done unless confirmed when it concerns
+/// directories with content or when the trash was asked. In such a case
+/// the request is stored, for a confirmation by the next verb.
+///
+/// The root of the displayed tree, and its ancestors, are never removed.
+pub fn remove(
+    paths: Vec<PathBuf>,
+    root: &Path,
+    request: &mut Option<RemovalRequest>,
+    permanently: bool,
+) -> Removal {
+    if paths.is_empty() {
+        return Removal::Failed("nothing to delete".to_string());
+    }
+    if let Some(path) = paths.iter().find(|path| root.starts_with(path)) {
+        return Removal::Failed(format!(
+            "{} can't be deleted: it's the tree root or one of its ancestors",
+            path.to_string_lossy(),
+        ));
+    }
+    if !permanently && trash::is_supported() {
+        for path in &paths {
+            if let Err(e) = trash::trash(path) {
+                return Removal::Failed(format!(
+                    "Failed to move {} to the trash: {e}. You may use :rm_permanently",
+                    path.to_string_lossy(),
+                ));
+            }
+        }
+        return Removal::Done;
+    }
+    let confirmed = request.take().map_or(false, |r| r.paths == paths);
+    if !confirmed {
+        let entries_count: usize = paths.iter().map(|p| count_dir_entries(p)).sum();
+        if entries_count > 0 || !permanently {
+            let mut what = if paths.len() == 1 {
+                paths[0].to_string_lossy().to_string()
+            } else {
+                format!("{} paths", paths.len())
+            };
+            if entries_count > 0 {
+                let entries = if entries_count == 1 { "entry" } else { "entries" };
+                what = if paths.len() == 1 {
+                    format!("{what} and its {entries_count} {entries}")
+                } else {
+                    format!("{what} and {entries_count} {entries}")
+                };
+            }
+            *request = Some(RemovalRequest { paths, fresh: true });
+            return Removal::Confirm(if permanently {
+                format!("Run :rm_permanently again to delete {what}")
+            } else {
+                format!("There's no trash here. Run :rm again to permanently delete {what}")
+            });
+        }
+    }
+    for path in &paths {
+        if let Err((failed_path, e)) = remove_path(path) {
+            return Removal::Failed(format!(
+                "Failed to delete {}: {e}",
+                failed_path.to_string_lossy(),
+            ));
+        }
+    }
+    Removal::Done
+}
+
+/// Count the files and directories in the given path, recursively,
+/// without following links (0 if it's not a directory, or a link)
+fn count_dir_entries(path: &Path) -> usize {
+    if !fs::symlink_metadata(path).map_or(false, |md| md.is_dir()) {
+        return 0;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let is_dir = entry.file_type().map_or(false, |ft| ft.is_dir());
+            1 + if is_dir { count_dir_entries(&entry.path()) } else { 0 }
+        })
+        .sum()
+}
+
+/// Delete a file or a directory with all its content, stopping at the
+/// first failure. Links are deleted, not their targets.
+pub fn remove_path(path: &Path) -> Result<(), (PathBuf, io::Error)> {
+    let fail = |e| (path.to_path_buf(), e);
+    let metadata = fs::symlink_metadata(path).map_err(fail)?;
+    if metadata.is_dir() {
+        for entry in fs::read_dir(path).map_err(fail)? {
+            remove_path(&entry.map_err(fail)?.path())?;
+        }
+        fs::remove_dir(path).map_err(fail)
+    } else if is_dir_link(&metadata) {
+        fs::remove_dir(path).map_err(fail)
+    } else {
+        fs::remove_file(path).map_err(fail)
+    }
+}
+
+/// tell whether the metadata are the ones of a link which must be
+/// removed as a directory (this only happens on Windows)
+#[cfg(windows)]
+fn is_dir_link(metadata: &fs::Metadata) -> bool {
+    use std::os::windows::fs::FileTypeExt;
+    metadata.file_type().is_symlink_dir()
+}
+#[cfg(not(windows))]
+fn is_dir_link(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+#[test]
+fn test_remove_permanently_with_confirmation() {
+    let tmp = tempfile::tempdir().unwrap();
+    let dir = tmp.path().to_path_buf();
+    let sub = dir.join("sub");
+    fs::create_dir_all(sub.join("deeper")).unwrap();
+    fs::write(sub.join("a.txt"), "a").unwrap();
+    fs::write(dir.join("b.txt"), "b").unwrap();
+    let mut request = None;
+    // the root can't be deleted
+    assert!(matches!(remove(vec![dir.clone()], &dir, &mut request, true), Removal::Failed(_)));
+    // a file is deleted without confirmation
+    assert!(matches!(remove(vec![dir.join("b.txt")], &dir, &mut request, true), Removal::Done));
+    assert!(!dir.join("b.txt").exists());
+    // a link to a directory is removed without confirmation, not its target
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&sub, dir.join("link")).unwrap();
+        assert!(matches!(remove(vec![dir.join("link")], &dir, &mut request, true), Removal::Done));
+        assert!(sub.join("a.txt").exists());
+    }
+    // a directory with content needs a confirmation by the next verb
+    match remove(vec![sub.clone()], &dir, &mut request, true) {
+        Removal::Confirm(message) => assert!(message.contains("its 2 entries")),
+        other => panic!("unexpected {other:?}"),
+    }
+    RemovalRequest::age(&mut request);
+    RemovalRequest::age(&mut request); // another verb came in between
+    assert!(matches!(remove(vec![sub.clone()], &dir, &mut request, true), Removal::Confirm(_)));
+    RemovalRequest::age(&mut request);
+    assert!(matches!(remove(vec![sub.clone()], &dir, &mut request, true), Removal::Done));
+    assert!(!sub.exists());
+}
@@ -138,7 +138,7 @@ pub fn on_path(
     if in_new_panel {
         warn!("bang in :select isn't supported yet");
     }
-    if tree.try_select_path(&path) {
+    if tree.try_select_path_or_ancestor(&path) {
         tree.make_selection_visible(BrowserState::page_height(screen));
     }
     CmdResult::Keep
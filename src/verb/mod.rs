@@ -5,7 +5,11 @@ mod external_execution;
 mod external_execution_mode;
 mod internal;
 mod internal_execution;
+pub mod internal_cp;
 pub mod internal_focus;
+pub mod internal_mark;
+pub mod internal_mkdir;
+pub mod internal_rm;
 pub mod internal_select;
 mod invocation_parser;
 mod sequence_execution;
@@ -22,7 +26,9 @@ pub use {
     external_execution::ExternalExecution,
     external_execution_mode::ExternalExecutionMode,
     internal::Internal,
+    internal_cp::{OverwriteRequest, Transfer, TransferKind, TransferStart},
     internal_execution::InternalExecution,
+    internal_rm::{Removal, RemovalRequest},
     invocation_parser::InvocationParser,
     once_cell::sync::Lazy,
     sequence_execution::SequenceExecution,
@@ -45,7 +51,7 @@ pub fn str_has_selection_group(s: &str) -> bool {
     GROUP.find_iter(s)
         .any(|group| matches!(
             group.as_str(),
-            "{file}" | "{file-name}" | "{parent}" | "{directory}",
+            "{file}" | "{file-name}" | "{parent}" | "{directory}" | "{files}",
         ))
 }
 pub fn str_has_other_panel_group(s: &str) -> bool {
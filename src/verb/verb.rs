@@ -221,11 +221,11 @@ impl Verb {
     ) -> String {
         let name = self.names.get(0).unwrap_or(&invocation.name);
 
-        // there's one special case: the ̀ :focus` internal. As long
-        // as no other internal takes args, and no other verb can
+        // there are special cases: the ̀ :focus` and `:go_to_mark` internals.
+        // As long as few internals take args, and no other verb can
         // have an optional argument, I don't try to build a
         // generic behavior for internal optionally taking args and
-        // thus I hardcode the test here.
+        // thus I hardcode the tests here.
         if let VerbExecution::Internal(internal_exec) = &self.execution {
             if internal_exec.internal == Internal::focus {
                 return internal_focus::get_status_markdown(
@@ -236,6 +236,9 @@ impl Verb {
                     app_state,
                 );
             }
+            if internal_exec.internal == Internal::go_to_mark {
+                return internal_mark::get_status_markdown(invocation, app_state);
+            }
         }
 
         let builder = || {
@@ -60,6 +60,21 @@ impl VerbInvocation {
         }
         s
     }
+    /// ":12" can be read as ":select_line 12". Return this
+    /// reading when the invocation is made only of digits
+    pub fn as_line_selection(&self) -> Option<Self> {
+        if self.args.is_some()
+            || self.name.is_empty()
+            || !self.name.bytes().all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+        Some(Self {
+            name: "select_line".to_string(),
+            args: Some(self.name.clone()),
+            bang: self.bang,
+        })
+    }
 }
 
 impl From<&str> for VerbInvocation {
@@ -186,4 +201,14 @@ mod verb_invocation_tests {
             VerbInvocation::new("..", Some(".."), false),
         );
     }
+
+    #[test]
+    fn check_verb_invocation_line_number() {
+        assert_eq!(
+            VerbInvocation::from("12").as_line_selection(),
+            Some(VerbInvocation::new("select_line", Some("12"), false)),
+        );
+        assert_eq!(VerbInvocation::from("12 a").as_line_selection(), None);
+        assert_eq!(VerbInvocation::from("1a").as_line_selection(), None);
+    }
 }
@@ -70,6 +70,8 @@ impl VerbStore {
         self.add_internal(line_down).with_key(key!(down)).with_key(key!('j'));
         self.add_internal(line_up).with_key(key!(up)).with_key(key!('k'));
 
+        self.add_internal(select_line);
+        self.add_internal(set_max_depth);
         self.add_internal(set_syntax_theme);
 
         // those two operations are mapped on ALT-ENTER, one
@@ -84,12 +86,6 @@ impl VerbStore {
             .with_shortcut("ol")
             .with_description("change directory and quit");
 
-        #[cfg(unix)]
-        self.add_external("chmod {args}", "chmod {args} {file}", StayInBroot)
-            .with_stype(SelectionType::File);
-        #[cfg(unix)]
-        self.add_external("chmod {args}", "chmod -R {args} {file}", StayInBroot)
-            .with_stype(SelectionType::Directory);
         self.add_internal(open_preview);
         self.add_internal(close_preview);
         self.add_internal(toggle_preview);
@@ -99,42 +95,27 @@ impl VerbStore {
             .with_shortcut("txt");
         self.add_internal(preview_binary)
             .with_shortcut("hex");
+        #[cfg(not(any(target_family = "windows", target_os = "android")))]
+        self.add_internal(chmod);
         self.add_internal(close_panel_ok);
         self.add_internal(close_panel_cancel)
             .with_key(key!(ctrl-w));
-        #[cfg(unix)]
-        self.add_external(
-            "copy {newpath}",
-            "cp -r {file} {newpath:path-from-parent}",
-            StayInBroot,
-        )
-            .with_shortcut("cp");
-        #[cfg(windows)]
-        self.add_external(
-            "copy {newpath}",
-            "xcopy /Q /H /Y /I {file} {newpath:path-from-parent}",
-            StayInBroot,
-        )
-            .with_shortcut("cp");
-        #[cfg(feature = "clipboard")]
+        self.add_internal(cp)
+            .with_shortcut("copy")
+            .with_auto_exec(false);
         self.add_internal(copy_line)
             .with_key(key!(alt-c));
-        #[cfg(feature = "clipboard")]
-        self.add_internal(copy_path);
-        #[cfg(unix)]
-        self.add_external(
-            "copy_to_panel",
-            "cp -r {file} {other-panel-directory}",
-            StayInBroot,
-        )
-            .with_shortcut("cpp");
-        #[cfg(windows)]
-        self.add_external(
-            "copy_to_panel",
-            "xcopy /Q /H /Y /I {file} {other-panel-directory}",
-            StayInBroot,
-        )
+        self.add_internal(copy_path)
             .with_shortcut("cpp");
+        self.add_internal(copy_relative_path)
+            .with_shortcut("cprp");
+        self.add_internal(copy_file_name)
+            .with_shortcut("cpfn");
+        self.add_internal(copy_to_panel)
+            .with_shortcut("cpo")
+            .needing_another_panel();
+        self.add_internal(edit)
+            .with_shortcut("e");
         #[cfg(unix)]
         self.add_internal(filesystems)
             .with_shortcut("fs");
@@ -150,62 +131,18 @@ impl VerbStore {
         #[cfg(feature="clipboard")]
         self.add_internal(input_paste)
             .with_key(key!(ctrl-v));
-        #[cfg(unix)]
-        self.add_external(
-            "mkdir {subpath}",
-            "mkdir -p {subpath:path-from-directory}",
-            StayInBroot,
-        )
-            .with_shortcut("md");
-        #[cfg(windows)]
-        self.add_external(
-            "mkdir {subpath}",
-            "cmd /c mkdir {subpath:path-from-directory}",
-            StayInBroot,
-        )
+        self.add_internal(mkdir)
             .with_shortcut("md");
-        #[cfg(unix)]
-        self.add_external(
-            "move {newpath}",
-            "mv {file} {newpath:path-from-parent}",
-            StayInBroot,
-        )
-            .with_shortcut("mv");
-        #[cfg(windows)]
-        self.add_external(
-            "move {newpath}",
-            "cmd /c move /Y {file} {newpath:path-from-parent}",
-            StayInBroot,
-        )
-            .with_shortcut("mv");
-        #[cfg(unix)]
-        self.add_external(
-            "move_to_panel",
-            "mv {file} {other-panel-directory}",
-            StayInBroot,
-        )
-            .with_shortcut("mvp");
-        #[cfg(windows)]
-        self.add_external(
-            "move_to_panel",
-            "cmd /c move /Y {file} {other-panel-directory}",
-            StayInBroot,
-        )
-            .with_shortcut("mvp");
-        #[cfg(unix)]
-        self.add_external(
-            "rename {new_filename:file-name}",
-            "mv {file} {parent}/{new_filename}",
-            StayInBroot,
-        )
-            .with_auto_exec(false)
-            .with_key(key!(f2));
-        #[cfg(windows)]
-        self.add_external(
-            "rename {new_filename:file-name}",
-            "cmd /c move /Y {file} {parent}/{new_filename}",
-            StayInBroot,
-        )
+        self.add_internal(touch)
+            .with_shortcut("new");
+        self.add_internal(mv)
+            .with_shortcut("move")
+            .with_auto_exec(false);
+        self.add_internal(move_to_panel)
+            .with_shortcut("mvo")
+            .with_shortcut("mvp")
+            .needing_another_panel();
+        self.add_internal(rename)
             .with_auto_exec(false)
             .with_key(key!(f2));
         self.add_internal_bang(start_end_panel)
@@ -221,6 +158,10 @@ impl VerbStore {
             .with_key(key!(backtab));
         self.add_internal(next_match)
             .with_key(key!(tab));
+        self.add_internal(previous_sibling)
+            .with_key(key!(alt-up));
+        self.add_internal(next_sibling)
+            .with_key(key!(alt-down));
         self.add_internal(no_sort)
             .with_shortcut("ns");
         self.add_internal(open_stay)
@@ -232,10 +173,8 @@ impl VerbStore {
             .with_key(key!(h))
             .with_shortcut("p");
         self.add_internal(page_down)
-            .with_key(key!(ctrl-d))
             .with_key(key!(pagedown));
         self.add_internal(page_up)
-            .with_key(key!(ctrl-u))
             .with_key(key!(pageup));
         self.add_internal(panel_left_no_open)
             .with_key(key!(ctrl-left));
@@ -249,12 +188,27 @@ impl VerbStore {
             .with_key(key!(ctrl-q))
             .with_shortcut("q");
         self.add_internal(refresh).with_key(key!(f5));
+        self.add_internal(scroll_down)
+            .with_key(key!(ctrl-d));
+        self.add_internal(scroll_up)
+            .with_key(key!(ctrl-u));
+        self.add_internal(soft_refresh);
         self.add_internal(root_up)
             .with_key(key!(ctrl-up));
         self.add_internal(root_down)
             .with_key(key!(ctrl-down));
-        self.add_internal(select_first);
-        self.add_internal(select_last);
+        // home and end move the cursor in the input
+        self.add_internal(select_first)
+            .with_key(key!(ctrl-home));
+        self.add_internal(select_last)
+            .with_key(key!(ctrl-end));
+        self.add_internal(mark);
+        self.add_internal(go_to_mark);
+        self.add_internal(select_parent);
+        self.add_internal(selection_back)
+            .with_key(key!(alt-left));
+        self.add_internal(selection_forward)
+            .with_key(key!(alt-right));
         self.add_internal(select);
         self.add_internal(clear_stage).with_shortcut("cls");
         self.add_internal(stage)
@@ -270,35 +224,47 @@ impl VerbStore {
         self.add_internal(toggle_staging_area).with_shortcut("tsa");
         self.add_internal(sort_by_count).with_shortcut("sc");
         self.add_internal(sort_by_date).with_shortcut("sd");
+        self.add_internal(sort_by_date_in_tree).with_shortcut("sdt");
         self.add_internal(sort_by_size).with_shortcut("ss");
+        self.add_internal(sort_by_size_in_tree).with_shortcut("sst");
         self.add_internal(sort_by_type).with_shortcut("st");
-        #[cfg(unix)]
-        self.add_external("rm", "rm -rf {file}", StayInBroot);
-        #[cfg(windows)]
-        self.add_external("rm", "cmd /c rmdir /Q /S {file}", StayInBroot)
-            .with_stype(SelectionType::Directory);
-        #[cfg(windows)]
-        self.add_external("rm", "cmd /c del /Q {file}", StayInBroot)
-            .with_stype(SelectionType::File);
+        self.add_internal(rm);
+        self.add_internal(rm_permanently);
         self.add_internal(toggle_counts).with_shortcut("counts");
         self.add_internal(toggle_dates).with_shortcut("dates");
         self.add_internal(toggle_device_id).with_shortcut("dev");
+        self.add_internal(toggle_dirs_first).with_shortcut("df");
+        self.add_internal(expand_dir)
+            .with_key(key!(alt-x));
+        self.add_internal(toggle_expansion)
+            .with_key(key!(alt-e));
         self.add_internal(toggle_files).with_shortcut("files");
+        self.add_internal(toggle_flat).with_shortcut("flat");
         self.add_internal(toggle_git_ignore)
             .with_key(key!(alt-i))
             .with_shortcut("gi");
         self.add_internal(toggle_git_file_info).with_shortcut("gf");
         self.add_internal(toggle_git_status).with_shortcut("gs");
+        self.add_internal(toggle_line_numbers).with_shortcut("ln");
         self.add_internal(toggle_root_fs).with_shortcut("rfs");
         self.add_internal(toggle_hidden)
             .with_key(key!(alt-h))
             .with_shortcut("h");
         #[cfg(unix)]
+        self.add_internal(toggle_hard_links).with_shortcut("links");
+        self.add_internal(toggle_one_file_system).with_shortcut("ofs");
+        #[cfg(unix)]
         self.add_internal(toggle_perm).with_shortcut("perm");
         self.add_internal(toggle_sizes).with_shortcut("sizes");
         self.add_internal(toggle_trim_root);
+        self.add_internal(toggle_mouse_capture).with_shortcut("mouse");
+        self.add_internal(toggle_watch).with_shortcut("watch");
         self.add_internal(total_search).with_key(key!(ctrl-s));
-        self.add_internal(up_tree).with_shortcut("up");
+        // backspace is only a verb key when the input is empty
+        // and the root is selected
+        self.add_internal(up_tree)
+            .with_key(key!(backspace))
+            .with_shortcut("up");
     }
 
     fn build_add_internal(
@@ -391,6 +357,9 @@ impl VerbStore {
             if let Some(b) = vc.switch_terminal {
                 external_execution.switch_terminal = b;
             }
+            if let Some(b) = vc.refresh_after {
+                external_execution.refresh_after = b;
+            }
             external_execution
         };
         let execution = match (execution, internal, external, cmd) {
@@ -500,6 +469,19 @@ impl VerbStore {
         self.search(prefix, None, None, None)
     }
 
+    /// Return the ":select_line 12" reading of ":12", unless
+    /// a verb name starts with "12"
+    pub fn line_selection(
+        &self,
+        invocation: &VerbInvocation,
+    ) -> Option<VerbInvocation> {
+        let line_selection = invocation.as_line_selection()?;
+        match self.search_prefix(&invocation.name) {
+            PrefixSearchResult::NoMatch => Some(line_selection),
+            _ => None,
+        }
+    }
+
     /// Return either the only match, or None if there's not
     /// exactly one match
     pub fn search_sel_info_unique <'v>(